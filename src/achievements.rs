@@ -0,0 +1,539 @@
+use crate::models::global_enums::{Activity, Boss, ComputedMetricEnum, Metric, Skill};
+use crate::models::player::{AchievementMeasure, SnapShotData};
+use std::sync::OnceLock;
+use strum::IntoEnumIterator;
+
+/// Experience needed for level 99
+pub const MAX_LEVEL_EXPERIENCE: i64 = 13_034_431;
+
+/// Experience for levels 60, 70, 80 and 90, the base stats achievements
+const BASE_STATS_EXPERIENCE: [(i64, &str); 4] = [
+    (273_742, "Base 60 Stats"),
+    (737_627, "Base 70 Stats"),
+    (1_986_068, "Base 80 Stats"),
+    (5_346_332, "Base 90 Stats"),
+];
+
+/// The combat level of a player with every combat skill at 99
+const MAX_COMBAT_LEVEL: i64 = 126;
+
+/// Measured by combat level rather than experience
+const MAXED_COMBAT: &str = "Maxed combat";
+
+/// One of Wise Old Man's achievements
+#[derive(Debug, Clone, PartialEq)]
+pub struct AchievementDefinition {
+    pub name: String,
+    pub metric: Metric,
+    pub measure: AchievementMeasure,
+    pub threshold: i64,
+    /// The threshold of the achievement before this one for the same metric and measure, or 0.
+    /// Relative progress is measured from here
+    pub previous_threshold: i64,
+}
+
+/// Every achievement Wise Old Man awards, lower thresholds first for each metric
+pub fn definitions() -> &'static [AchievementDefinition] {
+    static DEFINITIONS: OnceLock<Vec<AchievementDefinition>> = OnceLock::new();
+    DEFINITIONS.get_or_init(|| {
+        let mut definitions = vec![];
+        let overall = Metric::Skill(Skill::Overall);
+        tier(
+            &mut definitions,
+            &overall,
+            AchievementMeasure::Experience,
+            &[500_000_000, 1_000_000_000, 2_000_000_000, 4_600_000_000],
+            |threshold| format!("{} Overall Exp.", short_number(threshold)),
+        );
+        let mut base_stats: Vec<i64> = BASE_STATS_EXPERIENCE.iter().map(|(xp, _)| *xp).collect();
+        base_stats.push(MAX_LEVEL_EXPERIENCE);
+        tier(
+            &mut definitions,
+            &overall,
+            AchievementMeasure::Levels,
+            &base_stats,
+            |threshold| {
+                BASE_STATS_EXPERIENCE
+                    .iter()
+                    .find(|(xp, _)| *xp == threshold)
+                    .map_or("Maxed Overall".to_string(), |(_, name)| name.to_string())
+            },
+        );
+        tier(
+            &mut definitions,
+            &overall,
+            AchievementMeasure::Levels,
+            &[MAX_COMBAT_LEVEL],
+            |_| MAXED_COMBAT.to_string(),
+        );
+        for skill in Skill::iter().filter(|skill| !matches!(skill, Skill::None | Skill::Overall)) {
+            let name = skill.to_string();
+            tier(
+                &mut definitions,
+                &Metric::Skill(skill),
+                AchievementMeasure::Experience,
+                &[MAX_LEVEL_EXPERIENCE, 50_000_000, 100_000_000, 200_000_000],
+                |threshold| format!("{} {}", short_number(threshold), name),
+            );
+        }
+        for boss in Boss::iter().filter(|boss| boss != &Boss::None) {
+            let name = boss_name(&boss);
+            tier(
+                &mut definitions,
+                &Metric::Boss(boss),
+                AchievementMeasure::Kills,
+                &[500, 1_000, 5_000, 10_000],
+                |threshold| format!("{} {} kills", short_number(threshold), name),
+            );
+        }
+        for activity in Activity::iter().filter(|activity| activity != &Activity::None) {
+            let name = activity_name(&activity);
+            let thresholds: &[i64] = match activity {
+                Activity::ClueScrollsAll
+                | Activity::ClueScrollsBeginner
+                | Activity::ClueScrollsEasy
+                | Activity::ClueScrollsMedium
+                | Activity::ClueScrollsHard
+                | Activity::ClueScrollsElite
+                | Activity::ClueScrollsMaster => &[100, 1_000, 5_000],
+                _ => &[1_000, 5_000, 10_000],
+            };
+            tier(
+                &mut definitions,
+                &Metric::Activity(activity),
+                AchievementMeasure::Score,
+                thresholds,
+                |threshold| format!("{} {} score", short_number(threshold), name),
+            );
+        }
+        for computed in ComputedMetricEnum::iter().filter(|c| c != &ComputedMetricEnum::None) {
+            let name = computed_name(&computed);
+            tier(
+                &mut definitions,
+                &Metric::ComputedMetric(computed),
+                AchievementMeasure::Value,
+                &[100, 300, 500, 1_000, 5_000, 10_000],
+                |threshold| format!("{} {}", short_number(threshold), name),
+            );
+        }
+        definitions
+    })
+}
+
+/// Bosses as Wise Old Man names them in achievements
+fn boss_name(boss: &Boss) -> &'static str {
+    match boss {
+        Boss::None => "",
+        Boss::AbyssalSire => "Abyssal Sire",
+        Boss::AlchemicalHydra => "Alchemical Hydra",
+        Boss::Artio => "Artio",
+        Boss::BarrowsChests => "Barrows Chests",
+        Boss::Bryophyta => "Bryophyta",
+        Boss::Callisto => "Callisto",
+        Boss::Calvarion => "Calvar'ion",
+        Boss::Cerberus => "Cerberus",
+        Boss::ChambersOfXeric => "Chambers Of Xeric",
+        Boss::ChambersOfXericChallengeMode => "Chambers Of Xeric (CM)",
+        Boss::ChaosElemental => "Chaos Elemental",
+        Boss::ChaosFanatic => "Chaos Fanatic",
+        Boss::CommanderZilyana => "Commander Zilyana",
+        Boss::CorporealBeast => "Corporeal Beast",
+        Boss::CrazyArchaeologist => "Crazy Archaeologist",
+        Boss::DagannothPrime => "Dagannoth Prime",
+        Boss::DagannothRex => "Dagannoth Rex",
+        Boss::DagannothSupreme => "Dagannoth Supreme",
+        Boss::DerangedArchaeologist => "Deranged Archaeologist",
+        Boss::DukeSucellus => "Duke Sucellus",
+        Boss::GeneralGraardor => "General Graardor",
+        Boss::GiantMole => "Giant Mole",
+        Boss::GrotesqueGuardians => "Grotesque Guardians",
+        Boss::Hespori => "Hespori",
+        Boss::KalphiteQueen => "Kalphite Queen",
+        Boss::KingBlackDragon => "King Black Dragon",
+        Boss::Kraken => "Kraken",
+        Boss::Kreearra => "Kree'Arra",
+        Boss::KrilTsutsaroth => "K'ril Tsutsaroth",
+        Boss::Mimic => "Mimic",
+        Boss::Nex => "Nex",
+        Boss::Nightmare => "Nightmare",
+        Boss::PhosanisNightmare => "Phosani's Nightmare",
+        Boss::Obor => "Obor",
+        Boss::PhantomMuspah => "Phantom Muspah",
+        Boss::Sarachnis => "Sarachnis",
+        Boss::Scorpia => "Scorpia",
+        Boss::Scurrius => "Scurrius",
+        Boss::Skotizo => "Skotizo",
+        Boss::Spindel => "Spindel",
+        Boss::Tempoross => "Tempoross",
+        Boss::TheGauntlet => "The Gauntlet",
+        Boss::TheCorruptedGauntlet => "The Corrupted Gauntlet",
+        Boss::TheLeviathan => "The Leviathan",
+        Boss::TheWhisperer => "The Whisperer",
+        Boss::TheatreOfBlood => "Theatre Of Blood",
+        Boss::TheatreOfBloodHardMode => "Theatre Of Blood (HM)",
+        Boss::ThermonuclearSmokeDevil => "Thermonuclear Smoke Devil",
+        Boss::TombsOfAmascut => "Tombs of Amascut",
+        Boss::TombsOfAmascutExpert => "Tombs of Amascut (Expert Mode)",
+        Boss::TzkalZuk => "TzKal-Zuk",
+        Boss::TztokJad => "TzTok-Jad",
+        Boss::Vardorvis => "Vardorvis",
+        Boss::Venenatis => "Venenatis",
+        Boss::Vetion => "Vet'ion",
+        Boss::Vorkath => "Vorkath",
+        Boss::Wintertodt => "Wintertodt",
+        Boss::Zalcano => "Zalcano",
+        Boss::Zulrah => "Zulrah",
+    }
+}
+
+/// Activities as Wise Old Man names them in achievements
+fn activity_name(activity: &Activity) -> &'static str {
+    match activity {
+        Activity::None => "",
+        Activity::LeaguePoints => "League Points",
+        Activity::BountyHunterHunter => "Bounty Hunter (Hunter)",
+        Activity::BountyHunterRogue => "Bounty Hunter (Rogue)",
+        Activity::ClueScrollsAll => "Clue Scrolls (All)",
+        Activity::ClueScrollsBeginner => "Clue Scrolls (Beginner)",
+        Activity::ClueScrollsEasy => "Clue Scrolls (Easy)",
+        Activity::ClueScrollsMedium => "Clue Scrolls (Medium)",
+        Activity::ClueScrollsHard => "Clue Scrolls (Hard)",
+        Activity::ClueScrollsElite => "Clue Scrolls (Elite)",
+        Activity::ClueScrollsMaster => "Clue Scrolls (Master)",
+        Activity::LastManStanding => "Last Man Standing",
+        Activity::PvpArena => "PvP Arena",
+        Activity::SoulWarsZeal => "Soul Wars Zeal",
+        Activity::GuardiansOfTheRift => "Guardians of the Rift",
+    }
+}
+
+/// Computed metrics as Wise Old Man names them in achievements
+fn computed_name(computed: &ComputedMetricEnum) -> &'static str {
+    match computed {
+        ComputedMetricEnum::None => "",
+        ComputedMetricEnum::Ehp => "EHP",
+        ComputedMetricEnum::Ehb => "EHB",
+    }
+}
+
+fn tier(
+    definitions: &mut Vec<AchievementDefinition>,
+    metric: &Metric,
+    measure: AchievementMeasure,
+    thresholds: &[i64],
+    name: impl Fn(i64) -> String,
+) {
+    let mut previous_threshold = 0;
+    for threshold in thresholds {
+        definitions.push(AchievementDefinition {
+            name: name(*threshold),
+            metric: metric.clone(),
+            measure: measure.clone(),
+            threshold: *threshold,
+            previous_threshold,
+        });
+        previous_threshold = *threshold;
+    }
+}
+
+/// Thresholds as they appear in achievement names: 99, 500, 1k, 50m, 4.6b
+fn short_number(threshold: i64) -> String {
+    match threshold {
+        MAX_LEVEL_EXPERIENCE => "99".to_string(),
+        n if n >= 1_000_000_000 => format!("{}b", n as f64 / 1_000_000_000.0),
+        n if n >= 1_000_000 => format!("{}m", n as f64 / 1_000_000.0),
+        n if n >= 1_000 => format!("{}k", n as f64 / 1_000.0),
+        n => n.to_string(),
+    }
+}
+
+/// A player's standing towards one achievement
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluatedAchievement {
+    pub definition: &'static AchievementDefinition,
+    /// Unranked values count as 0
+    pub current_value: i64,
+    /// From 0 to the threshold, between 0 and 1
+    pub absolute_progress: f64,
+    /// From the previous threshold to this one, between 0 and 1
+    pub relative_progress: f64,
+}
+
+impl EvaluatedAchievement {
+    pub fn is_achieved(&self) -> bool {
+        self.current_value >= self.definition.threshold
+    }
+
+    /// Whether this is the next achievement to get for its metric and measure
+    pub fn is_next(&self) -> bool {
+        !self.is_achieved() && self.current_value >= self.definition.previous_threshold
+    }
+}
+
+/// Every achievement evaluated against one snapshot
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AchievementReport {
+    pub achieved: Vec<EvaluatedAchievement>,
+    /// Not achieved yet but with some progress, in definition order
+    pub in_progress: Vec<EvaluatedAchievement>,
+    /// The next achievement for each metric and measure, and for Maxed combat, closest to
+    /// done first
+    pub next: Vec<EvaluatedAchievement>,
+}
+
+/// Works out achievement progress from snapshot data without calling the api.
+/// Progress values are rounded to 4 decimal places like the api's
+pub fn evaluate(data: &SnapShotData) -> AchievementReport {
+    let mut report = AchievementReport::default();
+    for achievement in definitions()
+        .iter()
+        .map(|definition| evaluate_one(definition, data))
+    {
+        if achievement.is_achieved() {
+            report.achieved.push(achievement);
+            continue;
+        }
+        if achievement.is_next() {
+            report.next.push(achievement.clone());
+        }
+        if achievement.absolute_progress > 0.0 {
+            report.in_progress.push(achievement);
+        }
+    }
+    report
+        .next
+        .sort_by(|a, b| b.relative_progress.total_cmp(&a.relative_progress));
+    report
+}
+
+/// Progress towards a single achievement
+pub fn evaluate_one(
+    definition: &'static AchievementDefinition,
+    data: &SnapShotData,
+) -> EvaluatedAchievement {
+    let current_value = current_value(definition, data).max(0);
+    let progress = |from: i64| {
+        let progress = (current_value - from) as f64 / (definition.threshold - from) as f64;
+        (progress.clamp(0.0, 1.0) * 10_000.0).round() / 10_000.0
+    };
+    EvaluatedAchievement {
+        definition,
+        current_value,
+        absolute_progress: progress(0),
+        relative_progress: progress(definition.previous_threshold),
+    }
+}
+
+/// Base stats are measured by the lowest experience of any skill
+fn current_value(definition: &AchievementDefinition, data: &SnapShotData) -> i64 {
+    match definition.measure {
+        AchievementMeasure::Levels if definition.name == MAXED_COMBAT => combat_level(data),
+        AchievementMeasure::Levels => data
+            .skills
+            .iter()
+            .filter(|skill| skill.metric != Skill::Overall)
+            .map(|skill| skill.experience)
+            .min()
+            .unwrap_or_default(),
+        _ => data
+            .value(&definition.metric)
+            .map(|value| value.floor() as i64)
+            .unwrap_or_default(),
+    }
+}
+
+/// The combat level from the combat skills' levels, as the game works it out
+fn combat_level(data: &SnapShotData) -> i64 {
+    let skills = &data.skills;
+    let base = (skills.defence.level + skills.hitpoints.level + skills.prayer.level / 2) as f64;
+    let melee = (skills.attack.level + skills.strength.level) as f64;
+    let ranged = (skills.ranged.level * 3 / 2) as f64;
+    let magic = (skills.magic.level * 3 / 2) as f64;
+    (base / 4.0 + 0.325 * melee.max(ranged).max(magic)).floor() as i64
+}
+
+/// Ranks players by how close they are to the nearest achievement `filter` accepts that they
+/// do not have yet, for questions like who in a clan is closest to their next 99.
+/// Players who have every accepted achievement are left out
+pub fn closest_to_next<'a, K>(
+    players: impl IntoIterator<Item = (K, &'a SnapShotData)>,
+    filter: impl Fn(&AchievementDefinition) -> bool,
+) -> Vec<(K, EvaluatedAchievement)> {
+    let mut closest: Vec<(K, EvaluatedAchievement)> = players
+        .into_iter()
+        .filter_map(|(player, data)| {
+            definitions()
+                .iter()
+                .filter(|definition| filter(definition))
+                .map(|definition| evaluate_one(definition, data))
+                .filter(|achievement| !achievement.is_achieved())
+                .max_by(|a, b| a.absolute_progress.total_cmp(&b.absolute_progress))
+                .map(|achievement| (player, achievement))
+        })
+        .collect();
+    closest.sort_by(|(_, a), (_, b)| b.absolute_progress.total_cmp(&a.absolute_progress));
+    closest
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::achievements::{
+        closest_to_next, definitions, evaluate, evaluate_one, MAXED_COMBAT, MAX_LEVEL_EXPERIENCE,
+    };
+    use crate::fixtures::fixture;
+    use crate::models::global_enums::{Boss, Metric, Skill};
+    use crate::models::player::{AchievementMeasure, PlayerDetails, SnapShotData};
+    use std::collections::HashSet;
+
+    fn snapshot_data() -> SnapShotData {
+        let snapshots: serde_json::Value = fixture("player/player_snapshots.json");
+        serde_json::from_value(snapshots[0]["data"].clone()).unwrap()
+    }
+
+    #[test]
+    fn definitions_test() {
+        let definitions = definitions();
+        let names: HashSet<_> = definitions.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names.len(), definitions.len());
+        for name in [
+            "99 Agility",
+            "200m Agility",
+            "4.6b Overall Exp.",
+            "Base 70 Stats",
+            "Maxed Overall",
+            "500 Zulrah kills",
+            "10k TzKal-Zuk kills",
+            "1k Kree'Arra kills",
+            "5k Phosani's Nightmare kills",
+            "100 Clue Scrolls (Beginner) score",
+            "Maxed combat",
+            "1k EHB",
+        ] {
+            assert!(names.contains(name), "missing {}", name);
+        }
+
+        let agility: Vec<_> = definitions
+            .iter()
+            .filter(|d| d.metric == Metric::Skill(Skill::Agility))
+            .map(|d| (d.threshold, d.previous_threshold))
+            .collect();
+        assert_eq!(
+            agility,
+            vec![
+                (MAX_LEVEL_EXPERIENCE, 0),
+                (50_000_000, MAX_LEVEL_EXPERIENCE),
+                (100_000_000, 50_000_000),
+                (200_000_000, 100_000_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn evaluate_test() {
+        let mut data = snapshot_data();
+        // The same agility experience as the achievement progress mock
+        data.skills.agility.experience = 738_591;
+        let report = evaluate(&data);
+
+        let find = |list: &[crate::achievements::EvaluatedAchievement], name: &str| {
+            list.iter()
+                .find(|a| a.definition.name == name)
+                .cloned()
+                .unwrap()
+        };
+        let agility_99 = find(&report.next, "99 Agility");
+        assert_eq!(agility_99.absolute_progress, 0.0567);
+        assert_eq!(agility_99.relative_progress, 0.0567);
+        let agility_50m = find(&report.in_progress, "50m Agility");
+        assert_eq!(agility_50m.absolute_progress, 0.0148);
+        assert_eq!(agility_50m.relative_progress, 0.0);
+        assert!(!report
+            .next
+            .iter()
+            .any(|a| a.definition.name == "50m Agility"));
+
+        // The lowest skill is now agility, just above level 70
+        assert!(report
+            .achieved
+            .iter()
+            .any(|a| a.definition.name == "Base 70 Stats"));
+        let base_80 = find(&report.next, "Base 80 Stats");
+        assert_eq!(base_80.definition.measure, AchievementMeasure::Levels);
+        assert_eq!(base_80.current_value, 738_591);
+
+        let kraken = find(&report.next, "5k Kraken kills");
+        assert_eq!(kraken.current_value, 1232);
+        assert!(report
+            .achieved
+            .iter()
+            .any(|a| a.definition.name == "1k Kraken kills"));
+        assert!(report
+            .achieved
+            .iter()
+            .any(|a| a.definition.name == "500 EHP"));
+
+        // One next achievement for each metric and measure, closest first
+        let unique: HashSet<_> = report
+            .next
+            .iter()
+            .map(|a| {
+                let combat = a.definition.name == MAXED_COMBAT;
+                format!(
+                    "{} {:?} {}",
+                    a.definition.metric, a.definition.measure, combat
+                )
+            })
+            .collect();
+        assert_eq!(unique.len(), report.next.len());
+        assert!(report
+            .next
+            .windows(2)
+            .all(|pair| pair[0].relative_progress >= pair[1].relative_progress));
+    }
+
+    #[test]
+    fn maxed_combat_test() {
+        let details: PlayerDetails = fixture("player/player_details.json");
+        let data = &details.latest_snapshot.unwrap().data;
+        let maxed_combat = definitions()
+            .iter()
+            .find(|definition| definition.name == MAXED_COMBAT)
+            .unwrap();
+
+        let achievement = evaluate_one(maxed_combat, data);
+
+        // 94 defence, 98 hitpoints, 80 prayer, 86 attack and 99 strength
+        assert_eq!(achievement.current_value, 118);
+        assert_eq!(achievement.absolute_progress, 0.9365);
+    }
+
+    #[test]
+    fn closest_to_next_test() {
+        let mut alice = snapshot_data();
+        alice.skills.attack.experience = 12_000_000;
+        let mut bob = snapshot_data();
+        bob.bosses.zulrah.kills = 495;
+        let carol = snapshot_data();
+
+        let is_99_or_500_kc = |definition: &crate::achievements::AchievementDefinition| {
+            matches!(
+                (&definition.metric, definition.threshold),
+                (Metric::Skill(_), MAX_LEVEL_EXPERIENCE) | (Metric::Boss(_), 500)
+            ) && definition.measure != AchievementMeasure::Levels
+        };
+        let closest = closest_to_next(
+            [("carol", &carol), ("alice", &alice), ("bob", &bob)],
+            is_99_or_500_kc,
+        );
+
+        let summary: Vec<_> = closest
+            .iter()
+            .map(|(player, achievement)| (*player, achievement.definition.name.as_str()))
+            .collect();
+        assert_eq!(summary[0], ("bob", "500 Zulrah kills"));
+        assert_eq!(summary[1], ("alice", "99 Attack"));
+        assert_eq!(summary[2], ("carol", "99 Hitpoints"));
+        assert_eq!(closest[0].1.definition.metric, Metric::Boss(Boss::Zulrah));
+        assert_eq!(closest[0].1.absolute_progress, 0.99);
+    }
+}
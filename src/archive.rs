@@ -0,0 +1,688 @@
+use crate::models::global_types::PlayerId;
+use crate::models::player::SnapShot;
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+
+/// Starts every archive
+pub const MAGIC: &[u8; 4] = b"WOMS";
+
+/// The layout of records this crate writes. Readers accept this version and every one before it
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Which kind of metric an entry of [METRICS] is, which decides its fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Skill,
+    Boss,
+    Activity,
+    Computed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FieldType {
+    Integer,
+    Float,
+}
+
+impl Kind {
+    /// The key of the metric's group in `SnapShotData`
+    pub(crate) fn group(&self) -> &'static str {
+        match self {
+            Kind::Skill => "skills",
+            Kind::Boss => "bosses",
+            Kind::Activity => "activities",
+            Kind::Computed => "computed",
+        }
+    }
+
+    /// The metric's fields besides `metric`, in the order they are written
+    pub(crate) fn fields(&self) -> &'static [(&'static str, FieldType)] {
+        match self {
+            Kind::Skill => &[
+                ("experience", FieldType::Integer),
+                ("rank", FieldType::Integer),
+                ("level", FieldType::Integer),
+                ("ehp", FieldType::Float),
+            ],
+            Kind::Boss => &[
+                ("kills", FieldType::Integer),
+                ("rank", FieldType::Integer),
+                ("ehb", FieldType::Float),
+            ],
+            Kind::Activity => &[("score", FieldType::Integer), ("rank", FieldType::Integer)],
+            Kind::Computed => &[("value", FieldType::Float), ("rank", FieldType::Integer)],
+        }
+    }
+
+    /// What a metric missing from an older archive decodes to: unranked, as the api
+    /// returns metrics a player has no hiscores entry for
+    pub(crate) fn missing(&self, field: &str) -> Value {
+        match (self, field) {
+            (Kind::Skill, "level") => Value::from(1),
+            (Kind::Skill, "ehp") | (Kind::Boss, "ehb") | (Kind::Computed, "value") => {
+                Value::from(0.0)
+            }
+            _ => Value::from(-1),
+        }
+    }
+}
+
+/// Every metric in the order records hold them. Only ever append to this, as an archive
+/// records how many of them it was written with, so archives written before a metric was
+/// added still decode, and readers built before it skip it. Parquet exports order their
+/// metric columns by it too
+pub(crate) const METRICS: [(Kind, &str); 99] = [
+    (Kind::Skill, "overall"),
+    (Kind::Skill, "attack"),
+    (Kind::Skill, "defence"),
+    (Kind::Skill, "strength"),
+    (Kind::Skill, "hitpoints"),
+    (Kind::Skill, "ranged"),
+    (Kind::Skill, "prayer"),
+    (Kind::Skill, "magic"),
+    (Kind::Skill, "cooking"),
+    (Kind::Skill, "woodcutting"),
+    (Kind::Skill, "fletching"),
+    (Kind::Skill, "fishing"),
+    (Kind::Skill, "firemaking"),
+    (Kind::Skill, "crafting"),
+    (Kind::Skill, "smithing"),
+    (Kind::Skill, "mining"),
+    (Kind::Skill, "herblore"),
+    (Kind::Skill, "agility"),
+    (Kind::Skill, "thieving"),
+    (Kind::Skill, "slayer"),
+    (Kind::Skill, "farming"),
+    (Kind::Skill, "runecrafting"),
+    (Kind::Skill, "hunter"),
+    (Kind::Skill, "construction"),
+    (Kind::Boss, "abyssal_sire"),
+    (Kind::Boss, "alchemical_hydra"),
+    (Kind::Boss, "artio"),
+    (Kind::Boss, "barrows_chests"),
+    (Kind::Boss, "bryophyta"),
+    (Kind::Boss, "callisto"),
+    (Kind::Boss, "calvarion"),
+    (Kind::Boss, "cerberus"),
+    (Kind::Boss, "chambers_of_xeric"),
+    (Kind::Boss, "chambers_of_xeric_challenge_mode"),
+    (Kind::Boss, "chaos_elemental"),
+    (Kind::Boss, "chaos_fanatic"),
+    (Kind::Boss, "commander_zilyana"),
+    (Kind::Boss, "corporeal_beast"),
+    (Kind::Boss, "crazy_archaeologist"),
+    (Kind::Boss, "dagannoth_prime"),
+    (Kind::Boss, "dagannoth_rex"),
+    (Kind::Boss, "dagannoth_supreme"),
+    (Kind::Boss, "deranged_archaeologist"),
+    (Kind::Boss, "duke_sucellus"),
+    (Kind::Boss, "general_graardor"),
+    (Kind::Boss, "giant_mole"),
+    (Kind::Boss, "grotesque_guardians"),
+    (Kind::Boss, "hespori"),
+    (Kind::Boss, "kalphite_queen"),
+    (Kind::Boss, "king_black_dragon"),
+    (Kind::Boss, "kraken"),
+    (Kind::Boss, "kreearra"),
+    (Kind::Boss, "kril_tsutsaroth"),
+    (Kind::Boss, "mimic"),
+    (Kind::Boss, "nex"),
+    (Kind::Boss, "nightmare"),
+    (Kind::Boss, "phosanis_nightmare"),
+    (Kind::Boss, "obor"),
+    (Kind::Boss, "phantom_muspah"),
+    (Kind::Boss, "sarachnis"),
+    (Kind::Boss, "scorpia"),
+    (Kind::Boss, "scurrius"),
+    (Kind::Boss, "skotizo"),
+    (Kind::Boss, "spindel"),
+    (Kind::Boss, "tempoross"),
+    (Kind::Boss, "the_gauntlet"),
+    (Kind::Boss, "the_corrupted_gauntlet"),
+    (Kind::Boss, "the_leviathan"),
+    (Kind::Boss, "the_whisperer"),
+    (Kind::Boss, "theatre_of_blood"),
+    (Kind::Boss, "theatre_of_blood_hard_mode"),
+    (Kind::Boss, "thermonuclear_smoke_devil"),
+    (Kind::Boss, "tombs_of_amascut"),
+    (Kind::Boss, "tombs_of_amascut_expert"),
+    (Kind::Boss, "tzkal_zuk"),
+    (Kind::Boss, "tztok_jad"),
+    (Kind::Boss, "vardorvis"),
+    (Kind::Boss, "venenatis"),
+    (Kind::Boss, "vetion"),
+    (Kind::Boss, "vorkath"),
+    (Kind::Boss, "wintertodt"),
+    (Kind::Boss, "zalcano"),
+    (Kind::Boss, "zulrah"),
+    (Kind::Activity, "league_points"),
+    (Kind::Activity, "bounty_hunter_hunter"),
+    (Kind::Activity, "bounty_hunter_rogue"),
+    (Kind::Activity, "clue_scrolls_all"),
+    (Kind::Activity, "clue_scrolls_beginner"),
+    (Kind::Activity, "clue_scrolls_easy"),
+    (Kind::Activity, "clue_scrolls_medium"),
+    (Kind::Activity, "clue_scrolls_hard"),
+    (Kind::Activity, "clue_scrolls_elite"),
+    (Kind::Activity, "clue_scrolls_master"),
+    (Kind::Activity, "last_man_standing"),
+    (Kind::Activity, "pvp_arena"),
+    (Kind::Activity, "soul_wars_zeal"),
+    (Kind::Activity, "guardians_of_the_rift"),
+    (Kind::Computed, "ehp"),
+    (Kind::Computed, "ehb"),
+];
+
+/// Each metric field's last value for a player, integers as `i64` bits and floats as `f64` bits
+#[derive(Debug, Default)]
+struct PlayerState {
+    id: i64,
+    created_at: i64,
+    fields: Vec<u64>,
+}
+
+/// Writes snapshots to an archive one at a time. Each one is written against the same
+/// player's previous snapshot, so a player's later snapshots take a few bytes for each
+/// metric that changed. Remembers the last snapshot of every player written
+pub struct SnapshotWriter<W: Write> {
+    writer: W,
+    metric_count: usize,
+    players: HashMap<PlayerId, PlayerState>,
+    record: Vec<u8>,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    /// Writes the archive's header
+    pub fn new(writer: W) -> Result<Self, anyhow::Error> {
+        Self::with_metric_count(writer, METRICS.len())
+    }
+
+    /// Writes only the first metrics of the table, as an older version of the crate would have
+    fn with_metric_count(mut writer: W, metric_count: usize) -> Result<Self, anyhow::Error> {
+        let mut header = MAGIC.to_vec();
+        header.push(FORMAT_VERSION);
+        write_varint(&mut header, metric_count as u64);
+        writer.write_all(&header)?;
+        Ok(Self {
+            writer,
+            metric_count,
+            players: HashMap::new(),
+            record: vec![],
+        })
+    }
+
+    /// Fails when the snapshot has a field the format does not hold, so nothing is silently lost
+    pub fn write(&mut self, snapshot: &SnapShot) -> Result<(), anyhow::Error> {
+        let data = serde_json::to_value(&snapshot.data)?;
+        let values = metric_values(&data, self.metric_count)?;
+        let state = self.players.entry(snapshot.player_id).or_default();
+        state.fields.resize(values.len(), 0);
+
+        let record = &mut self.record;
+        record.clear();
+        record.push(u8::from(snapshot.imported_at.is_some()));
+        write_signed(record, snapshot.player_id.0);
+        write_signed(record, snapshot.id.wrapping_sub(state.id));
+        let created_at = snapshot.created_at.timestamp();
+        write_signed(record, created_at.wrapping_sub(state.created_at));
+        write_varint(record, snapshot.created_at.timestamp_subsec_nanos() as u64);
+        if let Some(imported_at) = snapshot.imported_at {
+            write_signed(record, imported_at.timestamp().wrapping_sub(created_at));
+            write_varint(record, imported_at.timestamp_subsec_nanos() as u64);
+        }
+        // Only changed fields are written, each after the count of unchanged ones before it
+        let mut unchanged = 0u64;
+        for (value, (previous, field_type)) in values
+            .iter()
+            .zip(state.fields.iter().zip(field_types(self.metric_count)))
+        {
+            let change = match field_type {
+                FieldType::Integer => zigzag((*value as i64).wrapping_sub(*previous as i64)),
+                FieldType::Float => value ^ previous,
+            };
+            if change == 0 {
+                unchanged += 1;
+                continue;
+            }
+            write_varint(record, unchanged);
+            write_varint(record, change);
+            unchanged = 0;
+        }
+        write_varint(record, unchanged);
+        state.id = snapshot.id;
+        state.created_at = created_at;
+        state.fields = values;
+
+        let mut length = vec![];
+        write_varint(&mut length, record.len() as u64);
+        self.writer.write_all(&length)?;
+        self.writer.write_all(record)?;
+        Ok(())
+    }
+
+    /// Flushes and hands back the underlying writer
+    pub fn finish(mut self) -> Result<W, anyhow::Error> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads snapshots back out of an archive, in the order they were written
+pub struct SnapshotReader<R: Read> {
+    reader: R,
+    version: u8,
+    metric_count: usize,
+    players: HashMap<PlayerId, PlayerState>,
+}
+
+impl<R: Read> SnapshotReader<R> {
+    /// Reads the archive's header
+    pub fn new(mut reader: R) -> Result<Self, anyhow::Error> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            bail!("Not a snapshot archive");
+        }
+        let mut version = [0u8; 1];
+        reader.read_exact(&mut version)?;
+        let version = version[0];
+        if version == 0 || version > FORMAT_VERSION {
+            bail!(
+                "Snapshot archive version {} is not supported, the newest supported is {}",
+                version,
+                FORMAT_VERSION
+            );
+        }
+        let metric_count = read_varint(&mut reader)?
+            .ok_or_else(|| anyhow!("Snapshot archive header is cut short"))?
+            as usize;
+        Ok(Self {
+            reader,
+            version,
+            metric_count,
+            players: HashMap::new(),
+        })
+    }
+
+    /// The format version the archive was written with
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// How many metrics the archive's records hold, which may be more or fewer than this
+    /// version of the crate knows
+    pub fn metric_count(&self) -> usize {
+        self.metric_count
+    }
+
+    fn read_record(&mut self) -> Result<Option<SnapShot>, anyhow::Error> {
+        let Some(length) = read_varint(&mut self.reader)? else {
+            return Ok(None);
+        };
+        // Read through `take` so a corrupt length fails instead of allocating it up front
+        let mut record = vec![];
+        (&mut self.reader).take(length).read_to_end(&mut record)?;
+        if record.len() as u64 != length {
+            bail!("Snapshot archive record is cut short");
+        }
+        let mut cursor = record.as_slice();
+
+        let flags = take_byte(&mut cursor)?;
+        let player_id = PlayerId(take_signed(&mut cursor)?);
+        let state = self.players.entry(player_id).or_default();
+        let id = state.id.wrapping_add(take_signed(&mut cursor)?);
+        let created_seconds = state.created_at.wrapping_add(take_signed(&mut cursor)?);
+        let created_at = timestamp(created_seconds, take_varint(&mut cursor)?)?;
+        let imported_at = if flags & 1 == 1 {
+            let seconds = created_seconds.wrapping_add(take_signed(&mut cursor)?);
+            Some(timestamp(seconds, take_varint(&mut cursor)?)?)
+        } else {
+            None
+        };
+
+        // Metrics this version does not know come last and are skipped with the rest of the record
+        let known = self.metric_count.min(METRICS.len());
+        let types: Vec<FieldType> = field_types(known).collect();
+        state.fields.resize(types.len(), 0);
+        let mut index = 0;
+        while index < types.len() {
+            let skip = take_varint(&mut cursor)?;
+            index = usize::try_from(skip)
+                .ok()
+                .and_then(|skip| index.checked_add(skip))
+                .ok_or_else(|| anyhow!("Snapshot archive record is corrupt"))?;
+            let Some(field_type) = types.get(index) else {
+                break;
+            };
+            let change = take_varint(&mut cursor)?;
+            let previous = &mut state.fields[index];
+            *previous = match field_type {
+                FieldType::Integer => (*previous as i64).wrapping_add(unzigzag(change)) as u64,
+                FieldType::Float => *previous ^ change,
+            };
+            index += 1;
+        }
+        state.id = id;
+        state.created_at = created_seconds;
+
+        let data = snapshot_data(&state.fields);
+        Ok(Some(SnapShot {
+            id,
+            player_id,
+            created_at,
+            imported_at,
+            data: serde_json::from_value(data)?,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for SnapshotReader<R> {
+    type Item = Result<SnapShot, anyhow::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+fn field_types(metric_count: usize) -> impl Iterator<Item = FieldType> {
+    METRICS[..metric_count]
+        .iter()
+        .flat_map(|(kind, _)| kind.fields().iter().map(|(_, field_type)| *field_type))
+}
+
+/// Pulls every metric field out of serialized `SnapShotData`, checking it holds exactly
+/// the metrics and fields the format knows
+fn metric_values(data: &Value, metric_count: usize) -> Result<Vec<u64>, anyhow::Error> {
+    let groups = data
+        .as_object()
+        .ok_or_else(|| anyhow!("Snapshot data is not an object"))?;
+    for (group, metrics) in groups {
+        let known = METRICS
+            .iter()
+            .filter(|(kind, _)| kind.group() == group)
+            .count();
+        let count = metrics.as_object().map_or(0, |metrics| metrics.len());
+        if known == 0 || count != known {
+            bail!(
+                "Snapshot data has metrics in {:?} the archive format does not know",
+                group
+            );
+        }
+    }
+
+    let mut values = vec![];
+    for (kind, key) in &METRICS[..metric_count] {
+        let metric = data[kind.group()][key]
+            .as_object()
+            .ok_or_else(|| anyhow!("Snapshot data is missing {}", key))?;
+        if metric.len() != kind.fields().len() + 1
+            || metric.get("metric") != Some(&Value::from(*key))
+        {
+            bail!(
+                "Snapshot data for {} does not match the archive format",
+                key
+            );
+        }
+        for (field, field_type) in kind.fields() {
+            let value = metric.get(*field);
+            let bits = match field_type {
+                FieldType::Integer => value.and_then(Value::as_i64).map(|value| value as u64),
+                FieldType::Float => value.and_then(Value::as_f64).map(f64::to_bits),
+            };
+            values.push(bits.ok_or_else(|| anyhow!("{}.{} is not a number", key, field))?);
+        }
+    }
+    Ok(values)
+}
+
+/// Rebuilds serialized `SnapShotData`, filling in metrics the archive did not hold
+fn snapshot_data(values: &[u64]) -> Value {
+    let mut values = values.iter();
+    let mut data = Map::new();
+    for (kind, key) in METRICS.iter() {
+        let mut metric = Map::new();
+        metric.insert("metric".to_string(), Value::from(*key));
+        for (field, field_type) in kind.fields() {
+            let value = match (values.next(), field_type) {
+                (Some(bits), FieldType::Integer) => Value::from(*bits as i64),
+                (Some(bits), FieldType::Float) => Value::from(f64::from_bits(*bits)),
+                (None, _) => kind.missing(field),
+            };
+            metric.insert(field.to_string(), value);
+        }
+        data.entry(kind.group())
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .unwrap()
+            .insert(key.to_string(), Value::Object(metric));
+    }
+    Value::Object(data)
+}
+
+fn timestamp(seconds: i64, nanos: u64) -> Result<DateTime<Utc>, anyhow::Error> {
+    DateTime::from_timestamp(seconds, nanos as u32)
+        .ok_or_else(|| anyhow!("Snapshot archive has an invalid timestamp"))
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Maps signed numbers onto unsigned ones so small negative numbers stay small as varints
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+fn write_signed(out: &mut Vec<u8>, value: i64) {
+    write_varint(out, zigzag(value));
+}
+
+/// `None` at the end of the stream, before the first byte
+fn read_varint(reader: &mut impl Read) -> Result<Option<u64>, anyhow::Error> {
+    let mut value = 0u64;
+    for index in 0..10 {
+        let mut byte = [0u8; 1];
+        if let Err(err) = reader.read_exact(&mut byte) {
+            if err.kind() == ErrorKind::UnexpectedEof && index == 0 {
+                return Ok(None);
+            }
+            return Err(err.into());
+        }
+        value |= ((byte[0] & 0x7f) as u64) << (7 * index);
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+    }
+    bail!("Snapshot archive has a number that is too long")
+}
+
+fn take_byte(cursor: &mut &[u8]) -> Result<u8, anyhow::Error> {
+    let (first, rest) = cursor
+        .split_first()
+        .ok_or_else(|| anyhow!("Snapshot archive record is cut short"))?;
+    *cursor = rest;
+    Ok(*first)
+}
+
+fn take_varint(cursor: &mut &[u8]) -> Result<u64, anyhow::Error> {
+    read_varint(cursor)?.ok_or_else(|| anyhow!("Snapshot archive record is cut short"))
+}
+
+fn take_signed(cursor: &mut &[u8]) -> Result<i64, anyhow::Error> {
+    Ok(unzigzag(take_varint(cursor)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::archive::{
+        read_varint, write_varint, SnapshotReader, SnapshotWriter, FORMAT_VERSION, MAGIC, METRICS,
+    };
+    use crate::fixtures::fixture;
+    use crate::models::global_types::PlayerId;
+    use crate::models::player::{PlayerDetails, SnapShot};
+    use chrono::Duration;
+
+    fn zezima() -> SnapShot {
+        let details: PlayerDetails = fixture("player/player_details.json");
+        details.latest_snapshot.unwrap()
+    }
+
+    /// A week of zezima's snapshots, interleaved with another player's
+    fn snapshots() -> Vec<SnapShot> {
+        let mut snapshots = vec![];
+        let mut snapshot = zezima();
+        for day in 0..7 {
+            let mut other = snapshot.clone();
+            other.player_id = PlayerId(7);
+            other.id += 1_000_000;
+            other.data.skills.attack.rank = -1;
+            snapshots.push(snapshot.clone());
+            snapshots.push(other);
+
+            snapshot.id += 17 + day;
+            snapshot.created_at += Duration::hours(23) + Duration::milliseconds(412);
+            snapshot.imported_at = (day % 2 == 0).then_some(snapshot.created_at);
+            snapshot.data.skills.overall.experience += 12_345;
+            snapshot.data.skills.overall.rank -= 30;
+            snapshot.data.skills.slayer.experience += 12_345;
+            snapshot.data.skills.slayer.ehp += 0.4167;
+            snapshot.data.bosses.kraken.kills += 40;
+            snapshot.data.computed.ehp.value += 0.4167;
+        }
+        snapshots
+    }
+
+    fn archive(snapshots: &[SnapShot], writer: SnapshotWriter<Vec<u8>>) -> Vec<u8> {
+        let mut writer = writer;
+        for snapshot in snapshots {
+            writer.write(snapshot).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn read_all(archive: &[u8]) -> Vec<SnapShot> {
+        SnapshotReader::new(archive)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let snapshots = snapshots();
+        let archive = archive(&snapshots, SnapshotWriter::new(vec![]).unwrap());
+
+        assert_eq!(read_all(&archive), snapshots);
+        let json = serde_json::to_vec(&snapshots).unwrap().len();
+        assert!(
+            archive.len() * 10 < json,
+            "{} bytes against {} as json",
+            archive.len(),
+            json
+        );
+
+        // A later snapshot of the same player only costs its changes
+        let first = archive_size(&snapshots[..1]);
+        let second = archive_size(&[snapshots[0].clone(), snapshots[2].clone()]) - first;
+        assert!(second < 64, "{} bytes for the second snapshot", second);
+    }
+
+    fn archive_size(snapshots: &[SnapShot]) -> usize {
+        archive(snapshots, SnapshotWriter::new(vec![]).unwrap()).len()
+    }
+
+    #[test]
+    fn older_archive_test() {
+        // Written before the computed metrics were added
+        let snapshots = snapshots();
+        let archive = archive(
+            &snapshots,
+            SnapshotWriter::with_metric_count(vec![], METRICS.len() - 2).unwrap(),
+        );
+
+        let read = read_all(&archive);
+        assert_eq!(read.len(), snapshots.len());
+        for (read, written) in read.iter().zip(&snapshots) {
+            assert_eq!(read.data.skills, written.data.skills);
+            assert_eq!(read.data.bosses, written.data.bosses);
+            assert_eq!(read.data.computed.ehp.value, 0.0);
+            assert_eq!(read.data.computed.ehb.rank, -1);
+        }
+    }
+
+    #[test]
+    fn newer_archive_test() {
+        let snapshots = snapshots();
+        let archive = archive(&snapshots, SnapshotWriter::new(vec![]).unwrap());
+
+        // Rewrite the archive as if a newer version had appended a metric to every record
+        let mut cursor = &archive[5..];
+        let mut newer = archive[..5].to_vec();
+        let metric_count = read_varint(&mut cursor).unwrap().unwrap();
+        write_varint(&mut newer, metric_count + 1);
+        while let Some(length) = read_varint(&mut cursor).unwrap() {
+            let (record, rest) = cursor.split_at(length as usize);
+            write_varint(&mut newer, length + 2);
+            newer.extend_from_slice(record);
+            newer.extend_from_slice(&[0x84, 0x01]);
+            cursor = rest;
+        }
+
+        let reader = SnapshotReader::new(newer.as_slice()).unwrap();
+        assert_eq!(reader.metric_count(), METRICS.len() + 1);
+        assert_eq!(read_all(&newer), snapshots);
+
+        let mut future = archive.clone();
+        future[4] = FORMAT_VERSION + 1;
+        assert!(SnapshotReader::new(future.as_slice()).is_err());
+        assert!(SnapshotReader::new(&b"{\"id\":1}"[..]).is_err());
+        assert!(SnapshotReader::new(&archive[..archive.len() - 1])
+            .unwrap()
+            .any(|snapshot| snapshot.is_err()));
+    }
+
+    #[test]
+    fn corrupt_length_test() {
+        let archive = archive(&snapshots()[..1], SnapshotWriter::new(vec![]).unwrap());
+        let mut cursor = &archive[5..];
+        let metric_count = read_varint(&mut cursor).unwrap().unwrap();
+        let length = read_varint(&mut cursor).unwrap().unwrap();
+
+        // The same record claiming to be far longer than the archive
+        let mut corrupt = archive[..5].to_vec();
+        write_varint(&mut corrupt, metric_count);
+        write_varint(&mut corrupt, u64::MAX);
+        corrupt.extend_from_slice(&cursor[..length as usize]);
+
+        let mut reader = SnapshotReader::new(corrupt.as_slice()).unwrap();
+        let err = reader.next().unwrap().unwrap_err();
+        assert!(err.to_string().contains("cut short"), "{}", err);
+    }
+
+    #[test]
+    fn corrupt_skip_test() {
+        // Zeroed header fields, the first metric field unchanged, then a skip past usize::MAX
+        let mut record = vec![0, 0, 0, 0, 0, 0, 0];
+        write_varint(&mut record, u64::MAX);
+        let mut corrupt = MAGIC.to_vec();
+        corrupt.push(FORMAT_VERSION);
+        write_varint(&mut corrupt, METRICS.len() as u64);
+        write_varint(&mut corrupt, record.len() as u64);
+        corrupt.extend_from_slice(&record);
+
+        let mut reader = SnapshotReader::new(corrupt.as_slice()).unwrap();
+        let err = reader.next().unwrap().unwrap_err();
+        assert!(err.to_string().contains("corrupt"), "{}", err);
+    }
+}
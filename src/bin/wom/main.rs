@@ -0,0 +1,390 @@
+use crate::output::OutputFormat;
+use chrono::{DateTime, Utc};
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::process::ExitCode;
+use wom_rs::models::competition::CompetitionStatus;
+use wom_rs::models::error::ApiError;
+use wom_rs::models::global_enums::{Metric, Period};
+use wom_rs::models::global_types::{GroupId, InvalidUsername, PlayerId};
+use wom_rs::query::{CompetitionQuery, DateRange, QueryError, RecordQuery};
+use wom_rs::{Pagination, WomClient};
+
+mod output;
+
+/// Exit code used for network errors and any api error without a more specific code
+const EXIT_ERROR: u8 = 1;
+/// Exit code used for invalid arguments, the same code clap uses
+const EXIT_USAGE: u8 = 2;
+/// Exit code used when the api could not find the player or group
+const EXIT_NOT_FOUND: u8 = 3;
+/// Exit code used when the api rejected the request
+const EXIT_BAD_REQUEST: u8 = 4;
+/// Exit code used when the api rate limited the request
+const EXIT_RATE_LIMITED: u8 = 5;
+
+/// Command line access to the Wise Old Man API
+#[derive(Parser)]
+#[command(name = "wom", version, about)]
+struct Cli {
+    /// Wise Old Man API key
+    #[arg(long, env = "WOM_API_KEY", global = true, hide_env_values = true)]
+    api_key: Option<String>,
+
+    /// Base URL of the api, for self hosted/League WOM instances
+    #[arg(long, env = "WOM_BASE_URL", global = true)]
+    base_url: Option<String>,
+
+    /// How the response should be printed
+    #[arg(long, short, value_enum, default_value_t = OutputFormat::Table, global = true)]
+    format: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Player endpoints
+    #[command(subcommand)]
+    Player(PlayerCommand),
+    /// Group endpoints
+    #[command(subcommand)]
+    Group(GroupCommand),
+}
+
+#[derive(Subcommand)]
+enum PlayerCommand {
+    /// Search for players by username
+    Search {
+        username: String,
+        #[command(flatten)]
+        pagination: PaginationArgs,
+    },
+    /// Get a player's details
+    Details { username: String },
+    /// Get a player's details by player id
+    DetailsById { player_id: PlayerId },
+    /// Update a player's hiscore data
+    Update { username: String },
+    /// Assert a player's game-mode type
+    AssertType { username: String },
+    /// Get a player's achievements
+    Achievements { username: String },
+    /// Get a player's achievement progress
+    AchievementProgress { username: String },
+    /// Get a player's competition participations
+    Competitions {
+        username: String,
+        #[arg(long, value_parser = parse_serde::<CompetitionStatus>)]
+        status: Option<CompetitionStatus>,
+        #[command(flatten)]
+        pagination: PaginationArgs,
+    },
+    /// Get a player's competition standings
+    Standings {
+        username: String,
+        #[arg(long, value_parser = parse_serde::<CompetitionStatus>)]
+        status: CompetitionStatus,
+    },
+    /// Get a player's group memberships
+    Groups {
+        username: String,
+        #[command(flatten)]
+        pagination: PaginationArgs,
+    },
+    /// Get a player's gains over a period or date range
+    Gains {
+        username: String,
+        #[command(flatten)]
+        range: RangeArgs,
+    },
+    /// Get a player's snapshots over a period or date range
+    Snapshots {
+        username: String,
+        #[command(flatten)]
+        range: RangeArgs,
+    },
+    /// Get a player's snapshot timeline for a metric over a period or date range
+    Timeline {
+        username: String,
+        #[arg(long, value_parser = parse_serde::<Metric>)]
+        metric: Metric,
+        #[command(flatten)]
+        range: RangeArgs,
+    },
+    /// Get a player's records
+    Records {
+        username: String,
+        #[arg(long, value_parser = parse_serde::<Period>)]
+        period: Option<Period>,
+        #[arg(long, value_parser = parse_serde::<Metric>)]
+        metric: Option<Metric>,
+    },
+    /// Get a player's name changes
+    Names { username: String },
+    /// Get a player's archives
+    Archives { username: String },
+}
+
+#[derive(Subcommand)]
+enum GroupCommand {
+    /// Search for groups by name
+    Search {
+        name: String,
+        #[command(flatten)]
+        pagination: PaginationArgs,
+    },
+    /// Get a group's details
+    Details { group_id: GroupId },
+    /// Get a group's name changes
+    NameChanges {
+        group_id: GroupId,
+        #[command(flatten)]
+        pagination: PaginationArgs,
+    },
+}
+
+#[derive(Args)]
+struct PaginationArgs {
+    #[arg(long)]
+    limit: Option<i32>,
+    #[arg(long)]
+    offset: Option<i32>,
+}
+
+impl PaginationArgs {
+    fn into_pagination(self) -> Option<Pagination> {
+        match (self.limit, self.offset) {
+            (None, None) => None,
+            (limit, offset) => Some(Pagination { limit, offset }),
+        }
+    }
+}
+
+fn competition_query(
+    status: Option<CompetitionStatus>,
+    pagination: PaginationArgs,
+) -> CompetitionQuery {
+    let mut query = CompetitionQuery::new();
+    if let Some(status) = status {
+        query = query.status(status);
+    }
+    if let Some(pagination) = pagination.into_pagination() {
+        query = query.pagination(pagination);
+    }
+    query
+}
+
+/// Either a period or a start and end date, as the api takes one or the other
+#[derive(Args)]
+#[group(required = true, multiple = true)]
+#[command(group(ArgGroup::new("dates").args(["start", "end"]).multiple(true)))]
+struct RangeArgs {
+    #[arg(long, value_parser = parse_serde::<Period>, conflicts_with = "dates")]
+    period: Option<Period>,
+    /// Start date as RFC 3339, e.g. 2024-01-01T00:00:00Z
+    #[arg(long, requires = "end")]
+    start: Option<DateTime<Utc>>,
+    /// End date as RFC 3339, e.g. 2024-01-31T00:00:00Z
+    #[arg(long, requires = "start")]
+    end: Option<DateTime<Utc>>,
+}
+
+/// Parses an argument the same way the api's json is deserialized, so `five_min` or `zulrah` work as is
+fn parse_serde<T: DeserializeOwned>(value: &str) -> Result<T, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|err| err.to_string())
+}
+
+fn to_json<T: Serialize>(result: anyhow::Result<T>) -> anyhow::Result<serde_json::Value> {
+    Ok(serde_json::to_value(result?)?)
+}
+
+async fn run_player(
+    client: &WomClient,
+    command: PlayerCommand,
+) -> anyhow::Result<serde_json::Value> {
+    let player_client = &client.player_client;
+    match command {
+        PlayerCommand::Search {
+            username,
+            pagination,
+        } => to_json(
+            player_client
+                .search(username, pagination.into_pagination())
+                .await,
+        ),
+        PlayerCommand::Details { username } => to_json(player_client.get_details(username).await),
+        PlayerCommand::DetailsById { player_id } => {
+            to_json(player_client.get_details_by_id(player_id).await)
+        }
+        PlayerCommand::Update { username } => to_json(player_client.update(username).await),
+        PlayerCommand::AssertType { username } => {
+            to_json(player_client.assert_type(username).await)
+        }
+        PlayerCommand::Achievements { username } => {
+            to_json(player_client.get_achievements(username).await)
+        }
+        PlayerCommand::AchievementProgress { username } => {
+            to_json(player_client.get_achievement_progress(username).await)
+        }
+        PlayerCommand::Competitions {
+            username,
+            status,
+            pagination,
+        } => to_json(
+            player_client
+                .get_competitions(username, competition_query(status, pagination))
+                .await,
+        ),
+        PlayerCommand::Standings { username, status } => to_json(
+            player_client
+                .get_competition_standings(username, status)
+                .await,
+        ),
+        PlayerCommand::Groups {
+            username,
+            pagination,
+        } => to_json(
+            player_client
+                .get_groups(username, pagination.into_pagination())
+                .await,
+        ),
+        PlayerCommand::Gains { username, range } => match range {
+            RangeArgs {
+                start: Some(start),
+                end: Some(end),
+                ..
+            } => to_json(
+                player_client
+                    .get_gains_by_date(username, DateRange::new(start, end)?)
+                    .await,
+            ),
+            RangeArgs { period, .. } => to_json(
+                player_client
+                    .get_gains_by_period(username, period.unwrap_or(Period::Week))
+                    .await,
+            ),
+        },
+        PlayerCommand::Snapshots { username, range } => match range {
+            RangeArgs {
+                start: Some(start),
+                end: Some(end),
+                ..
+            } => to_json(
+                player_client
+                    .get_snapshots_by_date(username, DateRange::new(start, end)?)
+                    .await,
+            ),
+            RangeArgs { period, .. } => to_json(
+                player_client
+                    .get_snapshots_by_period(username, period.unwrap_or(Period::Week))
+                    .await,
+            ),
+        },
+        PlayerCommand::Timeline {
+            username,
+            metric,
+            range,
+        } => match range {
+            RangeArgs {
+                start: Some(start),
+                end: Some(end),
+                ..
+            } => to_json(
+                player_client
+                    .get_snapshots_timeline_by_date(username, metric, DateRange::new(start, end)?)
+                    .await,
+            ),
+            RangeArgs { period, .. } => to_json(
+                player_client
+                    .get_snapshots_timeline(username, metric, period.unwrap_or(Period::Week))
+                    .await,
+            ),
+        },
+        PlayerCommand::Records {
+            username,
+            period,
+            metric,
+        } => {
+            let mut query = RecordQuery::new();
+            if let Some(period) = period {
+                query = query.period(period);
+            }
+            if let Some(metric) = metric {
+                query = query.metric(metric);
+            }
+            to_json(player_client.get_records(username, query).await)
+        }
+        PlayerCommand::Names { username } => {
+            to_json(player_client.get_name_changes(username).await)
+        }
+        PlayerCommand::Archives { username } => to_json(player_client.get_archives(username).await),
+    }
+}
+
+async fn run_group(client: &WomClient, command: GroupCommand) -> anyhow::Result<serde_json::Value> {
+    let group_client = &client.group_client;
+    match command {
+        GroupCommand::Search { name, pagination } => to_json(
+            group_client
+                .search(name, pagination.into_pagination())
+                .await,
+        ),
+        GroupCommand::Details { group_id } => {
+            to_json(group_client.get_group_details(group_id).await)
+        }
+        GroupCommand::NameChanges {
+            group_id,
+            pagination,
+        } => to_json(
+            group_client
+                .get_group_name_changes(group_id, pagination.into_pagination())
+                .await,
+        ),
+    }
+}
+
+/// Maps the api's status to an exit code so scripts can branch on what went wrong
+fn exit_code(err: &anyhow::Error) -> u8 {
+    if err.downcast_ref::<QueryError>().is_some() || err.downcast_ref::<InvalidUsername>().is_some()
+    {
+        return EXIT_USAGE;
+    }
+    match err.downcast_ref::<ApiError>() {
+        Some(api_error) if api_error.is_not_found() => EXIT_NOT_FOUND,
+        Some(api_error) if api_error.is_bad_request() => EXIT_BAD_REQUEST,
+        Some(api_error) if api_error.is_rate_limited() => EXIT_RATE_LIMITED,
+        _ => EXIT_ERROR,
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let client = match cli.base_url {
+        Some(base_url) => WomClient::new_with_base_url(base_url, cli.api_key),
+        None => match cli.api_key {
+            Some(api_key) => WomClient::new_with_key(api_key),
+            None => WomClient::new(),
+        },
+    };
+
+    let result = match cli.command {
+        Command::Player(command) => run_player(&client, command).await,
+        Command::Group(command) => run_group(&client, command).await,
+    };
+
+    match result {
+        Ok(value) => {
+            print!("{}", output::render(&value, cli.format));
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use clap::ValueEnum;
+use serde_json::Value;
+
+/// How results are printed to stdout
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+/// Renders a response in the requested format, with fields sorted by name.
+/// Lists become one row per entry, single objects become a field/value table in table mode
+pub fn render(value: &Value, format: OutputFormat) -> String {
+    let value = &sort_keys(value);
+    match format {
+        OutputFormat::Json => format!(
+            "{}\n",
+            serde_json::to_string_pretty(value).unwrap_or_default()
+        ),
+        OutputFormat::Csv => {
+            let (headers, rows) = to_rows(value);
+            let mut csv = String::new();
+            for line in std::iter::once(&headers).chain(rows.iter()) {
+                let fields: Vec<String> = line.iter().map(|field| csv_escape(field)).collect();
+                csv.push_str(&fields.join(","));
+                csv.push('\n');
+            }
+            csv
+        }
+        OutputFormat::Table => match value {
+            Value::Object(_) => {
+                let rows: Vec<Vec<String>> = flatten(value)
+                    .into_iter()
+                    .map(|(key, value)| vec![key, value])
+                    .collect();
+                table(&["field".to_string(), "value".to_string()], &rows)
+            }
+            _ => {
+                let (headers, rows) = to_rows(value);
+                table(&headers, &rows)
+            }
+        },
+    }
+}
+
+/// A copy of `value` with the fields of every object in name order, whichever map
+/// serde_json was built with
+fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut fields: Vec<(&String, &Value)> = map.iter().collect();
+            fields.sort_by(|a, b| a.0.cmp(b.0));
+            Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), sort_keys(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sort_keys).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Splits a response into a header and rows, with nested fields flattened into dotted column names
+fn to_rows(value: &Value) -> (Vec<String>, Vec<Vec<String>>) {
+    let entries: Vec<Vec<(String, String)>> = match value {
+        Value::Array(items) => items.iter().map(flatten).collect(),
+        other => vec![flatten(other)],
+    };
+
+    let mut headers: Vec<String> = Vec::new();
+    for entry in &entries {
+        for (key, _) in entry {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+
+    let rows = entries
+        .into_iter()
+        .map(|entry| {
+            headers
+                .iter()
+                .map(|header| {
+                    entry
+                        .iter()
+                        .find(|(key, _)| key == header)
+                        .map(|(_, value)| value.clone())
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .collect();
+    (headers, rows)
+}
+
+fn flatten(value: &Value) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    flatten_into("", value, &mut fields);
+    fields
+}
+
+fn flatten_into(prefix: &str, value: &Value, fields: &mut Vec<(String, String)>) {
+    let key_for = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten_into(&key_for(key), value, fields);
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                flatten_into(&key_for(&index.to_string()), item, fields);
+            }
+        }
+        Value::Null => fields.push((prefix.to_string(), String::new())),
+        Value::String(string) => fields.push((prefix.to_string(), string.clone())),
+        other => fields.push((prefix.to_string(), other.to_string())),
+    }
+}
+
+fn table(headers: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect();
+    for row in rows {
+        for (index, cell) in row.iter().enumerate() {
+            widths[index] = widths[index].max(cell.chars().count());
+        }
+    }
+
+    let format_line = |cells: &[String]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+
+    let mut table = format_line(headers);
+    let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    table.push_str(&format_line(&separator));
+    for row in rows {
+        table.push_str(&format_line(row));
+    }
+    table
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,467 @@
+use crate::models::error::ApiError;
+use crate::models::global_types::{GroupId, Username};
+use crate::models::group::{GroupDetail, GroupMemberShip};
+use crate::models::player::{Player, PlayerDetails};
+use crate::rate_limit::{acquire, RateLimiter};
+use crate::transport::Transport;
+use crate::WomClient;
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::future::Future;
+
+/// Requests per minute the api allows without an api key
+pub const ANONYMOUS_REQUESTS_PER_MINUTE: u32 = 20;
+/// How long the api waits before it lets a player be updated again
+pub const UPDATE_COOLDOWN_SECONDS: i64 = 60;
+
+/// The players a [BulkUpdater] should update
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpdateTargets {
+    /// Updated as given, there is no last update time to check against the cooldown
+    Usernames(Vec<Username>),
+    /// Every member of the group, players updated within the cooldown are skipped
+    Group(GroupId),
+    /// Players from an earlier call, those updated within the cooldown are skipped
+    Players(Vec<Player>),
+}
+
+/// What happened when updating one player
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpdateOutcome {
+    Updated(Box<PlayerDetails>),
+    /// Not sent because the player was updated within the cooldown
+    Skipped {
+        updated_at: DateTime<Utc>,
+    },
+    NotFound,
+    Failed(String),
+}
+
+/// The outcome of updating one player
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerUpdateResult {
+    pub username: Username,
+    pub outcome: UpdateOutcome,
+}
+
+/// Sent to the progress callback after each player is finished
+#[derive(Debug)]
+pub struct UpdateProgress<'a> {
+    pub completed: usize,
+    pub total: usize,
+    pub result: &'a PlayerUpdateResult,
+}
+
+/// Per player results of a bulk update, in the order the players finished
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BulkUpdateSummary {
+    pub results: Vec<PlayerUpdateResult>,
+}
+
+impl BulkUpdateSummary {
+    pub fn updated(&self) -> Vec<&PlayerDetails> {
+        self.results
+            .iter()
+            .filter_map(|result| match &result.outcome {
+                UpdateOutcome::Updated(details) => Some(details.as_ref()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn skipped(&self) -> Vec<&Username> {
+        self.usernames(|outcome| matches!(outcome, UpdateOutcome::Skipped { .. }))
+    }
+
+    pub fn not_found(&self) -> Vec<&Username> {
+        self.usernames(|outcome| matches!(outcome, UpdateOutcome::NotFound))
+    }
+
+    pub fn failed(&self) -> Vec<&Username> {
+        self.usernames(|outcome| matches!(outcome, UpdateOutcome::Failed(_)))
+    }
+
+    fn usernames(&self, filter: impl Fn(&UpdateOutcome) -> bool) -> Vec<&Username> {
+        self.results
+            .iter()
+            .filter(|result| filter(&result.outcome))
+            .map(|result| &result.username)
+            .collect()
+    }
+}
+
+type ProgressCallback<'a> = Box<dyn Fn(&UpdateProgress) + Send + Sync + 'a>;
+
+/// Updates many players at once, with bounded concurrency and under the api's rate limit.
+/// A client made with [WomClient::rate_limited] keeps its own limit, shared with everything
+/// else using it. Otherwise the updater waits for its `requests_per_minute`
+pub struct BulkUpdater<'a, T: Transport> {
+    client: &'a WomClient<T>,
+    concurrency: usize,
+    requests_per_minute: u32,
+    cooldown: Duration,
+    on_progress: Option<ProgressCallback<'a>>,
+}
+
+impl<'a, T: Transport> BulkUpdater<'a, T> {
+    /// Defaults to 5 updates at a time under the anonymous rate limit
+    pub fn new(client: &'a WomClient<T>) -> Self {
+        Self {
+            client,
+            concurrency: 5,
+            requests_per_minute: ANONYMOUS_REQUESTS_PER_MINUTE,
+            cooldown: Duration::seconds(UPDATE_COOLDOWN_SECONDS),
+            on_progress: None,
+        }
+    }
+
+    /// How many updates may be in flight at once
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Raise this when using an api key, which allows 100 requests per minute.
+    /// Not used when the client is rate limited, its limit applies instead
+    pub fn requests_per_minute(mut self, requests_per_minute: u32) -> Self {
+        self.requests_per_minute = requests_per_minute;
+        self
+    }
+
+    /// Players updated more recently than this are skipped
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Called after each player is finished, whatever the outcome
+    pub fn on_progress(mut self, on_progress: impl Fn(&UpdateProgress) + Send + Sync + 'a) -> Self {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    /// Updates every target. Only fails if a group's members could not be fetched,
+    /// errors for single players are reported in the summary
+    pub async fn run(&self, targets: UpdateTargets) -> Result<BulkUpdateSummary, anyhow::Error> {
+        // A rate limited client already waits before every request it sends
+        let limiter = match self.client.player_client.rate_limiter {
+            Some(_) => None,
+            None => Some(RateLimiter::new(self.requests_per_minute)),
+        };
+        let players = match targets {
+            UpdateTargets::Usernames(usernames) => usernames
+                .into_iter()
+                .map(|username| (username, None))
+                .collect(),
+            UpdateTargets::Players(players) => self.with_last_update(players),
+            UpdateTargets::Group(group_id) => {
+                acquire(limiter.as_ref()).await;
+                let group = self.client.group_client.get_group_details(group_id).await?;
+                self.with_last_update(
+                    group
+                        .memberships
+                        .into_iter()
+                        .map(|membership| membership.player)
+                        .collect(),
+                )
+            }
+        };
+
+        let total = players.len();
+        let limiter = limiter.as_ref();
+        let mut updates = stream::iter(players)
+            .map(|(username, updated_at)| async move {
+                let outcome = match updated_at {
+                    Some(updated_at) if Utc::now() - updated_at < self.cooldown => {
+                        UpdateOutcome::Skipped { updated_at }
+                    }
+                    _ => {
+                        acquire(limiter).await;
+                        self.update(&username).await
+                    }
+                };
+                PlayerUpdateResult { username, outcome }
+            })
+            .buffer_unordered(self.concurrency);
+
+        let mut summary = BulkUpdateSummary::default();
+        while let Some(result) = updates.next().await {
+            summary.results.push(result);
+            if let Some(on_progress) = &self.on_progress {
+                on_progress(&UpdateProgress {
+                    completed: summary.results.len(),
+                    total,
+                    result: summary.results.last().unwrap(),
+                });
+            }
+        }
+        Ok(summary)
+    }
+
+    fn with_last_update(&self, players: Vec<Player>) -> Vec<(Username, Option<DateTime<Utc>>)> {
+        players
+            .into_iter()
+            .map(|player| (player.username, player.updated_at))
+            .collect()
+    }
+
+    async fn update(&self, username: &Username) -> UpdateOutcome {
+        match self.client.player_client.update(username.clone()).await {
+            Ok(details) => UpdateOutcome::Updated(Box::new(details)),
+            Err(err) => match err.downcast_ref::<ApiError>() {
+                Some(api_error) if api_error.is_not_found() => UpdateOutcome::NotFound,
+                _ => UpdateOutcome::Failed(err.to_string()),
+            },
+        }
+    }
+}
+
+/// Results of fetching many players, keyed by the username each was asked for.
+/// A failure for one player leaves the others intact
+pub type BulkResults<ResponseType> = HashMap<Username, Result<ResponseType, anyhow::Error>>;
+
+/// Runs `fetch` for every username, at most `concurrency` at a time
+pub(crate) async fn fetch_many<ResponseType, Fetch, Fut>(
+    usernames: impl IntoIterator<Item = Username>,
+    concurrency: usize,
+    fetch: Fetch,
+) -> BulkResults<ResponseType>
+where
+    Fetch: Fn(Username) -> Fut,
+    Fut: Future<Output = Result<ResponseType, anyhow::Error>>,
+{
+    let fetch = &fetch;
+    stream::iter(usernames)
+        .map(|username| async move { (username.clone(), fetch(username).await) })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// A group with the details of each of its members
+#[derive(Debug)]
+pub struct EnrichedGroupDetail {
+    pub group: GroupDetail,
+    /// Keyed by each member's username
+    pub details: BulkResults<PlayerDetails>,
+}
+
+impl EnrichedGroupDetail {
+    /// Each membership with its member's details, `None` when they could not be fetched
+    pub fn members(&self) -> impl Iterator<Item = (&GroupMemberShip, Option<&PlayerDetails>)> {
+        self.group.memberships.iter().map(|membership| {
+            let details = self
+                .details
+                .get(&membership.player.username)
+                .and_then(|result| result.as_ref().ok());
+            (membership, details)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bulk::{BulkUpdater, UpdateOutcome, UpdateTargets};
+    use crate::fixtures::{self, fixture, fixture_bytes};
+    use crate::models::error::ApiError;
+    use crate::models::global_enums::Period;
+    use crate::models::global_types::{GroupId, Username};
+    use crate::models::group::GroupDetail;
+    use crate::query::SnapshotQuery;
+    use crate::transport::Transport;
+    use crate::WomClient;
+    use chrono::Utc;
+    use reqwest::StatusCode;
+    use std::sync::{Arc, Mutex};
+
+    fn usernames(names: &[&str]) -> Vec<Username> {
+        names
+            .iter()
+            .map(|name| Username::new(*name).unwrap())
+            .collect()
+    }
+
+    fn fake_client(requests: Arc<Mutex<Vec<String>>>) -> WomClient<impl Transport> {
+        fixtures::fake_client(requests, |_, path| match path {
+            "/groups/1" => (StatusCode::OK, fixture_bytes("group/group_detail.json")),
+            "/players/missing" => (
+                StatusCode::NOT_FOUND,
+                br#"{"message":"Player not found."}"#.to_vec(),
+            ),
+            "/players/broken" => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                br#"{"message":"Failed to load hiscores."}"#.to_vec(),
+            ),
+            path if path.contains("/gained") => {
+                (StatusCode::OK, fixture_bytes("player/player_gains.json"))
+            }
+            _ => (StatusCode::OK, fixture_bytes("player/player_details.json")),
+        })
+    }
+
+    #[tokio::test]
+    async fn bulk_update_usernames_test() {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let wom_client = fake_client(requests.clone());
+        let progress = Mutex::new(vec![]);
+
+        let summary = BulkUpdater::new(&wom_client)
+            .requests_per_minute(6000)
+            .on_progress(|update| {
+                progress
+                    .lock()
+                    .unwrap()
+                    .push((update.completed, update.total))
+            })
+            .run(UpdateTargets::Usernames(usernames(&[
+                "zezima", "missing", "broken",
+            ])))
+            .await
+            .unwrap();
+
+        assert_eq!(summary.results.len(), 3);
+        assert_eq!(summary.updated().len(), 1);
+        assert_eq!(summary.not_found(), vec!["missing"]);
+        assert_eq!(summary.failed(), vec!["broken"]);
+        assert!(summary.results.iter().any(|result| result.outcome
+            == UpdateOutcome::Failed("Failed to load hiscores.".to_string())));
+        assert_eq!(*progress.lock().unwrap(), vec![(1, 3), (2, 3), (3, 3)]);
+        assert!(requests
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|request| request.starts_with("POST")));
+    }
+
+    #[tokio::test]
+    async fn bulk_update_group_skips_recently_updated_test() {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let wom_client = fake_client(requests.clone());
+        let group: GroupDetail = fixture("group/group_detail.json");
+        let members: Vec<_> = group
+            .memberships
+            .iter()
+            .map(|membership| membership.player.username.clone())
+            .collect();
+
+        let summary = BulkUpdater::new(&wom_client)
+            .requests_per_minute(6000)
+            .run(UpdateTargets::Group(GroupId(1)))
+            .await
+            .unwrap();
+
+        assert_eq!(summary.results.len(), members.len());
+        assert_eq!(summary.updated().len(), members.len());
+        assert_eq!(requests.lock().unwrap()[0], "GET /groups/1");
+
+        let mut players: Vec<_> = group
+            .memberships
+            .into_iter()
+            .map(|membership| membership.player)
+            .collect();
+        players[0].updated_at = Some(Utc::now());
+        requests.lock().unwrap().clear();
+
+        let summary = BulkUpdater::new(&wom_client)
+            .requests_per_minute(6000)
+            .run(UpdateTargets::Players(players))
+            .await
+            .unwrap();
+
+        assert_eq!(summary.skipped(), vec![&members[0]]);
+        assert_eq!(requests.lock().unwrap().len(), members.len() - 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bulk_update_respects_rate_limit_test() {
+        let wom_client = fake_client(Arc::new(Mutex::new(vec![])));
+        let started = tokio::time::Instant::now();
+
+        let summary = BulkUpdater::new(&wom_client)
+            .concurrency(10)
+            .run(UpdateTargets::Usernames(usernames(&[
+                "player 0", "player 1", "player 2", "player 3",
+            ])))
+            .await
+            .unwrap();
+
+        assert_eq!(summary.updated().len(), 4);
+        // 20 requests a minute lets one start every three seconds
+        assert_eq!(started.elapsed().as_secs(), 9);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bulk_update_uses_client_rate_limit_test() {
+        let wom_client = fake_client(Arc::new(Mutex::new(vec![]))).rate_limited(60);
+        let started = tokio::time::Instant::now();
+
+        let summary = BulkUpdater::new(&wom_client)
+            .concurrency(10)
+            .requests_per_minute(20)
+            .run(UpdateTargets::Usernames(usernames(&[
+                "player 0", "player 1", "player 2", "player 3",
+            ])))
+            .await
+            .unwrap();
+
+        assert_eq!(summary.updated().len(), 4);
+        // Only the client's 60 a minute applies, one request a second
+        assert_eq!(started.elapsed().as_secs(), 3);
+    }
+
+    #[tokio::test]
+    async fn get_details_many_keeps_partial_successes_test() {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let wom_client = fake_client(requests.clone());
+
+        let results = wom_client
+            .player_client
+            .get_details_many(usernames(&["zezima", "missing", "broken"]), 2)
+            .await;
+
+        assert_eq!(results.len(), 3);
+        let result = |name: &str| &results[&Username::new(name).unwrap()];
+        assert!(result("Zezima").is_ok());
+        let not_found = result("missing").as_ref().unwrap_err();
+        assert!(not_found.downcast_ref::<ApiError>().unwrap().is_not_found());
+        assert!(result("broken").is_err());
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn get_member_details_test() {
+        let wom_client = fake_client(Arc::new(Mutex::new(vec![])));
+        let group = wom_client
+            .group_client
+            .get_group_details(GroupId(1))
+            .await
+            .unwrap();
+        let member_count = group.memberships.len();
+
+        let enriched = wom_client.player_client.get_member_details(group, 4).await;
+
+        assert_eq!(enriched.details.len(), member_count);
+        assert_eq!(enriched.members().count(), member_count);
+        assert!(enriched.members().all(|(_, details)| details.is_some()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bulk_fetch_respects_rate_limit_test() {
+        let wom_client = fake_client(Arc::new(Mutex::new(vec![]))).rate_limited(20);
+        let started = tokio::time::Instant::now();
+
+        let results = wom_client
+            .player_client
+            .get_gains_many(
+                usernames(&["player 0", "player 1", "player 2", "player 3"]),
+                SnapshotQuery::from(Period::Week),
+                10,
+            )
+            .await;
+
+        assert_eq!(results.len(), 4);
+        assert!(results.values().all(|result| result.is_ok()));
+        assert_eq!(started.elapsed().as_secs(), 9);
+    }
+}
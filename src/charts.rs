@@ -0,0 +1,567 @@
+use crate::helpers::sorted_gains;
+use crate::models::player::TimelineDatapoint;
+use chrono::{DateTime, Utc};
+use std::fmt::{Display, Write};
+
+/// Shortens large numbers the way the website does, such as 19.3M
+pub use crate::helpers::format_number;
+
+/// Line colours, used in order for each player of a comparison
+pub const PALETTE: [&str; 8] = [
+    "#2563eb", "#dc2626", "#16a34a", "#d97706", "#7c3aed", "#0891b2", "#db2777", "#4b5563",
+];
+
+const RANK_COLOUR: &str = "#9ca3af";
+const FONT: &str = "font-family=\"sans-serif\" font-size=\"12\"";
+const MARGIN_TOP: f64 = 40.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+const MARGIN_SIDE: f64 = 70.0;
+/// Wide enough for a twelve character username
+const MARGIN_NAMES: f64 = 100.0;
+const VALUE_TICKS: usize = 5;
+const DATE_TICKS: usize = 5;
+
+/// Size and title of a chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartOptions {
+    pub width: u32,
+    pub height: u32,
+    pub title: Option<String>,
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 400,
+            title: None,
+        }
+    }
+}
+
+impl ChartOptions {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            title: None,
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+}
+
+/// Renders a player's timeline as a line chart, with the value on the left axis and the
+/// rank, drawn dashed with the best rank at the top, on the right axis.
+/// Unranked points are left off the rank line
+pub fn timeline_chart(timeline: &[TimelineDatapoint], options: &ChartOptions) -> String {
+    let timeline = sorted(timeline);
+    let mut svg = Svg::new(options);
+    let plot = Plot::new(options, true);
+    if timeline.is_empty() {
+        svg.no_data(&plot);
+        return svg.finish();
+    }
+
+    let dates = DateScale::new(timeline.iter().map(|point| point.date));
+    let values = LinearScale::new(timeline.iter().map(|point| point.value), false);
+    let ranked: Vec<&TimelineDatapoint> = timeline.iter().filter(|point| point.rank > 0).collect();
+    let ranks = LinearScale::new(ranked.iter().map(|point| point.rank as f64), true);
+
+    svg.value_axis(&plot, &values, plot.left, "end", -8.0);
+    if !ranked.is_empty() {
+        svg.value_axis(&plot, &ranks, plot.right, "start", 8.0);
+    }
+    svg.date_axis(&plot, &dates);
+
+    let rank_line: Vec<(f64, f64)> = ranked
+        .iter()
+        .map(|point| {
+            (
+                plot.x(&dates, point.date),
+                plot.y(&ranks, point.rank as f64),
+            )
+        })
+        .collect();
+    svg.line(&rank_line, RANK_COLOUR, true);
+    let value_line: Vec<(f64, f64)> = timeline
+        .iter()
+        .map(|point| (plot.x(&dates, point.date), plot.y(&values, point.value)))
+        .collect();
+    svg.line(&value_line, PALETTE[0], false);
+    svg.legend(&plot, &[("Value", PALETTE[0]), ("Rank", RANK_COLOUR)]);
+    svg.finish()
+}
+
+/// Renders several players' timelines over each other on the same axes, one colour each
+/// from [PALETTE]
+pub fn comparison_chart<N: Display>(
+    timelines: &[(N, Vec<TimelineDatapoint>)],
+    options: &ChartOptions,
+) -> String {
+    let mut svg = Svg::new(options);
+    let plot = Plot::new(options, false);
+    let points = || timelines.iter().flat_map(|(_, timeline)| timeline);
+    if points().next().is_none() {
+        svg.no_data(&plot);
+        return svg.finish();
+    }
+
+    let dates = DateScale::new(points().map(|point| point.date));
+    let values = LinearScale::new(points().map(|point| point.value), false);
+    svg.value_axis(&plot, &values, plot.left, "end", -8.0);
+    svg.date_axis(&plot, &dates);
+
+    let names: Vec<String> = timelines.iter().map(|(name, _)| name.to_string()).collect();
+    let mut legend = vec![];
+    for (index, (_, timeline)) in timelines.iter().enumerate() {
+        let colour = PALETTE[index % PALETTE.len()];
+        let line: Vec<(f64, f64)> = sorted(timeline)
+            .iter()
+            .map(|point| (plot.x(&dates, point.date), plot.y(&values, point.value)))
+            .collect();
+        svg.line(&line, colour, false);
+        legend.push((names[index].as_str(), colour));
+    }
+    svg.legend(&plot, &legend);
+    svg.finish()
+}
+
+/// Renders gains as a horizontal bar chart, highest first. Each entry is a player's name and
+/// what they gained, such as `gain.data.skills.overall.experience.gained`
+pub fn gains_leaderboard<N: Display>(gains: &[(N, f64)], options: &ChartOptions) -> String {
+    let entries = sorted_gains(gains);
+
+    let mut svg = Svg::new(options);
+    let plot = Plot {
+        left: MARGIN_NAMES,
+        ..Plot::new(options, true)
+    };
+    if entries.is_empty() {
+        svg.no_data(&plot);
+        return svg.finish();
+    }
+
+    let most = entries
+        .iter()
+        .map(|(_, gained)| *gained)
+        .fold(0.0, f64::max);
+    let scale = if most > 0.0 { most } else { 1.0 };
+    let row = plot.height() / entries.len() as f64;
+    let bar = (row * 0.7).min(28.0);
+    for (index, (name, gained)) in entries.iter().enumerate() {
+        let middle = plot.top + row * (index as f64 + 0.5);
+        let width = plot.width() * gained.max(0.0) / scale;
+        svg.text(plot.left - 8.0, middle + 4.0, "end", name);
+        svg.push(format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+            plot.left,
+            middle - bar / 2.0,
+            width,
+            bar,
+            PALETTE[0]
+        ));
+        svg.text(
+            plot.left + width + 6.0,
+            middle + 4.0,
+            "start",
+            &format_number(*gained),
+        );
+    }
+    svg.push(format!(
+        "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#374151\"/>",
+        plot.left, plot.top, plot.left, plot.bottom
+    ));
+    svg.finish()
+}
+
+fn sorted(timeline: &[TimelineDatapoint]) -> Vec<TimelineDatapoint> {
+    let mut timeline = timeline.to_vec();
+    timeline.sort_by_key(|point| point.date);
+    timeline
+}
+
+/// The area inside the axes
+struct Plot {
+    left: f64,
+    right: f64,
+    top: f64,
+    bottom: f64,
+}
+
+impl Plot {
+    fn new(options: &ChartOptions, second_axis: bool) -> Self {
+        let right_margin = if second_axis { MARGIN_SIDE } else { 20.0 };
+        Self {
+            left: MARGIN_SIDE,
+            right: (options.width as f64 - right_margin).max(MARGIN_SIDE + 1.0),
+            top: MARGIN_TOP,
+            bottom: (options.height as f64 - MARGIN_BOTTOM).max(MARGIN_TOP + 1.0),
+        }
+    }
+
+    fn width(&self) -> f64 {
+        self.right - self.left
+    }
+
+    fn height(&self) -> f64 {
+        self.bottom - self.top
+    }
+
+    fn x(&self, dates: &DateScale, date: DateTime<Utc>) -> f64 {
+        self.left + self.width() * dates.position(date)
+    }
+
+    fn y(&self, scale: &LinearScale, value: f64) -> f64 {
+        self.bottom - self.height() * scale.position(value)
+    }
+}
+
+/// Maps values onto 0..1, rounded out to tidy tick steps
+struct LinearScale {
+    min: f64,
+    max: f64,
+    step: f64,
+    inverted: bool,
+}
+
+impl LinearScale {
+    /// An inverted scale puts its lowest value at the top, as ranks are drawn
+    fn new(values: impl Iterator<Item = f64>, inverted: bool) -> Self {
+        let (low, high) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| {
+            (low.min(value), high.max(value))
+        });
+        let (low, high) = if low > high {
+            (0.0, 1.0)
+        } else if low == high {
+            (low - 1.0, high + 1.0)
+        } else {
+            (low, high)
+        };
+        let step = nice_step((high - low) / VALUE_TICKS as f64);
+        Self {
+            min: (low / step).floor() * step,
+            max: (high / step).ceil() * step,
+            step,
+            inverted,
+        }
+    }
+
+    fn position(&self, value: f64) -> f64 {
+        let position = (value - self.min) / (self.max - self.min);
+        if self.inverted {
+            1.0 - position
+        } else {
+            position
+        }
+    }
+
+    /// Shortened like [format_number], with enough decimals to tell neighbouring ticks apart
+    fn label(&self, tick: f64) -> String {
+        let size = self.min.abs().max(self.max.abs());
+        let (divisor, suffix) = if size >= 1e9 {
+            (1e9, "B")
+        } else if size >= 1e6 {
+            (1e6, "M")
+        } else if size >= 1e3 {
+            (1e3, "K")
+        } else {
+            (1.0, "")
+        };
+        let decimals = (-(self.step / divisor).log10().floor()).clamp(0.0, 6.0) as usize;
+        format!("{:.*}{}", decimals, tick / divisor, suffix)
+    }
+
+    fn ticks(&self) -> Vec<f64> {
+        let count = ((self.max - self.min) / self.step).round() as usize;
+        (0..=count)
+            .map(|index| self.min + self.step * index as f64)
+            .collect()
+    }
+}
+
+/// The smallest 1, 2 or 5 times a power of ten that is at least `rough`
+fn nice_step(rough: f64) -> f64 {
+    let magnitude = 10f64.powf(rough.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * magnitude)
+}
+
+struct DateScale {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+impl DateScale {
+    fn new(dates: impl Iterator<Item = DateTime<Utc>>) -> Self {
+        let (start, end) = dates.fold(
+            (DateTime::<Utc>::MAX_UTC, DateTime::<Utc>::MIN_UTC),
+            |(start, end), date| (start.min(date), end.max(date)),
+        );
+        Self { start, end }
+    }
+
+    /// A single date is drawn in the middle
+    fn position(&self, date: DateTime<Utc>) -> f64 {
+        let span = (self.end - self.start).num_milliseconds();
+        if span == 0 {
+            return 0.5;
+        }
+        (date - self.start).num_milliseconds() as f64 / span as f64
+    }
+
+    fn ticks(&self) -> Vec<DateTime<Utc>> {
+        if self.start == self.end {
+            return vec![self.start];
+        }
+        let span = self.end - self.start;
+        (0..DATE_TICKS)
+            .map(|index| self.start + span * index as i32 / (DATE_TICKS - 1) as i32)
+            .collect()
+    }
+
+    /// Times are shown when the timeline is short enough for them to matter
+    fn label(&self, date: DateTime<Utc>) -> String {
+        if self.end - self.start > chrono::Duration::days(3) {
+            date.format("%Y-%m-%d").to_string()
+        } else {
+            date.format("%m-%d %H:%M").to_string()
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Accumulates the svg's elements, one per line so snapshots diff cleanly
+struct Svg {
+    out: String,
+}
+
+impl Svg {
+    fn new(options: &ChartOptions) -> Self {
+        let mut svg = Self {
+            out: format!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+                options.width, options.height
+            ),
+        };
+        svg.push(format!(
+            "<rect width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>",
+            options.width, options.height
+        ));
+        if let Some(title) = &options.title {
+            svg.push(format!(
+                "<text x=\"{:.1}\" y=\"24\" text-anchor=\"middle\" font-family=\"sans-serif\" font-size=\"16\" font-weight=\"bold\">{}</text>",
+                options.width as f64 / 2.0,
+                escape(title)
+            ));
+        }
+        svg
+    }
+
+    fn push(&mut self, element: String) {
+        self.out.push_str(&element);
+        self.out.push('\n');
+    }
+
+    fn text(&mut self, x: f64, y: f64, anchor: &str, text: &str) {
+        self.push(format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\" {}>{}</text>",
+            x,
+            y,
+            anchor,
+            FONT,
+            escape(text)
+        ));
+    }
+
+    fn no_data(&mut self, plot: &Plot) {
+        self.text(
+            plot.left + plot.width() / 2.0,
+            plot.top + plot.height() / 2.0,
+            "middle",
+            "No data",
+        );
+    }
+
+    /// Tick labels for a value scale with the axis line at `x`. Grid lines are drawn for the
+    /// left axis only, as the right one's ticks fall elsewhere
+    fn value_axis(&mut self, plot: &Plot, scale: &LinearScale, x: f64, anchor: &str, offset: f64) {
+        self.push(format!(
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#374151\"/>",
+            x, plot.top, x, plot.bottom
+        ));
+        for tick in scale.ticks() {
+            let y = plot.y(scale, tick);
+            let (x1, x2) = if x == plot.left {
+                (plot.left, plot.right)
+            } else {
+                (x, x + offset / 2.0)
+            };
+            self.push(format!(
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#e5e7eb\"/>",
+                x1, y, x2, y
+            ));
+            self.text(x + offset, y + 4.0, anchor, &scale.label(tick));
+        }
+    }
+
+    fn date_axis(&mut self, plot: &Plot, dates: &DateScale) {
+        self.push(format!(
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#374151\"/>",
+            plot.left, plot.bottom, plot.right, plot.bottom
+        ));
+        for tick in dates.ticks() {
+            let x = plot.x(dates, tick);
+            self.push(format!(
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#374151\"/>",
+                x,
+                plot.bottom,
+                x,
+                plot.bottom + 5.0
+            ));
+            self.text(x, plot.bottom + 20.0, "middle", &dates.label(tick));
+        }
+    }
+
+    /// A single point is drawn as a dot, as a line needs two
+    fn line(&mut self, points: &[(f64, f64)], colour: &str, dashed: bool) {
+        if let [(x, y)] = points {
+            self.push(format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{}\"/>",
+                x, y, colour
+            ));
+            return;
+        }
+        if points.is_empty() {
+            return;
+        }
+        let mut path = String::new();
+        for (index, (x, y)) in points.iter().enumerate() {
+            let command = if index == 0 { 'M' } else { 'L' };
+            let _ = write!(path, "{}{:.1},{:.1} ", command, x, y);
+        }
+        let dash = if dashed {
+            " stroke-dasharray=\"6 4\""
+        } else {
+            ""
+        };
+        self.push(format!(
+            "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"{}/>",
+            path.trim_end(),
+            colour,
+            dash
+        ));
+    }
+
+    fn legend(&mut self, plot: &Plot, entries: &[(&str, &str)]) {
+        for (index, (name, colour)) in entries.iter().enumerate() {
+            let y = plot.top + 12.0 + 18.0 * index as f64;
+            self.push(format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"12\" height=\"12\" fill=\"{}\"/>",
+                plot.left + 10.0,
+                y - 10.0,
+                colour
+            ));
+            self.text(plot.left + 28.0, y, "start", name);
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.out.push_str("</svg>\n");
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::charts::{
+        comparison_chart, format_number, gains_leaderboard, timeline_chart, ChartOptions,
+    };
+    use crate::fixtures::fixture;
+    use crate::models::player::{PlayerGain, TimelineDatapoint};
+    use chrono::Duration;
+    use std::path::Path;
+
+    /// Compares with the file under tests/snapshots/charts. Run with `UPDATE_SNAPSHOTS=1`
+    /// to write them after an intended change
+    fn assert_snapshot(name: &str, svg: &str) {
+        let path = Path::new("./tests/snapshots/charts").join(format!("{}.svg", name));
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, svg).unwrap();
+        }
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("missing {:?}, run with UPDATE_SNAPSHOTS=1", path));
+        assert_eq!(svg, expected, "{} no longer matches its snapshot", name);
+    }
+
+    fn timeline() -> Vec<TimelineDatapoint> {
+        fixture("player/player_snapshots_timeline.json")
+    }
+
+    #[test]
+    fn timeline_chart_test() {
+        let options = ChartOptions::default().title("Overall <zezima>");
+        let svg = timeline_chart(&timeline(), &options);
+
+        assert!(svg.contains("Overall &lt;zezima&gt;"));
+        assert!(svg.contains("stroke-dasharray"));
+        assert_snapshot("timeline", &svg);
+        assert_snapshot("timeline_empty", &timeline_chart(&[], &options));
+    }
+
+    #[test]
+    fn comparison_chart_test() {
+        let zezima = timeline();
+        let lynx_titan: Vec<TimelineDatapoint> = zezima
+            .iter()
+            .map(|point| TimelineDatapoint {
+                value: point.value * 1.1,
+                rank: point.rank - 1000,
+                date: point.date - Duration::hours(6),
+            })
+            .collect();
+        let svg = comparison_chart(
+            &[("Zezima", zezima), ("Lynx Titan", lynx_titan)],
+            &ChartOptions::new(600, 300),
+        );
+
+        assert_eq!(svg.matches("<path").count(), 2);
+        assert_snapshot("comparison", &svg);
+    }
+
+    #[test]
+    fn gains_leaderboard_test() {
+        let gain: PlayerGain = fixture("player/player_gains.json");
+        let overall = gain.data.skills.overall.experience.gained;
+        let svg = gains_leaderboard(
+            &[
+                ("Zezima", overall),
+                ("Lynx Titan", overall * 2.5),
+                ("B0aty", 0.0),
+            ],
+            &ChartOptions::new(600, 200).title("Overall gains"),
+        );
+
+        let lynx = svg.find("Lynx Titan").unwrap();
+        assert!(lynx < svg.find("Zezima").unwrap());
+        assert!(lynx < svg.find("B0aty").unwrap());
+        assert_snapshot("gains_leaderboard", &svg);
+
+        assert_eq!(format_number(19314798.0), "19.3M");
+        assert_eq!(format_number(1000.0), "1K");
+        assert_eq!(format_number(-250.0), "-250");
+    }
+}
@@ -0,0 +1,127 @@
+use crate::drift::DriftReporter;
+use crate::helpers::{handle_response, send};
+use crate::models::competition::{CompetitionCreateResponse, CreateCompetition};
+use crate::rate_limit::{acquire, RateLimiter};
+use crate::transport::{Request, ReqwestTransport, Transport};
+use crate::ApiEndpoint;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
+
+enum CompetitionEndPoints {
+    Create,
+}
+
+impl CompetitionEndPoints {
+    /// Name of the endpoint as reported in traces
+    fn name(&self) -> &'static str {
+        match self {
+            CompetitionEndPoints::Create => "competition.create",
+        }
+    }
+
+    fn url(&self) -> String {
+        match self {
+            CompetitionEndPoints::Create => ApiEndpoint::Competition.as_str().to_string(),
+        }
+    }
+}
+
+/// Handles requests to the [Competition Endpoints](https://docs.wiseoldman.net/competitions-api/competition-endpoints)
+pub struct CompetitionClient<T: Transport = ReqwestTransport> {
+    transport: Arc<T>,
+    base_url: String,
+    /// Set in strict mode
+    pub(crate) on_drift: Option<DriftReporter>,
+    /// Shared by every client when set by [crate::WomClient::rate_limited]
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl<T: Transport> CompetitionClient<T> {
+    pub fn new(transport: Arc<T>, base_url: &str) -> Self {
+        Self {
+            transport,
+            base_url: base_url.to_string(),
+            on_drift: None,
+            rate_limiter: None,
+        }
+    }
+
+    /// Sends a request with a json body and reads the response into its model
+    async fn request<ResponseType: DeserializeOwned + Serialize>(
+        &self,
+        method: Method,
+        endpoint: CompetitionEndPoints,
+        body: &impl Serialize,
+    ) -> Result<ResponseType, anyhow::Error> {
+        let name = endpoint.name();
+        let mut request = Request::new(method, format!("{}{}", self.base_url, endpoint.url()));
+        request.body = Some(serde_json::to_string(body)?);
+        acquire(self.rate_limiter.as_deref()).await;
+        let result = send(self.transport.as_ref(), name, request).await;
+        handle_response(result, name, self.on_drift.as_ref()).await
+    }
+
+    /// Creates a competition. Give a group id and the group's verification code to create it
+    /// for a group, otherwise list its participants
+    /// [Create Competition](https://docs.wiseoldman.net/competitions-api/competition-endpoints#create-competition)
+    pub async fn create_competition(
+        &self,
+        competition: &CreateCompetition,
+    ) -> Result<CompetitionCreateResponse, anyhow::Error> {
+        self.request(Method::POST, CompetitionEndPoints::Create, competition)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::competition::CreateCompetition;
+    use crate::models::global_enums::{Boss, Metric};
+    use crate::models::global_types::GroupId;
+    use crate::WomClient;
+    use chrono::{TimeZone, Utc};
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn create_group_competition_test() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/competitions")
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "title": "Zulrah of the Week #12",
+                    "metric": "zulrah",
+                    "startsAt": "2024-02-12T18:00:00Z",
+                    "endsAt": "2024-02-19T18:00:00Z",
+                    "groupId": 139,
+                    "groupVerificationCode": "111-222-333",
+                }));
+            then.status(201)
+                .header("content-type", "application/json")
+                .body_from_file("./tests/mocks/competition/competition_create_response.json");
+        });
+
+        let wom_client = WomClient::new_with_base_url(server.base_url(), None);
+        let result = wom_client
+            .competition_client
+            .create_competition(&CreateCompetition {
+                title: "Zulrah of the Week #12".to_string(),
+                metric: Metric::Boss(Boss::Zulrah),
+                starts_at: Utc.with_ymd_and_hms(2024, 2, 12, 18, 0, 0).unwrap(),
+                ends_at: Utc.with_ymd_and_hms(2024, 2, 19, 18, 0, 0).unwrap(),
+                group_id: Some(GroupId(139)),
+                group_verification_code: Some("111-222-333".to_string()),
+                participants: vec![],
+            })
+            .await;
+
+        mock.assert();
+        let created = result.unwrap();
+        assert_eq!(created.competition.metric, Metric::Boss(Boss::Zulrah));
+        assert_eq!(created.competition.participations.len(), 1);
+    }
+}
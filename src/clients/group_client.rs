@@ -1,9 +1,18 @@
-use crate::helpers::{handle_response, query_params_to_string};
+use crate::coalesce::Coalescer;
+use crate::drift::DriftReporter;
+use crate::helpers::{handle_response, query_params_to_string, send_limited};
 
 use crate::models::global_types::{GroupId, GroupName};
 use crate::models::group::{Group, GroupDetail};
 use crate::models::name::NameChange;
+use crate::query::pagination_to_query;
+use crate::rate_limit::RateLimiter;
+use crate::transport::{Request, ReqwestTransport, Response, Transport};
 use crate::{ApiEndpoint, Pagination, QueryParams};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
 
 enum GroupEndPoints {
     Search,
@@ -13,6 +22,15 @@ enum GroupEndPoints {
 }
 
 impl GroupEndPoints {
+    /// Name of the endpoint as reported in traces
+    fn name(&self) -> &'static str {
+        match self {
+            GroupEndPoints::Search => "group.search",
+            GroupEndPoints::GetGroupDetails(_) => "group.details",
+            GroupEndPoints::GroupNameChanges(_) => "group.name_changes",
+        }
+    }
+
     fn url(&self) -> String {
         match self {
             GroupEndPoints::Search => ApiEndpoint::Group.as_str().to_string(),
@@ -28,16 +46,24 @@ impl GroupEndPoints {
 }
 
 /// Handles all requests to the [Group Endpoints](https://docs.wiseoldman.net/groups-api/group-endpoints)
-pub struct GroupClient {
-    client: reqwest::Client,
+pub struct GroupClient<T: Transport = ReqwestTransport> {
+    transport: Arc<T>,
     base_url: String,
+    in_flight: Coalescer,
+    /// Set in strict mode
+    pub(crate) on_drift: Option<DriftReporter>,
+    /// Shared by every client when set by [crate::WomClient::rate_limited]
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
 }
 
-impl GroupClient {
-    pub fn new(client: reqwest::Client, base_url: &str) -> Self {
+impl<T: Transport> GroupClient<T> {
+    pub fn new(transport: Arc<T>, base_url: &str) -> Self {
         Self {
-            client,
+            transport,
             base_url: base_url.to_string(),
+            in_flight: Coalescer::new(),
+            on_drift: None,
+            rate_limiter: None,
         }
     }
 
@@ -53,6 +79,36 @@ impl GroupClient {
         }
     }
 
+    async fn send(
+        &self,
+        method: Method,
+        endpoint: GroupEndPoints,
+        query_params: Option<QueryParams>,
+    ) -> anyhow::Result<Response, anyhow::Error> {
+        let name = endpoint.name();
+        let request = Request::new(method, self.get_url(endpoint, query_params));
+        send_limited(
+            &self.transport,
+            &self.rate_limiter,
+            &self.in_flight,
+            name,
+            request,
+        )
+        .await
+    }
+
+    /// Sends a request and reads the response into its model
+    async fn request<ResponseType: DeserializeOwned + Serialize>(
+        &self,
+        method: Method,
+        endpoint: GroupEndPoints,
+        query_params: Option<QueryParams>,
+    ) -> anyhow::Result<ResponseType, anyhow::Error> {
+        let name = endpoint.name();
+        let result = self.send(method, endpoint, query_params).await;
+        handle_response(result, name, self.on_drift.as_ref()).await
+    }
+
     /// Search for groups by groupname, takes an optional pagination parameter
     /// [Search Groups](https://docs.wiseoldman.net/groups-api/group-endpoints#search-groups)
     pub async fn search(
@@ -60,41 +116,36 @@ impl GroupClient {
         name: GroupName,
         pagination: Option<Pagination>,
     ) -> anyhow::Result<Vec<Group>, anyhow::Error> {
-        let mut queries = Vec::new();
-        if let Some(pagination) = pagination {
-            queries.extend(pagination.to_query())
-        }
+        let mut queries = pagination_to_query(&pagination)?;
         queries.push(("name".to_string(), name.to_string()));
-        let full_url = self.get_url(GroupEndPoints::Search, Some(queries));
-        let result = self.client.get(full_url.as_str()).send().await;
-        handle_response(result).await
+        self.request(Method::GET, GroupEndPoints::Search, Some(queries))
+            .await
     }
 
     /// Get group details by group id
     /// [Get Group Details](https://docs.wiseoldman.net/groups-api/group-endpoints#get-group-details)
     pub async fn get_group_details(
         &self,
-        group_id: GroupId,
+        group_id: impl Into<GroupId>,
     ) -> anyhow::Result<GroupDetail, anyhow::Error> {
-        let full_url = self.get_url(GroupEndPoints::GetGroupDetails(group_id), None);
-        let result = self.client.get(full_url.as_str()).send().await;
-        handle_response(result).await
+        let endpoint = GroupEndPoints::GetGroupDetails(group_id.into());
+        self.request(Method::GET, endpoint, None).await
     }
 
     /// Get group name changes by group id
     /// [Get Group Name Changes](https://docs.wiseoldman.net/groups-api/group-endpoints#get-group-name-changes)
     pub async fn get_group_name_changes(
         &self,
-        group_id: GroupId,
+        group_id: impl Into<GroupId>,
         pagination: Option<Pagination>,
     ) -> anyhow::Result<Vec<NameChange>, anyhow::Error> {
-        let mut queries = Vec::new();
-        if let Some(pagination) = pagination {
-            queries.extend(pagination.to_query())
-        }
-        let full_url = self.get_url(GroupEndPoints::GroupNameChanges(group_id), Some(queries));
-        let result = self.client.get(full_url.as_str()).send().await;
-        handle_response(result).await
+        let queries = pagination_to_query(&pagination)?;
+        self.request(
+            Method::GET,
+            GroupEndPoints::GroupNameChanges(group_id.into()),
+            Some(queries),
+        )
+        .await
     }
 
     // Needs further testing
@@ -0,0 +1,371 @@
+use crate::models::error::ApiError;
+use crate::models::global_types::IntoUsername;
+use crate::models::player::{PlayerType, SnapShotData};
+use crate::transport::{Request, ReqwestTransport, Transport};
+use anyhow::anyhow;
+use reqwest::StatusCode;
+use serde_json::{json, Map};
+use std::sync::Arc;
+
+/// Where the official hiscores are served from
+pub const HISCORES_BASE_URL: &str = "https://secure.runescape.com";
+
+/// Skill rows, each is `rank,level,experience`
+const SKILL_ROWS: [&str; 24] = [
+    "overall",
+    "attack",
+    "defence",
+    "strength",
+    "hitpoints",
+    "ranged",
+    "prayer",
+    "magic",
+    "cooking",
+    "woodcutting",
+    "fletching",
+    "fishing",
+    "firemaking",
+    "crafting",
+    "smithing",
+    "mining",
+    "herblore",
+    "agility",
+    "thieving",
+    "slayer",
+    "farming",
+    "runecrafting",
+    "hunter",
+    "construction",
+];
+
+/// Activity rows, each is `rank,score`. `None` rows are on the hiscores but not tracked by
+/// Wise Old Man: deadman points and the legacy bounty hunter scores
+const ACTIVITY_ROWS: [Option<&str>; 17] = [
+    Some("league_points"),
+    None,
+    Some("bounty_hunter_hunter"),
+    Some("bounty_hunter_rogue"),
+    None,
+    None,
+    Some("clue_scrolls_all"),
+    Some("clue_scrolls_beginner"),
+    Some("clue_scrolls_easy"),
+    Some("clue_scrolls_medium"),
+    Some("clue_scrolls_hard"),
+    Some("clue_scrolls_elite"),
+    Some("clue_scrolls_master"),
+    Some("last_man_standing"),
+    Some("pvp_arena"),
+    Some("soul_wars_zeal"),
+    Some("guardians_of_the_rift"),
+];
+
+/// Boss rows, each is `rank,kills`
+const BOSS_ROWS: [&str; 59] = [
+    "abyssal_sire",
+    "alchemical_hydra",
+    "artio",
+    "barrows_chests",
+    "bryophyta",
+    "callisto",
+    "calvarion",
+    "cerberus",
+    "chambers_of_xeric",
+    "chambers_of_xeric_challenge_mode",
+    "chaos_elemental",
+    "chaos_fanatic",
+    "commander_zilyana",
+    "corporeal_beast",
+    "crazy_archaeologist",
+    "dagannoth_prime",
+    "dagannoth_rex",
+    "dagannoth_supreme",
+    "deranged_archaeologist",
+    "duke_sucellus",
+    "general_graardor",
+    "giant_mole",
+    "grotesque_guardians",
+    "hespori",
+    "kalphite_queen",
+    "king_black_dragon",
+    "kraken",
+    "kreearra",
+    "kril_tsutsaroth",
+    "mimic",
+    "nex",
+    "nightmare",
+    "phosanis_nightmare",
+    "obor",
+    "phantom_muspah",
+    "sarachnis",
+    "scorpia",
+    "scurrius",
+    "skotizo",
+    "spindel",
+    "tempoross",
+    "the_gauntlet",
+    "the_corrupted_gauntlet",
+    "the_leviathan",
+    "the_whisperer",
+    "theatre_of_blood",
+    "theatre_of_blood_hard_mode",
+    "thermonuclear_smoke_devil",
+    "tombs_of_amascut",
+    "tombs_of_amascut_expert",
+    "tzkal_zuk",
+    "tztok_jad",
+    "vardorvis",
+    "venenatis",
+    "vetion",
+    "vorkath",
+    "wintertodt",
+    "zalcano",
+    "zulrah",
+];
+
+/// The hiscores table a player type is ranked on
+fn hiscores_table(player_type: &PlayerType) -> &'static str {
+    match player_type {
+        PlayerType::Ironman => "hiscore_oldschool_ironman",
+        PlayerType::Hardcore => "hiscore_oldschool_hardcore_ironman",
+        PlayerType::Ultimate => "hiscore_oldschool_ultimate",
+        PlayerType::Regular | PlayerType::Unknown => "hiscore_oldschool",
+    }
+}
+
+/// Parses the official hiscores lite CSV into [SnapShotData].
+/// Rows come as skills, then activities, then bosses, each group in the order its fields are
+/// declared in [crate::models::snapshot]. Unranked entries stay `-1`. Rows the hiscores add
+/// after the known ones are ignored. Efficiency values are not on the hiscores and are left at 0
+pub fn parse_index_lite(csv: &str) -> Result<SnapShotData, anyhow::Error> {
+    let rows: Vec<Vec<i64>> = csv
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            line.split(',')
+                .map(|value| value.trim().parse::<i64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| anyhow!("Invalid hiscores row {} {:?}: {}", index + 1, line, err))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let expected = SKILL_ROWS.len() + ACTIVITY_ROWS.len() + BOSS_ROWS.len();
+    if rows.len() < expected {
+        return Err(anyhow!(
+            "Expected at least {} hiscores rows, got {}",
+            expected,
+            rows.len()
+        ));
+    }
+    let (skill_rows, rest) = rows.split_at(SKILL_ROWS.len());
+    let (activity_rows, rest) = rest.split_at(ACTIVITY_ROWS.len());
+    let boss_rows = &rest[..BOSS_ROWS.len()];
+
+    let mut skills = Map::new();
+    for (name, row) in SKILL_ROWS.iter().zip(skill_rows) {
+        let [rank, level, experience] = columns(row, name)?;
+        skills.insert(
+            name.to_string(),
+            json!({"metric": name, "rank": rank, "level": level, "experience": experience, "ehp": 0.0}),
+        );
+    }
+
+    let mut activities = Map::new();
+    for (name, row) in ACTIVITY_ROWS.iter().zip(activity_rows) {
+        if let Some(name) = name {
+            let [rank, score] = columns(row, name)?;
+            activities.insert(
+                name.to_string(),
+                json!({"metric": name, "rank": rank, "score": score}),
+            );
+        }
+    }
+
+    let mut bosses = Map::new();
+    for (name, row) in BOSS_ROWS.iter().zip(boss_rows) {
+        let [rank, kills] = columns(row, name)?;
+        bosses.insert(
+            name.to_string(),
+            json!({"metric": name, "rank": rank, "kills": kills, "ehb": 0.0}),
+        );
+    }
+
+    let data = json!({
+        "skills": skills,
+        "bosses": bosses,
+        "activities": activities,
+        "computed": {
+            "ehp": {"metric": "ehp", "value": 0.0, "rank": -1},
+            "ehb": {"metric": "ehb", "value": 0.0, "rank": -1},
+        },
+    });
+    Ok(serde_json::from_value(data)?)
+}
+
+fn columns<const N: usize>(row: &[i64], name: &str) -> Result<[i64; N], anyhow::Error> {
+    row.try_into().map_err(|_| {
+        anyhow!(
+            "Expected {} values for {} on the hiscores, got {}",
+            N,
+            name,
+            row.len()
+        )
+    })
+}
+
+/// Reads a player's current stats straight from the official OSRS hiscores,
+/// for when Wise Old Man is down or slow
+pub struct HiscoresClient<T: Transport = ReqwestTransport> {
+    transport: Arc<T>,
+    base_url: String,
+}
+
+impl HiscoresClient {
+    pub fn new() -> Self {
+        Self::new_with_base_url(HISCORES_BASE_URL.to_string())
+    }
+
+    /// Used for testing against a local mock of the hiscores
+    pub fn new_with_base_url(base_url: String) -> Self {
+        Self::new_with_transport(
+            ReqwestTransport::new(reqwest::Client::new()),
+            base_url.as_str(),
+        )
+    }
+}
+
+impl Default for HiscoresClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Transport> HiscoresClient<T> {
+    pub fn new_with_transport(transport: T, base_url: &str) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            base_url: base_url.to_string(),
+        }
+    }
+
+    /// Gets a player's stats from the hiscores table for their type.
+    /// Fails with a not found [ApiError] if the player is not on that table
+    pub async fn get_stats(
+        &self,
+        username: impl IntoUsername,
+        player_type: &PlayerType,
+    ) -> Result<SnapShotData, anyhow::Error> {
+        let username = username.into_username()?;
+        let full_url = format!(
+            "{}/m={}/index_lite.ws?player={}",
+            self.base_url,
+            hiscores_table(player_type),
+            username
+        );
+        let response = self.transport.send(Request::get(full_url)).await?;
+        match response.status {
+            StatusCode::OK => parse_index_lite(&String::from_utf8_lossy(&response.body)),
+            status => Err(anyhow!(ApiError {
+                status: status.as_u16(),
+                message: match status {
+                    StatusCode::NOT_FOUND => format!("{} is not on the hiscores", username),
+                    status => status.to_string(),
+                },
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clients::hiscores_client::{parse_index_lite, HiscoresClient};
+    use crate::models::error::ApiError;
+    use crate::models::global_enums::{Activity, Boss, Skill};
+    use crate::models::player::PlayerType;
+    use httpmock::prelude::*;
+
+    const INDEX_LITE: &str = include_str!("../../tests/mocks/hiscores/index_lite.txt");
+
+    #[test]
+    fn parse_index_lite_test() {
+        let data = parse_index_lite(INDEX_LITE).unwrap();
+
+        let overall = data.skills.get(&Skill::Overall).unwrap();
+        assert_eq!(
+            (overall.rank, overall.level, overall.experience),
+            (34135, 2094, 173181621)
+        );
+        assert_eq!(data.bosses.zulrah.kills, 1337);
+        assert_eq!(data.bosses.get(&Boss::AbyssalSire).unwrap().kills, -1);
+        assert_eq!(data.activities.league_points.score, -1);
+        // Deadman and the legacy bounty hunter rows are skipped
+        assert_eq!(
+            data.activities
+                .get(&Activity::ClueScrollsAll)
+                .unwrap()
+                .score,
+            153
+        );
+        assert_eq!(data.activities.guardians_of_the_rift.score, 211);
+        assert_eq!(data.skills.iter().count(), 24);
+    }
+
+    #[test]
+    fn parse_index_lite_ignores_new_rows_test() {
+        let with_new_rows = format!("{}1,2\n3,4\n", INDEX_LITE);
+        assert_eq!(
+            parse_index_lite(&with_new_rows).unwrap(),
+            parse_index_lite(INDEX_LITE).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_index_lite_errors_test() {
+        let missing_rows: String = INDEX_LITE.lines().take(50).collect::<Vec<_>>().join("\n");
+        assert!(parse_index_lite(&missing_rows).is_err());
+
+        let bad_value = INDEX_LITE.replacen("34135", "lots", 1);
+        assert!(parse_index_lite(&bad_value).is_err());
+
+        let wrong_columns = INDEX_LITE.replacen("34135,2094,173181621", "34135,2094", 1);
+        assert!(parse_index_lite(&wrong_columns).is_err());
+    }
+
+    #[tokio::test]
+    async fn get_stats_uses_player_type_table_test() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/m=hiscore_oldschool_hardcore_ironman/index_lite.ws")
+                .query_param("player", "IFat Fingers");
+            then.status(200).body(INDEX_LITE);
+        });
+
+        let client = HiscoresClient::new_with_base_url(server.base_url());
+        let result = client
+            .get_stats("IFat Fingers".to_string(), &PlayerType::Hardcore)
+            .await;
+
+        mock.assert();
+        assert_eq!(result.unwrap().bosses.zulrah.kills, 1337);
+    }
+
+    #[tokio::test]
+    async fn get_stats_not_found_test() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/m=hiscore_oldschool/index_lite.ws");
+            then.status(404).body("<html>404 - Page not found</html>");
+        });
+
+        let client = HiscoresClient::new_with_base_url(server.base_url());
+        let err = client
+            .get_stats("nobody".to_string(), &PlayerType::Regular)
+            .await
+            .unwrap_err();
+
+        assert!(err.downcast_ref::<ApiError>().unwrap().is_not_found());
+    }
+}
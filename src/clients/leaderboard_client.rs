@@ -0,0 +1,254 @@
+use crate::coalesce::Coalescer;
+use crate::drift::DriftReporter;
+use crate::helpers::{handle_response, query_params_to_string, send_limited};
+use crate::models::delta::DeltaLeaderboardEntry;
+use crate::models::global_enums::{ComputedMetricEnum, Metric, Period};
+use crate::models::player::Player;
+use crate::models::record::RecordLeaderboardEntry;
+use crate::query::{pagination_to_query, LeaderboardQuery};
+use crate::rate_limit::RateLimiter;
+use crate::transport::{Request, ReqwestTransport, Transport};
+use crate::{ApiEndpoint, Pagination, QueryParams};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
+
+enum LeaderboardEndPoints {
+    Efficiency,
+    Records,
+    Deltas,
+}
+
+impl LeaderboardEndPoints {
+    /// Name of the endpoint as reported in traces
+    fn name(&self) -> &'static str {
+        match self {
+            LeaderboardEndPoints::Efficiency => "efficiency.leaderboard",
+            LeaderboardEndPoints::Records => "record.leaderboard",
+            LeaderboardEndPoints::Deltas => "delta.leaderboard",
+        }
+    }
+
+    fn url(&self) -> String {
+        let endpoint = match self {
+            LeaderboardEndPoints::Efficiency => ApiEndpoint::Efficiency,
+            LeaderboardEndPoints::Records => ApiEndpoint::Record,
+            LeaderboardEndPoints::Deltas => ApiEndpoint::Delta,
+        };
+        format!("{}/leaderboard", endpoint.as_str())
+    }
+}
+
+/// Handles requests to the efficiency, record and delta leaderboards
+pub struct LeaderboardClient<T: Transport = ReqwestTransport> {
+    transport: Arc<T>,
+    base_url: String,
+    in_flight: Coalescer,
+    /// Set in strict mode
+    pub(crate) on_drift: Option<DriftReporter>,
+    /// Shared by every client when set by [crate::WomClient::rate_limited]
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl<T: Transport> LeaderboardClient<T> {
+    pub fn new(transport: Arc<T>, base_url: &str) -> Self {
+        Self {
+            transport,
+            base_url: base_url.to_string(),
+            in_flight: Coalescer::new(),
+            on_drift: None,
+            rate_limiter: None,
+        }
+    }
+
+    /// Sends a GET request and reads the response into its model
+    async fn request<ResponseType: DeserializeOwned + Serialize>(
+        &self,
+        endpoint: LeaderboardEndPoints,
+        query_params: QueryParams,
+    ) -> Result<ResponseType, anyhow::Error> {
+        let name = endpoint.name();
+        let url = format!(
+            "{}{}{}",
+            self.base_url,
+            endpoint.url(),
+            query_params_to_string(&query_params)
+        );
+        let request = Request::new(Method::GET, url);
+        let result = send_limited(
+            &self.transport,
+            &self.rate_limiter,
+            &self.in_flight,
+            name,
+            request,
+        )
+        .await;
+        handle_response(result, name, self.on_drift.as_ref()).await
+    }
+
+    /// Get the players with the most efficient hours played or bossed, filtered by type,
+    /// build or country. Takes an optional pagination parameter
+    /// [Get Global Efficiency Leaderboards](https://docs.wiseoldman.net/efficiency-api/efficiency-endpoints#get-global-efficiency-leaderboards)
+    pub async fn get_efficiency_leaderboard(
+        &self,
+        metric: ComputedMetricEnum,
+        query: LeaderboardQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<Player>, anyhow::Error> {
+        let mut queries = vec![(
+            "metric".to_string(),
+            Metric::ComputedMetric(metric).to_string(),
+        )];
+        queries.extend(query.to_query()?);
+        queries.extend(pagination_to_query(&pagination)?);
+        self.request(LeaderboardEndPoints::Efficiency, queries)
+            .await
+    }
+
+    /// Get the top records for a metric and period, filtered by type, build or country
+    /// [Get Global Record Leaderboards](https://docs.wiseoldman.net/records-api/record-endpoints#get-global-record-leaderboards)
+    pub async fn get_records_leaderboard(
+        &self,
+        period: Period,
+        metric: Metric,
+        query: LeaderboardQuery,
+    ) -> Result<Vec<RecordLeaderboardEntry>, anyhow::Error> {
+        let mut queries = period_and_metric(period, metric);
+        queries.extend(query.to_query()?);
+        self.request(LeaderboardEndPoints::Records, queries).await
+    }
+
+    /// Get the top gains for a metric and period, filtered by type, build or country
+    /// [Get Global Delta Leaderboards](https://docs.wiseoldman.net/deltas-api/delta-endpoints#get-global-delta-leaderboards)
+    pub async fn get_deltas_leaderboard(
+        &self,
+        period: Period,
+        metric: Metric,
+        query: LeaderboardQuery,
+    ) -> Result<Vec<DeltaLeaderboardEntry>, anyhow::Error> {
+        let mut queries = period_and_metric(period, metric);
+        queries.extend(query.to_query()?);
+        self.request(LeaderboardEndPoints::Deltas, queries).await
+    }
+}
+
+fn period_and_metric(period: Period, metric: Metric) -> QueryParams {
+    vec![
+        ("period".to_string(), period.as_str().to_string()),
+        ("metric".to_string(), metric.to_string()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::global_enums::{Boss, ComputedMetricEnum, Metric, Period, Skill};
+    use crate::models::player::{Country, PlayerType};
+    use crate::query::LeaderboardQuery;
+    use crate::{Pagination, WomClient};
+    use httpmock::prelude::*;
+
+    const CONTENT_TYPE: &str = "content-type";
+    const APPLICATION_JSON: &str = "application/json";
+
+    #[tokio::test]
+    async fn efficiency_leaderboard_test() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/efficiency/leaderboard")
+                .query_param("metric", "ehb")
+                .query_param("playerType", "ironman")
+                .query_param("country", "NL")
+                .query_param("limit", "2")
+                .query_param("offset", "0");
+            then.status(200)
+                .header(CONTENT_TYPE, APPLICATION_JSON)
+                .body_from_file("./tests/mocks/leaderboard/efficiency_leaderboard.json");
+        });
+
+        let wom_client = WomClient::new_with_base_url(server.base_url(), None);
+        let result = wom_client
+            .leaderboard_client
+            .get_efficiency_leaderboard(
+                ComputedMetricEnum::Ehb,
+                LeaderboardQuery::new()
+                    .player_type(PlayerType::Ironman)
+                    .country(Country::NL),
+                Some(Pagination {
+                    limit: Some(2),
+                    offset: None,
+                }),
+            )
+            .await;
+
+        mock.assert();
+        let players = result.unwrap();
+        assert_eq!(players.len(), 2);
+        assert!(players
+            .iter()
+            .all(|player| player.country == Some(Country::NL)));
+    }
+
+    #[tokio::test]
+    async fn records_leaderboard_test() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/records/leaderboard")
+                .query_param("period", "week")
+                .query_param("metric", "zulrah")
+                .query_param("country", "NL");
+            then.status(200)
+                .header(CONTENT_TYPE, APPLICATION_JSON)
+                .body_from_file("./tests/mocks/leaderboard/records_leaderboard.json");
+        });
+
+        let wom_client = WomClient::new_with_base_url(server.base_url(), None);
+        let result = wom_client
+            .leaderboard_client
+            .get_records_leaderboard(
+                Period::Week,
+                Metric::Boss(Boss::Zulrah),
+                LeaderboardQuery::new().country(Country::NL),
+            )
+            .await;
+
+        mock.assert();
+        let records = result.unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].metric, Metric::Boss(Boss::Zulrah));
+        assert_eq!(records[0].player.country, Some(Country::NL));
+    }
+
+    #[tokio::test]
+    async fn deltas_leaderboard_test() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/deltas/leaderboard")
+                .query_param("period", "day")
+                .query_param("metric", "overall")
+                .query_param("country", "NL");
+            then.status(200)
+                .header(CONTENT_TYPE, APPLICATION_JSON)
+                .body_from_file("./tests/mocks/leaderboard/deltas_leaderboard.json");
+        });
+
+        let wom_client = WomClient::new_with_base_url(server.base_url(), None);
+        let result = wom_client
+            .leaderboard_client
+            .get_deltas_leaderboard(
+                Period::Day,
+                Metric::Skill(Skill::Overall),
+                LeaderboardQuery::new().country(Country::NL),
+            )
+            .await;
+
+        mock.assert();
+        let deltas = result.unwrap();
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].gained, 12_345_678.0);
+        assert_eq!(deltas[0].player_id, deltas[0].player.id);
+    }
+}
@@ -1,4 +1,11 @@
+/// The competition client module for [Competition Endpoints](https://docs.wiseoldman.net/competitions-api/competition-endpoints)
+pub mod competition_client;
 /// The group client module for [Group Endpoints](https://docs.wiseoldman.net/groups-api/group-endpoints)
 pub mod group_client;
+/// A fallback client for the official [OSRS hiscores](https://secure.runescape.com/m=hiscore_oldschool/overall)
+pub mod hiscores_client;
+/// The leaderboard client module for the [Efficiency](https://docs.wiseoldman.net/efficiency-api/efficiency-endpoints),
+/// [Record](https://docs.wiseoldman.net/records-api/record-endpoints) and [Delta](https://docs.wiseoldman.net/deltas-api/delta-endpoints) leaderboards
+pub mod leaderboard_client;
 /// The player client module for [Player Endpoints](https://docs.wiseoldman.net/players-api/player-endpoints)
 pub mod player_client;
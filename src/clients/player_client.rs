@@ -1,19 +1,28 @@
-use crate::helpers::{handle_response, query_params_to_string};
+use crate::bulk::{fetch_many, BulkResults, EnrichedGroupDetail};
+use crate::coalesce::Coalescer;
+use crate::drift::DriftReporter;
+use crate::helpers::{handle_response, query_params_to_string, send_limited};
 use crate::models::competition::{
     CompetitionStatus, PlayerCompetitionStanding, PlayerParticipation,
 };
 use crate::models::global_enums::{Metric, Period};
-use crate::models::global_types::{PlayerId, Username};
-use crate::models::group::PlayerMembership;
+use crate::models::global_types::{IntoUsername, PlayerId, Username};
+use crate::models::group::{GroupDetail, PlayerMembership};
 use crate::models::name::NameChange;
 use crate::models::player::{
     Achievement, AchievementProgress, AssertPlayerType, Player, PlayerArchive, PlayerDetails,
     PlayerGain, SnapShot, TimelineDatapoint,
 };
 use crate::models::record::Record;
+use crate::query::{pagination_to_query, CompetitionQuery, DateRange, RecordQuery, SnapshotQuery};
+use crate::rate_limit::RateLimiter;
+use crate::transport::{Request, ReqwestTransport, Response, Transport};
 use crate::{ApiEndpoint, Pagination, QueryParam, QueryParams};
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
 
 enum PlayerEndPoints {
     Search,
@@ -35,6 +44,28 @@ enum PlayerEndPoints {
 }
 
 impl PlayerEndPoints {
+    /// Name of the endpoint as reported in traces
+    fn name(&self) -> &'static str {
+        match self {
+            PlayerEndPoints::Search => "player.search",
+            PlayerEndPoints::Update(_) => "player.update",
+            PlayerEndPoints::AssertType(_) => "player.assert_type",
+            PlayerEndPoints::Details(_) => "player.details",
+            PlayerEndPoints::DetailsById(_) => "player.details_by_id",
+            PlayerEndPoints::Achievements(_) => "player.achievements",
+            PlayerEndPoints::AchievementsProgress(_) => "player.achievements_progress",
+            PlayerEndPoints::Competitions(_) => "player.competitions",
+            PlayerEndPoints::CompetitionsStandings(_) => "player.competition_standings",
+            PlayerEndPoints::GroupMembership(_) => "player.group_memberships",
+            PlayerEndPoints::Gains(_) => "player.gains",
+            PlayerEndPoints::Records(_) => "player.records",
+            PlayerEndPoints::Snapshots(_) => "player.snapshots",
+            PlayerEndPoints::SnapshotsTimeline(_) => "player.snapshots_timeline",
+            PlayerEndPoints::NameChange(_) => "player.name_changes",
+            PlayerEndPoints::Archives(_) => "player.archives",
+        }
+    }
+
     fn url(&self) -> String {
         match self {
             PlayerEndPoints::Search => {
@@ -102,16 +133,24 @@ impl PlayerEndPoints {
 }
 
 /// Handles all requests to the [Player Endpoints](https://docs.wiseoldman.net/players-api/player-endpoints)
-pub struct PlayerClient {
-    client: reqwest::Client,
+pub struct PlayerClient<T: Transport = ReqwestTransport> {
+    transport: Arc<T>,
     base_url: String,
+    in_flight: Coalescer,
+    /// Set in strict mode
+    pub(crate) on_drift: Option<DriftReporter>,
+    /// Shared by every client when set by [crate::WomClient::rate_limited]
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
 }
 
-impl PlayerClient {
-    pub fn new(client: reqwest::Client, base_url: &str) -> Self {
+impl<T: Transport> PlayerClient<T> {
+    pub fn new(transport: Arc<T>, base_url: &str) -> Self {
         Self {
-            client,
+            transport,
             base_url: base_url.to_string(),
+            in_flight: Coalescer::new(),
+            on_drift: None,
+            rate_limiter: None,
         }
     }
 
@@ -127,289 +166,322 @@ impl PlayerClient {
         }
     }
 
+    async fn send(
+        &self,
+        method: Method,
+        endpoint: PlayerEndPoints,
+        query_params: Option<QueryParams>,
+    ) -> Result<Response, anyhow::Error> {
+        let name = endpoint.name();
+        let request = Request::new(method, self.get_url(endpoint, query_params));
+        send_limited(
+            &self.transport,
+            &self.rate_limiter,
+            &self.in_flight,
+            name,
+            request,
+        )
+        .await
+    }
+
+    /// Sends a request and reads the response into its model
+    async fn request<ResponseType: DeserializeOwned + Serialize>(
+        &self,
+        method: Method,
+        endpoint: PlayerEndPoints,
+        query_params: Option<QueryParams>,
+    ) -> Result<ResponseType, anyhow::Error> {
+        let name = endpoint.name();
+        let result = self.send(method, endpoint, query_params).await;
+        handle_response(result, name, self.on_drift.as_ref()).await
+    }
+
     /// Search for players by username, takes an optional pagination parameter
     /// [Player Search](https://docs.wiseoldman.net/players-api/player-endpoints#search)
     pub async fn search(
         &self,
-        username: Username,
+        username: impl IntoUsername,
         pagination: Option<Pagination>,
     ) -> Result<Vec<Player>, anyhow::Error> {
-        let mut queries = Vec::new();
-        if let Some(pagination) = pagination {
-            queries.extend(pagination.to_query())
-        }
-        let username_query: QueryParam = ("username".to_string(), username.to_string());
+        let mut queries = pagination_to_query(&pagination)?;
+        let username_query: QueryParam = (
+            "username".to_string(),
+            username.into_username()?.to_string(),
+        );
         queries.push(username_query);
 
-        let full_url = self.get_url(PlayerEndPoints::Search, Some(queries));
-        let result = self.client.get(full_url.as_str()).send().await;
-        handle_response(result).await
+        self.request(Method::GET, PlayerEndPoints::Search, Some(queries))
+            .await
     }
 
     /// Sends a request to update the players hiscore data from the offical hiscores
     /// [Player Update](https://docs.wiseoldman.net/players-api/player-endpoints#update-a-player)
-    pub async fn update(&self, username: Username) -> Result<PlayerDetails, anyhow::Error> {
-        let full_url = self.get_url(PlayerEndPoints::Update(username), None);
-        let result = self.client.post(full_url.as_str()).send().await;
-        handle_response(result).await
+    pub async fn update(
+        &self,
+        username: impl IntoUsername,
+    ) -> Result<PlayerDetails, anyhow::Error> {
+        self.request(
+            Method::POST,
+            PlayerEndPoints::Update(username.into_username()?),
+            None,
+        )
+        .await
     }
 
     /// Asserts (and attempts to fix, if necessary) a player's game-mode type.
     /// [Assert Player Type](https://docs.wiseoldman.net/players-api/player-endpoints#assert-player-type)
-    pub async fn assert_type(&self, username: Username) -> Result<AssertPlayerType, anyhow::Error> {
-        let result = self
-            .client
-            .post(
-                self.get_url(PlayerEndPoints::AssertType(username), None)
-                    .as_str(),
-            )
-            .send()
-            .await;
-        handle_response(result).await
+    pub async fn assert_type(
+        &self,
+        username: impl IntoUsername,
+    ) -> Result<AssertPlayerType, anyhow::Error> {
+        self.request(
+            Method::POST,
+            PlayerEndPoints::AssertType(username.into_username()?),
+            None,
+        )
+        .await
     }
 
     /// Get a player's details by username
     /// [Player Details](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-details)
-    pub async fn get_details(&self, username: Username) -> Result<PlayerDetails, anyhow::Error> {
-        let result = self
-            .client
-            .get(
-                self.get_url(PlayerEndPoints::Details(username), None)
-                    .as_str(),
-            )
-            .send()
-            .await;
-        handle_response(result).await
+    pub async fn get_details(
+        &self,
+        username: impl IntoUsername,
+    ) -> Result<PlayerDetails, anyhow::Error> {
+        self.request(
+            Method::GET,
+            PlayerEndPoints::Details(username.into_username()?),
+            None,
+        )
+        .await
+    }
+
+    /// Gets the details of many players, at most `concurrency` at a time.
+    /// Each username gets its own result, so one failure does not lose the rest
+    pub async fn get_details_many(
+        &self,
+        usernames: impl IntoIterator<Item = Username>,
+        concurrency: usize,
+    ) -> BulkResults<PlayerDetails> {
+        fetch_many(usernames, concurrency, |username| {
+            self.get_details(username)
+        })
+        .await
+    }
+
+    /// Gets the details of every member of a group, at most `concurrency` at a time
+    pub async fn get_member_details(
+        &self,
+        group: GroupDetail,
+        concurrency: usize,
+    ) -> EnrichedGroupDetail {
+        let usernames: Vec<Username> = group
+            .memberships
+            .iter()
+            .map(|membership| membership.player.username.clone())
+            .collect();
+        let details = self.get_details_many(usernames, concurrency).await;
+        EnrichedGroupDetail { group, details }
     }
 
     /// Get a player's details by player id
     /// [Player Details](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-details-by-id)
     pub async fn get_details_by_id(
         &self,
-        player_id: PlayerId,
+        player_id: impl Into<PlayerId>,
     ) -> Result<PlayerDetails, anyhow::Error> {
-        let result = self
-            .client
-            .get(
-                self.get_url(PlayerEndPoints::DetailsById(player_id), None)
-                    .as_str(),
-            )
-            .send()
-            .await;
-        handle_response(result).await
+        let endpoint = PlayerEndPoints::DetailsById(player_id.into());
+        self.request(Method::GET, endpoint, None).await
     }
 
     /// Get a player's achievements by username
     /// [Player Achievements](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-achievements)
     pub async fn get_achievements(
         &self,
-        username: Username,
+        username: impl IntoUsername,
     ) -> Result<Vec<Achievement>, anyhow::Error> {
-        let result = self
-            .client
-            .get(
-                self.get_url(PlayerEndPoints::Achievements(username), None)
-                    .as_str(),
-            )
-            .send()
-            .await;
-        handle_response(result).await
+        self.request(
+            Method::GET,
+            PlayerEndPoints::Achievements(username.into_username()?),
+            None,
+        )
+        .await
     }
 
     /// Get a player's achievements progress by username
     /// [Player Achievements Progress](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-achievement-progress)
     pub async fn get_achievement_progress(
         &self,
-        username: Username,
+        username: impl IntoUsername,
     ) -> Result<Vec<AchievementProgress>, anyhow::Error> {
-        let result = self
-            .client
-            .get(
-                self.get_url(PlayerEndPoints::AchievementsProgress(username), None)
-                    .as_str(),
-            )
-            .send()
-            .await;
-        handle_response(result).await
+        self.request(
+            Method::GET,
+            PlayerEndPoints::AchievementsProgress(username.into_username()?),
+            None,
+        )
+        .await
     }
 
-    /// Get a player's competitions they have participated in by username
+    /// Get a player's competitions they have participated in by username, optionally filtered by status
     ///  [Get Player Competition Participations](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-competition-participations)
     pub async fn get_competitions(
         &self,
-        username: Username,
-        competition_status: Option<CompetitionStatus>,
-        pagination: Option<Pagination>,
+        username: impl IntoUsername,
+        query: CompetitionQuery,
     ) -> Result<Vec<PlayerParticipation>, anyhow::Error> {
-        let mut queries = Vec::new();
-        if let Some(pagination) = pagination {
-            queries.extend(pagination.to_query())
-        }
-        if let Some(status) = competition_status {
-            queries.push(("status".to_string(), status.as_str().to_string()));
-        }
-
-        let full_url = format!(
-            "{}{}",
-            self.get_url(PlayerEndPoints::Competitions(username), None),
-            query_params_to_string(&queries)
-        );
-
-        let result = self.client.get(full_url).send().await;
-        handle_response(result).await
+        self.request(
+            Method::GET,
+            PlayerEndPoints::Competitions(username.into_username()?),
+            Some(query.to_query()?),
+        )
+        .await
     }
 
     /// Get a player's competition standings by username
     /// [Get Player Competition Standings](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-competition-standings)
     pub async fn get_competition_standings(
         &self,
-        username: Username,
+        username: impl IntoUsername,
         competition_status: CompetitionStatus,
     ) -> Result<Vec<PlayerCompetitionStanding>, anyhow::Error> {
-        let full_url = format!(
-            "{}{}",
-            self.get_url(PlayerEndPoints::CompetitionsStandings(username), None),
-            query_params_to_string(&vec![(
-                "status".to_string(),
-                competition_status.as_str().to_string()
-            )])
-        );
-        let result = self.client.get(full_url).send().await;
-        handle_response(result).await
+        let queries = vec![(
+            "status".to_string(),
+            competition_status.as_str().to_string(),
+        )];
+        self.request(
+            Method::GET,
+            PlayerEndPoints::CompetitionsStandings(username.into_username()?),
+            Some(queries),
+        )
+        .await
     }
 
     /// Get a player's group memberships by username
     /// [Get Player Group Memberships](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-group-memberships)
     pub async fn get_groups(
         &self,
-        username: Username,
+        username: impl IntoUsername,
         pagination: Option<Pagination>,
     ) -> Result<Vec<PlayerMembership>, anyhow::Error> {
-        let mut queries = Vec::new();
-        if let Some(pagination) = pagination {
-            queries.extend(pagination.to_query())
-        }
-
-        let full_url = format!(
-            "{}{}",
-            self.get_url(PlayerEndPoints::GroupMembership(username), None),
-            query_params_to_string(&queries)
-        );
-
-        let result = self.client.get(full_url).send().await;
-        handle_response(result).await
+        let queries = pagination_to_query(&pagination)?;
+        self.request(
+            Method::GET,
+            PlayerEndPoints::GroupMembership(username.into_username()?),
+            Some(queries),
+        )
+        .await
     }
 
     /// Get a player's gains by username and period
     /// [Get Player Gains](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-gains)
     pub async fn get_gains_by_period(
         &self,
-        username: Username,
+        username: impl IntoUsername,
         period: Period,
     ) -> Result<PlayerGain, anyhow::Error> {
-        let full_url = self.get_url(
-            PlayerEndPoints::Gains(username),
-            Some(vec![("period".to_string(), period.as_str().to_string())]),
-        );
-        let result = self.client.get(full_url).send().await;
-        handle_response(result).await
+        self.get_gains(username, SnapshotQuery::from(period)).await
     }
 
     /// Get a player's gains by username plus start and end date
     /// [Get Player Gains](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-gains)
     pub async fn get_gains_by_date(
         &self,
-        username: Username,
-        start_date: DateTime<Utc>,
-        end_date: DateTime<Utc>,
+        username: impl IntoUsername,
+        date_range: DateRange,
     ) -> Result<PlayerGain, anyhow::Error> {
-        let full_url = self.get_url(
-            PlayerEndPoints::Gains(username),
-            Some(vec![
-                (
-                    "startDate".to_string(),
-                    start_date.format("%Y-%m-%d %H:%M:%S").to_string(),
-                ),
-                (
-                    "endDate".to_string(),
-                    end_date.format("%Y-%m-%d %H:%M:%S").to_string(),
-                ),
-            ]),
-        );
+        self.get_gains(username, SnapshotQuery::from(date_range))
+            .await
+    }
 
-        let result = self.client.get(full_url).send().await;
-        handle_response(result).await
+    async fn get_gains(
+        &self,
+        username: impl IntoUsername,
+        query: SnapshotQuery,
+    ) -> Result<PlayerGain, anyhow::Error> {
+        self.request(
+            Method::GET,
+            PlayerEndPoints::Gains(username.into_username()?),
+            Some(query.to_query()?),
+        )
+        .await
+    }
+
+    /// Gets the gains of many players within a period or date range, at most `concurrency` at a time
+    pub async fn get_gains_many(
+        &self,
+        usernames: impl IntoIterator<Item = Username>,
+        query: SnapshotQuery,
+        concurrency: usize,
+    ) -> BulkResults<PlayerGain> {
+        fetch_many(usernames, concurrency, |username| {
+            self.get_gains(username, query.clone())
+        })
+        .await
     }
 
-    /// Get a player's records by username
+    /// Get a player's records by username, optionally filtered by period and metric
     /// [Get Player Records](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-records)
     pub async fn get_records(
         &self,
-        username: Username,
-        period: Option<Period>,
-        metric: Option<Metric>,
+        username: impl IntoUsername,
+        query: RecordQuery,
     ) -> Result<Vec<Record>, anyhow::Error> {
-        let mut queries = Vec::new();
-        if let Some(period) = period.clone() {
-            queries.push(("period".to_string(), period.as_str().to_string()));
-        }
-        if let Some(metric) = metric.clone() {
-            queries.push(("metric".to_string(), metric.to_string()));
-        }
+        self.request(
+            Method::GET,
+            PlayerEndPoints::Records(username.into_username()?),
+            Some(query.to_query()?),
+        )
+        .await
+    }
 
-        let result = self
-            .client
-            .get(self.get_url(PlayerEndPoints::Records(username), Some(queries)))
-            .send()
-            .await;
-        handle_response(result).await
+    /// Get a player's snapshots by username, within either a period or a date range
+    /// [Get Player Snapshots](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-snapshots)
+    pub async fn get_snapshots(
+        &self,
+        username: impl IntoUsername,
+        query: SnapshotQuery,
+    ) -> Result<Vec<SnapShot>, anyhow::Error> {
+        self.request(
+            Method::GET,
+            PlayerEndPoints::Snapshots(username.into_username()?),
+            Some(query.to_query()?),
+        )
+        .await
+    }
+
+    /// Gets the snapshots of many players within a period or date range, at most `concurrency` at a time
+    pub async fn get_snapshots_many(
+        &self,
+        usernames: impl IntoIterator<Item = Username>,
+        query: SnapshotQuery,
+        concurrency: usize,
+    ) -> BulkResults<Vec<SnapShot>> {
+        fetch_many(usernames, concurrency, |username| {
+            self.get_snapshots(username, query.clone())
+        })
+        .await
     }
 
     /// Get a player's snapshots by username and within a period
     /// [Get Player Snapshots](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-snapshots)
     pub async fn get_snapshots_by_period(
         &self,
-        username: Username,
+        username: impl IntoUsername,
         period: Period,
     ) -> Result<Vec<SnapShot>, anyhow::Error> {
-        let result = self
-            .client
-            .get(
-                self.get_url(
-                    PlayerEndPoints::Snapshots(username),
-                    Some(vec![("period".to_string(), period.as_str().to_string())]),
-                )
-                .as_str(),
-            )
-            .send()
-            .await;
-        handle_response(result).await
+        self.get_snapshots(username, SnapshotQuery::from(period))
+            .await
     }
 
     /// Get a player's snapshot by username with a start and end date
     /// [Get Player Snapshots](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-snapshots)
     pub async fn get_snapshots_by_date(
         &self,
-        username: Username,
-        start_date: DateTime<Utc>,
-        end_date: DateTime<Utc>,
+        username: impl IntoUsername,
+        date_range: DateRange,
     ) -> Result<Vec<SnapShot>, anyhow::Error> {
-        let full_url = self.get_url(
-            PlayerEndPoints::Snapshots(username),
-            Some(vec![
-                (
-                    "startDate".to_string(),
-                    start_date.format("%Y-%m-%d %H:%M:%S").to_string(),
-                ),
-                (
-                    "endDate".to_string(),
-                    end_date.format("%Y-%m-%d %H:%M:%S").to_string(),
-                ),
-            ]),
-        );
-
-        let result = self.client.get(full_url).send().await;
-        handle_response(result).await
+        self.get_snapshots(username, SnapshotQuery::from(date_range))
+            .await
     }
 
     /// Get a player's snapshots timeline by username and within a period
@@ -417,87 +489,68 @@ impl PlayerClient {
     /// [Get Player Snapshots Timeline](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-snapshots-timeline)
     pub async fn get_snapshots_timeline(
         &self,
-        username: Username,
+        username: impl IntoUsername,
         metric: Metric,
         period: Period,
     ) -> Result<Vec<TimelineDatapoint>, anyhow::Error> {
-        let result = self
-            .client
-            .get(
-                self.get_url(
-                    PlayerEndPoints::SnapshotsTimeline(username),
-                    Some(vec![
-                        ("period".to_string(), period.as_str().to_string()),
-                        ("metric".to_string(), metric.to_string()),
-                    ]),
-                )
-                .as_str(),
-            )
-            .send()
-            .await;
-        handle_response(result).await
+        self.get_timeline(username, metric, SnapshotQuery::from(period))
+            .await
     }
 
     ///Gets a player's snapshots timeline by username with a start and end date
     /// [Get Player Snapshots Timeline](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-snapshots-timeline)
     pub async fn get_snapshots_timeline_by_date(
         &self,
-        username: Username,
+        username: impl IntoUsername,
         metric: Metric,
-        start_date: DateTime<Utc>,
-        end_date: DateTime<Utc>,
+        date_range: DateRange,
     ) -> Result<Vec<TimelineDatapoint>, anyhow::Error> {
-        let full_url = self.get_url(
-            PlayerEndPoints::SnapshotsTimeline(username),
-            Some(vec![
-                (
-                    "startDate".to_string(),
-                    start_date.format("%Y-%m-%d %H:%M:%S").to_string(),
-                ),
-                (
-                    "endDate".to_string(),
-                    end_date.format("%Y-%m-%d %H:%M:%S").to_string(),
-                ),
-                ("metric".to_string(), metric.to_string()),
-            ]),
-        );
+        self.get_timeline(username, metric, SnapshotQuery::from(date_range))
+            .await
+    }
 
-        let result = self.client.get(full_url).send().await;
-        handle_response(result).await
+    async fn get_timeline(
+        &self,
+        username: impl IntoUsername,
+        metric: Metric,
+        query: SnapshotQuery,
+    ) -> Result<Vec<TimelineDatapoint>, anyhow::Error> {
+        let mut queries = query.to_query()?;
+        queries.push(("metric".to_string(), metric.to_string()));
+        self.request(
+            Method::GET,
+            PlayerEndPoints::SnapshotsTimeline(username.into_username()?),
+            Some(queries),
+        )
+        .await
     }
 
     /// Get a player's name changes by username
     /// [Get Player Name Changes](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-name-changes)
     pub async fn get_name_changes(
         &self,
-        username: Username,
+        username: impl IntoUsername,
     ) -> Result<Vec<NameChange>, anyhow::Error> {
-        let result = self
-            .client
-            .get(
-                self.get_url(PlayerEndPoints::NameChange(username), None)
-                    .as_str(),
-            )
-            .send()
-            .await;
-        handle_response::<Vec<NameChange>>(result).await
+        self.request::<Vec<NameChange>>(
+            Method::GET,
+            PlayerEndPoints::NameChange(username.into_username()?),
+            None,
+        )
+        .await
     }
 
     /// Get a player's archives by username
     /// [Get Player Archives](https://docs.wiseoldman.net/players-api/player-endpoints#get-player-archives)
     pub async fn get_archives(
         &self,
-        username: Username,
+        username: impl IntoUsername,
     ) -> Result<Vec<PlayerArchive>, anyhow::Error> {
-        let result = self
-            .client
-            .get(
-                self.get_url(PlayerEndPoints::Archives(username), None)
-                    .as_str(),
-            )
-            .send()
-            .await;
-        handle_response(result).await
+        self.request(
+            Method::GET,
+            PlayerEndPoints::Archives(username.into_username()?),
+            None,
+        )
+        .await
     }
 }
 
@@ -506,6 +559,8 @@ mod tests {
     use crate::models::competition::CompetitionStatus;
     use crate::models::global_enums::Skill::Overall;
     use crate::models::global_enums::{Metric, Period};
+    use crate::models::global_types::InvalidUsername;
+    use crate::query::{CompetitionQuery, DateRange, QueryError, RecordQuery};
     use crate::{Pagination, WomClient};
     use chrono::TimeZone;
     use httpmock::prelude::*;
@@ -514,6 +569,14 @@ mod tests {
     const CONTENT_TYPE: &str = "content-type";
     const APPLICATION_JSON: &str = "application/json";
 
+    fn first_week_of_2021() -> DateRange {
+        DateRange::new(
+            chrono::Utc.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap(),
+            chrono::Utc.with_ymd_and_hms(2021, 1, 7, 0, 0, 0).unwrap(),
+        )
+        .unwrap()
+    }
+
     #[tokio::test]
     async fn player_search_test() {
         let server = MockServer::start();
@@ -626,6 +689,22 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn invalid_username_is_not_sent_test() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.any_request();
+            then.status(200);
+        });
+
+        let wom_client = WomClient::new_with_base_url(server.base_url().to_string(), None);
+        let result = wom_client.player_client.get_details("IFat Fingers!").await;
+
+        mock.assert_hits(0);
+        let err = result.unwrap_err();
+        assert!(err.downcast_ref::<InvalidUsername>().is_some());
+    }
+
     #[tokio::test]
     async fn player_details_by_id_test() {
         let server = MockServer::start();
@@ -705,7 +784,7 @@ mod tests {
         let wom_client = WomClient::new_with_base_url(server.base_url().to_string(), None);
         let result = wom_client
             .player_client
-            .get_competitions("IFat Fingers".to_string(), None, None)
+            .get_competitions("IFat Fingers".to_string(), CompetitionQuery::new())
             .await;
         mock.assert();
         assert!(result.is_ok());
@@ -732,11 +811,12 @@ mod tests {
             .player_client
             .get_competitions(
                 "IFat Fingers".to_string(),
-                Some(CompetitionStatus::Finished),
-                Some(Pagination {
-                    limit: Some(10),
-                    offset: Some(10),
-                }),
+                CompetitionQuery::new()
+                    .status(CompetitionStatus::Finished)
+                    .pagination(Pagination {
+                        limit: Some(10),
+                        offset: Some(10),
+                    }),
             )
             .await;
         mock.assert();
@@ -762,8 +842,7 @@ mod tests {
             .player_client
             .get_competitions(
                 "IFat Fingers".to_string(),
-                Some(CompetitionStatus::Finished),
-                None,
+                CompetitionQuery::new().status(CompetitionStatus::Finished),
             )
             .await;
         mock.assert();
@@ -791,8 +870,7 @@ mod tests {
             .player_client
             .get_competitions(
                 "IFat Fingers".to_string(),
-                None,
-                Some(Pagination {
+                CompetitionQuery::new().pagination(Pagination {
                     limit: Some(10),
                     offset: Some(10),
                 }),
@@ -891,7 +969,7 @@ mod tests {
 
         let result = wom_client
             .player_client
-            .get_records("IFat Fingers".to_string(), None, None)
+            .get_records("IFat Fingers".to_string(), RecordQuery::new())
             .await;
         mock.assert();
         assert!(result.is_ok());
@@ -918,8 +996,9 @@ mod tests {
             .player_client
             .get_records(
                 "IFat Fingers".to_string(),
-                Some(Period::Week),
-                Some(Metric::Skill(Overall)),
+                RecordQuery::new()
+                    .period(Period::Week)
+                    .metric(Metric::Skill(Overall)),
             )
             .await;
         mock.assert();
@@ -959,8 +1038,8 @@ mod tests {
         let mock = server.mock(|when, then| {
             when.method(GET)
                 .path(format!("{}/IFat%20Fingers/snapshots", BASE_URL))
-                .query_param("startDate", "2021-01-01 00:00:00")
-                .query_param("endDate", "2021-01-07 00:00:00");
+                .query_param("startDate", "2021-01-01T00:00:00.000Z")
+                .query_param("endDate", "2021-01-07T00:00:00.000Z");
             then.status(200)
                 .header(CONTENT_TYPE, APPLICATION_JSON)
                 .body_from_file("./tests/mocks/player/player_snapshots.json");
@@ -969,11 +1048,7 @@ mod tests {
         let wom_client = WomClient::new_with_base_url(server.base_url().to_string(), None);
         let result = wom_client
             .player_client
-            .get_snapshots_by_date(
-                "IFat Fingers".to_string(),
-                chrono::Utc.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap(),
-                chrono::Utc.with_ymd_and_hms(2021, 1, 7, 0, 0, 0).unwrap(),
-            )
+            .get_snapshots_by_date("IFat Fingers".to_string(), first_week_of_2021())
             .await;
 
         mock.assert();
@@ -1018,8 +1093,8 @@ mod tests {
         let mock = server.mock(|when, then| {
             when.method(GET)
                 .path(format!("{}/IFat%20Fingers/snapshots/timeline", BASE_URL))
-                .query_param("startDate", "2021-01-01 00:00:00")
-                .query_param("endDate", "2021-01-07 00:00:00")
+                .query_param("startDate", "2021-01-01T00:00:00.000Z")
+                .query_param("endDate", "2021-01-07T00:00:00.000Z")
                 .query_param("metric", "overall");
             then.status(200)
                 .header(CONTENT_TYPE, APPLICATION_JSON)
@@ -1032,8 +1107,7 @@ mod tests {
             .get_snapshots_timeline_by_date(
                 "IFat Fingers".to_string(),
                 Metric::Skill(Overall),
-                chrono::Utc.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap(),
-                chrono::Utc.with_ymd_and_hms(2021, 1, 7, 0, 0, 0).unwrap(),
+                first_week_of_2021(),
             )
             .await;
 
@@ -1087,4 +1161,38 @@ mod tests {
         assert!(result.is_ok());
         result.unwrap();
     }
+
+    #[tokio::test]
+    async fn player_invalid_query_is_not_sent_test() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("{}/IFat%20Fingers/competitions", BASE_URL));
+            then.status(200)
+                .header(CONTENT_TYPE, APPLICATION_JSON)
+                .body_from_file("./tests/mocks/player/player_competition_participation.json");
+        });
+
+        let wom_client = WomClient::new_with_base_url(server.base_url().to_string(), None);
+        let result = wom_client
+            .player_client
+            .get_competitions(
+                "IFat Fingers".to_string(),
+                CompetitionQuery::new().pagination(Pagination {
+                    limit: Some(0),
+                    offset: None,
+                }),
+            )
+            .await;
+
+        mock.assert_hits(0);
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<QueryError>(),
+            Some(&QueryError::InvalidPagination {
+                limit: 0,
+                offset: 0
+            })
+        );
+    }
 }
@@ -0,0 +1,178 @@
+use crate::transport::Response;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+type SharedResponse = Shared<BoxFuture<'static, Result<Response, Arc<anyhow::Error>>>>;
+
+/// A failed request that other callers shared. It reads as the original error, which is kept
+/// as its source so it can still be found with `err.chain()` or `err.root_cause()`
+#[derive(Debug, Clone)]
+pub(crate) struct SharedError(Arc<anyhow::Error>);
+
+impl Display for SharedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Error for SharedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.0.as_ref().as_ref())
+    }
+}
+
+/// Lets concurrent identical GET requests share one in-flight call to the api.
+/// Each caller gets its own copy of the response to decode
+#[derive(Default)]
+pub(crate) struct Coalescer {
+    in_flight: Arc<Mutex<HashMap<String, SharedResponse>>>,
+}
+
+impl Coalescer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `send` for `key`, unless a request for the same key is already in flight,
+    /// in which case it waits for that one's response instead
+    pub async fn run<F>(&self, key: String, send: F) -> Result<Response, anyhow::Error>
+    where
+        F: Future<Output = Result<Response, anyhow::Error>> + Send + 'static,
+    {
+        let shared = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(&key) {
+                Some(shared) => shared.clone(),
+                None => {
+                    let map = self.in_flight.clone();
+                    let cleanup_key = key.clone();
+                    let shared = async move {
+                        let result = send.await.map_err(Arc::new);
+                        // Later requests for the same key start a fresh call
+                        map.lock().unwrap().remove(&cleanup_key);
+                        result
+                    }
+                    .boxed()
+                    .shared();
+                    in_flight.insert(key, shared.clone());
+                    shared
+                }
+            }
+        };
+        // A caller left holding the only copy of an error gets it back as it was
+        shared.await.map_err(|err| match Arc::try_unwrap(err) {
+            Ok(err) => err,
+            Err(err) => anyhow::Error::new(SharedError(err)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coalesce::SharedError;
+    use crate::transport::{Request, Response};
+    use crate::WomClient;
+    use std::io::ErrorKind;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn counting_client(count: Arc<AtomicUsize>) -> WomClient<impl crate::transport::Transport> {
+        let transport = move |request: Request| {
+            let count = count.clone();
+            async move {
+                count.fetch_add(1, Ordering::SeqCst);
+                // Give the other callers a chance to join before answering
+                tokio::task::yield_now().await;
+                let fixture = if request.url.contains("/groups/") {
+                    "./tests/mocks/group/group_detail.json"
+                } else {
+                    "./tests/mocks/player/player_details.json"
+                };
+                Ok(Response {
+                    status: reqwest::StatusCode::OK,
+                    headers: vec![],
+                    body: std::fs::read(fixture)?,
+                })
+            }
+        };
+        WomClient::new_with_transport(transport, "http://fake".to_string())
+    }
+
+    #[tokio::test]
+    async fn concurrent_identical_gets_share_one_request_test() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let wom_client = counting_client(count.clone());
+
+        let (first, second, third) = tokio::join!(
+            wom_client.player_client.get_details("zezima".to_string()),
+            wom_client.player_client.get_details("zezima".to_string()),
+            wom_client.group_client.get_group_details(1),
+        );
+
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(first.unwrap().display_name, "Zezima");
+        assert_eq!(second.unwrap().display_name, "Zezima");
+        assert!(third.is_ok());
+    }
+
+    #[tokio::test]
+    async fn sequential_gets_are_not_coalesced_test() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let wom_client = counting_client(count.clone());
+
+        for _ in 0..2 {
+            let result = wom_client
+                .player_client
+                .get_details("zezima".to_string())
+                .await;
+            assert!(result.is_ok());
+        }
+
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn errors_keep_their_type_test() {
+        let transport = |_: Request| async {
+            // Give the other callers a chance to join before failing
+            tokio::task::yield_now().await;
+            Err(std::io::Error::new(ErrorKind::ConnectionRefused, "refused").into())
+        };
+        let wom_client = WomClient::new_with_transport(transport, "http://fake".to_string());
+        let get = || wom_client.player_client.get_details("zezima".to_string());
+
+        // A single caller gets the transport's error itself
+        let err = get().await.unwrap_err();
+        let source = err.downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(source.kind(), ErrorKind::ConnectionRefused);
+
+        // Callers that shared the request find it as the root cause
+        let (first, second) = tokio::join!(get(), get());
+        let errors = [first.unwrap_err(), second.unwrap_err()];
+        assert!(errors
+            .iter()
+            .any(|err| err.downcast_ref::<SharedError>().is_some()));
+        for err in errors {
+            let source = err.root_cause().downcast_ref::<std::io::Error>().unwrap();
+            assert_eq!(source.kind(), ErrorKind::ConnectionRefused);
+            assert_eq!(err.to_string(), "refused");
+        }
+    }
+
+    #[tokio::test]
+    async fn posts_are_not_coalesced_test() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let wom_client = counting_client(count.clone());
+
+        let _ = tokio::join!(
+            wom_client.player_client.update("zezima".to_string()),
+            wom_client.player_client.update("zezima".to_string()),
+        );
+
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,685 @@
+use crate::archive::{FieldType, Kind, METRICS};
+use crate::models::global_types::Username;
+use crate::models::name::NameChange;
+use crate::models::player::{PlayerGain, SnapShot};
+use crate::models::record::Record;
+use anyhow::{anyhow, bail};
+use arrow_array::{
+    Array, ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray, TimestampMillisecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, SecondsFormat, Utc};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::ChunkReader;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Stored in each schema's metadata. Columns are only ever added, so files written with an
+/// older version still read. Metric columns they lack read as unranked, like older snapshot
+/// archives, and other nullable columns as null
+pub const SCHEMA_VERSION: &str = "1";
+const SCHEMA_VERSION_KEY: &str = "wom_rs.schema_version";
+
+/// What a column holds. Timestamps are milliseconds in UTC, the precision the api returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Integer,
+    Float,
+    Text,
+    Timestamp,
+    /// Nested data kept as a JSON string
+    Json,
+}
+
+impl ColumnType {
+    fn data_type(&self) -> DataType {
+        match self {
+            ColumnType::Integer => DataType::Int64,
+            ColumnType::Float => DataType::Float64,
+            ColumnType::Text | ColumnType::Json => DataType::Utf8,
+            ColumnType::Timestamp => DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+        }
+    }
+}
+
+/// A column and where its value sits in the model's serialized form
+#[derive(Debug, Clone)]
+struct Column {
+    name: String,
+    pointer: String,
+    column_type: ColumnType,
+    nullable: bool,
+    /// What the column reads as in a batch written before it was added
+    missing: Option<Value>,
+}
+
+impl Column {
+    fn new(name: &str, pointer: &str, column_type: ColumnType, nullable: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            pointer: pointer.to_string(),
+            column_type,
+            nullable,
+            missing: None,
+        }
+    }
+
+    fn missing(mut self, missing: Value) -> Self {
+        self.missing = Some(missing);
+        self
+    }
+}
+
+/// Models that convert to and from Arrow record batches, one row each
+pub trait Columnar: Sized {
+    /// The same for every batch, so files from different runs can be queried together
+    fn schema() -> SchemaRef;
+
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch, anyhow::Error>;
+
+    fn from_batch(batch: &RecordBatch) -> Result<Vec<Self>, anyhow::Error>;
+}
+
+/// One column per metric and field of its `data`, such as `attack_experience` and
+/// `zulrah_kills`
+impl Columnar for SnapShot {
+    fn schema() -> SchemaRef {
+        schema(&snapshot_columns())
+    }
+
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch, anyhow::Error> {
+        let rows = rows
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<_, _>>()?;
+        to_batch(&snapshot_columns(), rows)
+    }
+
+    fn from_batch(batch: &RecordBatch) -> Result<Vec<Self>, anyhow::Error> {
+        from_batch(&snapshot_columns(), batch, |mut row| {
+            name_metrics(&mut row["data"]);
+            Ok(serde_json::from_value(row)?)
+        })
+    }
+}
+
+fn snapshot_columns() -> Vec<Column> {
+    let mut columns = vec![
+        Column::new("id", "/id", ColumnType::Integer, false),
+        Column::new("player_id", "/playerId", ColumnType::Integer, false),
+        Column::new("created_at", "/createdAt", ColumnType::Timestamp, false),
+        Column::new("imported_at", "/importedAt", ColumnType::Timestamp, true),
+    ];
+    for (kind, key, field, field_type) in metric_fields() {
+        let column_type = match field_type {
+            FieldType::Integer => ColumnType::Integer,
+            FieldType::Float => ColumnType::Float,
+        };
+        columns.push(
+            Column::new(
+                &format!("{}_{}", key, field),
+                &format!("/data/{}/{}/{}", kind.group(), key, field),
+                column_type,
+                false,
+            )
+            .missing(kind.missing(field)),
+        );
+    }
+    columns
+}
+
+/// A player's gains with their username, as [PlayerGain] does not say whose they are.
+/// Each metric field has `_gained`, `_start` and `_end` columns, such as
+/// `attack_experience_gained`. Activities the api left out are null
+impl Columnar for (Username, PlayerGain) {
+    fn schema() -> SchemaRef {
+        schema(&gain_columns())
+    }
+
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch, anyhow::Error> {
+        let rows = rows
+            .iter()
+            .map(|(username, gain)| {
+                let mut row = serde_json::to_value(gain)?;
+                row["username"] = Value::from(username.as_str());
+                Ok(row)
+            })
+            .collect::<Result<_, serde_json::Error>>()?;
+        to_batch(&gain_columns(), rows)
+    }
+
+    fn from_batch(batch: &RecordBatch) -> Result<Vec<Self>, anyhow::Error> {
+        from_batch(&gain_columns(), batch, |mut row| {
+            name_metrics(&mut row["data"]);
+            let username = serde_json::from_value(row["username"].take())?;
+            Ok((username, serde_json::from_value(row)?))
+        })
+    }
+}
+
+fn gain_columns() -> Vec<Column> {
+    let mut columns = vec![
+        Column::new("username", "/username", ColumnType::Text, false),
+        Column::new("starts_at", "/startsAt", ColumnType::Timestamp, true),
+        Column::new("ends_at", "/endsAt", ColumnType::Timestamp, true),
+    ];
+    for (kind, key, field, _) in metric_fields() {
+        for progress in ["gained", "start", "end"] {
+            let missing = match progress {
+                "gained" => Value::from(0.0),
+                _ => kind.missing(field),
+            };
+            columns.push(
+                Column::new(
+                    &format!("{}_{}_{}", key, field, progress),
+                    &format!("/data/{}/{}/{}/{}", kind.group(), key, field, progress),
+                    ColumnType::Float,
+                    kind == Kind::Activity,
+                )
+                .missing(missing),
+            );
+        }
+    }
+    columns
+}
+
+/// The metric is its snake case name, such as `zulrah`
+impl Columnar for Record {
+    fn schema() -> SchemaRef {
+        schema(&record_columns())
+    }
+
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch, anyhow::Error> {
+        let rows = rows
+            .iter()
+            .map(|record| {
+                let mut row = serde_json::to_value(record)?;
+                row["metric"] = Value::from(record.metric.to_string());
+                Ok(row)
+            })
+            .collect::<Result<_, serde_json::Error>>()?;
+        to_batch(&record_columns(), rows)
+    }
+
+    fn from_batch(batch: &RecordBatch) -> Result<Vec<Self>, anyhow::Error> {
+        from_batch(&record_columns(), batch, deserialize)
+    }
+}
+
+fn record_columns() -> Vec<Column> {
+    vec![
+        Column::new("id", "/id", ColumnType::Integer, false),
+        Column::new("player_id", "/playerId", ColumnType::Integer, false),
+        Column::new("period", "/period", ColumnType::Text, false),
+        Column::new("metric", "/metric", ColumnType::Text, false),
+        Column::new("value", "/value", ColumnType::Float, false),
+        Column::new("updated_at", "/updatedAt", ColumnType::Timestamp, false),
+    ]
+}
+
+/// The review context and player are kept as JSON
+impl Columnar for NameChange {
+    fn schema() -> SchemaRef {
+        schema(&name_change_columns())
+    }
+
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch, anyhow::Error> {
+        let rows = rows
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<_, _>>()?;
+        to_batch(&name_change_columns(), rows)
+    }
+
+    fn from_batch(batch: &RecordBatch) -> Result<Vec<Self>, anyhow::Error> {
+        from_batch(&name_change_columns(), batch, deserialize)
+    }
+}
+
+fn name_change_columns() -> Vec<Column> {
+    vec![
+        Column::new("id", "/id", ColumnType::Integer, false),
+        Column::new("player_id", "/playerId", ColumnType::Integer, false),
+        Column::new("old_name", "/oldName", ColumnType::Text, false),
+        Column::new("new_name", "/newName", ColumnType::Text, false),
+        Column::new("status", "/status", ColumnType::Text, false),
+        Column::new("review_context", "/reviewContext", ColumnType::Json, true),
+        Column::new("resolved_at", "/resolvedAt", ColumnType::Timestamp, true),
+        Column::new("created_at", "/createdAt", ColumnType::Timestamp, false),
+        Column::new("updated_at", "/updatedAt", ColumnType::Timestamp, false),
+        Column::new("player", "/player", ColumnType::Json, true),
+    ]
+}
+
+/// Writes batches of a model to a Parquet file, compressed with Snappy
+pub struct ParquetWriter<W: Write + Send, T: Columnar> {
+    writer: ArrowWriter<W>,
+    rows: std::marker::PhantomData<T>,
+}
+
+impl<W: Write + Send, T: Columnar> ParquetWriter<W, T> {
+    pub fn new(writer: W) -> Result<Self, anyhow::Error> {
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        Ok(Self {
+            writer: ArrowWriter::try_new(writer, T::schema(), Some(properties))?,
+            rows: std::marker::PhantomData,
+        })
+    }
+
+    pub fn write(&mut self, rows: &[T]) -> Result<(), anyhow::Error> {
+        self.writer.write(&T::to_batch(rows)?)?;
+        Ok(())
+    }
+
+    /// Writes the file's footer. The file can not be read without it
+    pub fn close(self) -> Result<(), anyhow::Error> {
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+/// Reads a Parquet file written by [ParquetWriter], or any file with the same columns,
+/// back into the crate's models
+pub fn read_parquet<T: Columnar>(
+    reader: impl ChunkReader + 'static,
+) -> Result<Vec<T>, anyhow::Error> {
+    let mut rows = vec![];
+    for batch in ParquetRecordBatchReaderBuilder::try_new(reader)?.build()? {
+        rows.extend(T::from_batch(&batch?)?);
+    }
+    Ok(rows)
+}
+
+fn deserialize<T: DeserializeOwned>(row: Value) -> Result<T, anyhow::Error> {
+    Ok(serde_json::from_value(row)?)
+}
+
+/// Every metric field in the order of [METRICS]
+fn metric_fields() -> impl Iterator<Item = (Kind, &'static str, &'static str, FieldType)> {
+    METRICS.iter().flat_map(|(kind, key)| {
+        kind.fields()
+            .iter()
+            .map(move |(field, field_type)| (*kind, *key, *field, *field_type))
+    })
+}
+
+/// Puts back the `metric` each metric repeats, and turns activities that had no
+/// columns set back into nulls
+fn name_metrics(data: &mut Value) {
+    for (kind, key) in METRICS.iter() {
+        let metric = &mut data[kind.group()][key];
+        match metric.as_object_mut() {
+            Some(metric) => {
+                metric.insert("metric".to_string(), Value::from(*key));
+            }
+            None => *metric = Value::Null,
+        }
+    }
+}
+
+fn schema(columns: &[Column]) -> SchemaRef {
+    let fields: Vec<Field> = columns
+        .iter()
+        .map(|column| {
+            Field::new(
+                &column.name,
+                column.column_type.data_type(),
+                column.nullable,
+            )
+        })
+        .collect();
+    let metadata = HashMap::from([(SCHEMA_VERSION_KEY.to_string(), SCHEMA_VERSION.to_string())]);
+    Arc::new(Schema::new_with_metadata(fields, metadata))
+}
+
+/// Builds a batch from the models' serialized forms
+fn to_batch(columns: &[Column], rows: Vec<Value>) -> Result<RecordBatch, anyhow::Error> {
+    let mut arrays: Vec<ArrayRef> = vec![];
+    for column in columns {
+        let cells: Vec<&Value> = rows
+            .iter()
+            .map(|row| row.pointer(&column.pointer).unwrap_or(&Value::Null))
+            .collect();
+        if !column.nullable && cells.iter().any(|cell| cell.is_null()) {
+            bail!("{} is missing in a row", column.name);
+        }
+        let invalid = || {
+            anyhow!(
+                "{} does not hold {:?} values",
+                column.name,
+                column.column_type
+            )
+        };
+        let array: ArrayRef = match column.column_type {
+            ColumnType::Integer => Arc::new(Int64Array::from(
+                cells
+                    .iter()
+                    .map(|cell| optional(cell, Value::as_i64).ok_or_else(invalid))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            ColumnType::Float => Arc::new(Float64Array::from(
+                cells
+                    .iter()
+                    .map(|cell| optional(cell, Value::as_f64).ok_or_else(invalid))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            ColumnType::Text => Arc::new(StringArray::from(
+                cells
+                    .iter()
+                    .map(|cell| optional(cell, Value::as_str).ok_or_else(invalid))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            ColumnType::Json => Arc::new(StringArray::from(
+                cells
+                    .iter()
+                    .map(|cell| (!cell.is_null()).then(|| cell.to_string()))
+                    .collect::<Vec<_>>(),
+            )),
+            ColumnType::Timestamp => Arc::new(
+                TimestampMillisecondArray::from(
+                    cells
+                        .iter()
+                        .map(|cell| {
+                            optional(cell, |cell| {
+                                let date: DateTime<Utc> = cell.as_str()?.parse().ok()?;
+                                Some(date.timestamp_millis())
+                            })
+                            .ok_or_else(invalid)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+                .with_timezone("UTC"),
+            ),
+        };
+        arrays.push(array);
+    }
+    Ok(RecordBatch::try_new(schema(columns), arrays)?)
+}
+
+/// `Some(None)` for a null, `None` when the value is not of the expected type
+fn optional<'a, T>(cell: &'a Value, read: impl Fn(&'a Value) -> Option<T>) -> Option<Option<T>> {
+    if cell.is_null() {
+        return Some(None);
+    }
+    read(cell).map(Some)
+}
+
+fn from_batch<T>(
+    columns: &[Column],
+    batch: &RecordBatch,
+    into_model: impl Fn(Value) -> Result<T, anyhow::Error>,
+) -> Result<Vec<T>, anyhow::Error> {
+    let mut rows = vec![Value::Object(Map::new()); batch.num_rows()];
+    for column in columns {
+        let Some(array) = batch.column_by_name(&column.name) else {
+            match &column.missing {
+                _ if column.nullable => continue,
+                Some(missing) => {
+                    for row in rows.iter_mut() {
+                        set_pointer(row, &column.pointer, missing.clone());
+                    }
+                    continue;
+                }
+                None => bail!("The batch has no {} column", column.name),
+            }
+        };
+        let cells = cells(column, array.as_ref())?;
+        for (row, cell) in rows.iter_mut().zip(cells) {
+            if !cell.is_null() {
+                set_pointer(row, &column.pointer, cell);
+            } else if !column.nullable {
+                bail!("{} is null in a row", column.name);
+            }
+        }
+    }
+    rows.into_iter().map(into_model).collect()
+}
+
+fn cells(column: &Column, array: &dyn Array) -> Result<Vec<Value>, anyhow::Error> {
+    let wrong_type = || {
+        anyhow!(
+            "{} is {} but should be {}",
+            column.name,
+            array.data_type(),
+            column.column_type.data_type()
+        )
+    };
+    let cells = match column.column_type {
+        ColumnType::Integer => {
+            let array = array
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .ok_or_else(wrong_type)?;
+            array
+                .iter()
+                .map(|cell| cell.map_or(Value::Null, Value::from))
+                .collect()
+        }
+        ColumnType::Float => {
+            let array = array
+                .as_any()
+                .downcast_ref::<Float64Array>()
+                .ok_or_else(wrong_type)?;
+            array
+                .iter()
+                .map(|cell| cell.map_or(Value::Null, Value::from))
+                .collect()
+        }
+        ColumnType::Text => {
+            let array = array
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(wrong_type)?;
+            array
+                .iter()
+                .map(|cell| cell.map_or(Value::Null, Value::from))
+                .collect()
+        }
+        ColumnType::Json => {
+            let array = array
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(wrong_type)?;
+            array
+                .iter()
+                .map(|cell| cell.map_or(Ok(Value::Null), serde_json::from_str))
+                .collect::<Result<_, _>>()?
+        }
+        ColumnType::Timestamp => {
+            let array = array
+                .as_any()
+                .downcast_ref::<TimestampMillisecondArray>()
+                .ok_or_else(wrong_type)?;
+            array
+                .iter()
+                .map(|cell| {
+                    let Some(millis) = cell else {
+                        return Ok(Value::Null);
+                    };
+                    let date = DateTime::from_timestamp_millis(millis)
+                        .ok_or_else(|| anyhow!("{} has an invalid timestamp", column.name))?;
+                    Ok(Value::from(
+                        date.to_rfc3339_opts(SecondsFormat::Millis, true),
+                    ))
+                })
+                .collect::<Result<_, anyhow::Error>>()?
+        }
+    };
+    Ok(cells)
+}
+
+/// Sets the value at a JSON pointer, creating the objects on the way
+fn set_pointer(row: &mut Value, pointer: &str, value: Value) {
+    let mut target = row;
+    for key in pointer.trim_start_matches('/').split('/') {
+        if !target.is_object() {
+            *target = Value::Object(Map::new());
+        }
+        target = target
+            .as_object_mut()
+            .unwrap()
+            .entry(key)
+            .or_insert(Value::Null);
+    }
+    *target = value;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::columnar::{read_parquet, Columnar, ParquetWriter, SCHEMA_VERSION};
+    use crate::fixtures::fixture;
+    use crate::models::global_types::Username;
+    use crate::models::name::NameChange;
+    use crate::models::player::{PlayerDetails, PlayerGain, SnapShot};
+    use crate::models::record::Record;
+    use arrow_array::RecordBatch;
+    use arrow_schema::DataType;
+    use chrono::Duration;
+    use std::fmt::Debug;
+    use std::fs::File;
+
+    fn snapshots() -> Vec<SnapShot> {
+        let details: PlayerDetails = fixture("player/player_details.json");
+        let first = details.latest_snapshot.unwrap();
+        let mut second = first.clone();
+        second.id += 1;
+        second.created_at += Duration::days(1);
+        second.imported_at = Some(second.created_at + Duration::milliseconds(250));
+        second.data.bosses.zulrah.kills += 12;
+        second.data.computed.ehb.value += 0.8;
+        vec![first, second]
+    }
+
+    /// Writes the rows to a Parquet file in two batches and reads them back
+    fn parquet_round_trip<T: Columnar + Clone + PartialEq + Debug>(name: &str, rows: Vec<T>) {
+        let path =
+            std::env::temp_dir().join(format!("wom_rs_{}_{}.parquet", name, std::process::id()));
+        let mut writer = ParquetWriter::<_, T>::new(File::create(&path).unwrap()).unwrap();
+        let (first, rest) = rows.split_at(1);
+        writer.write(first).unwrap();
+        writer.write(rest).unwrap();
+        writer.close().unwrap();
+
+        let read: Vec<T> = read_parquet(File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read, rows);
+    }
+
+    #[test]
+    fn snapshot_columns_test() {
+        let snapshots = snapshots();
+        let batch = SnapShot::to_batch(&snapshots).unwrap();
+
+        let schema = SnapShot::schema();
+        assert_eq!(batch.schema(), schema);
+        assert_eq!(schema.metadata()["wom_rs.schema_version"], SCHEMA_VERSION);
+        assert_eq!(schema.field(0).name(), "id");
+        assert_eq!(schema.field(4).name(), "overall_experience");
+        assert_eq!(
+            schema.field_with_name("zulrah_kills").unwrap().data_type(),
+            &DataType::Int64
+        );
+        assert_eq!(
+            schema.field_with_name("attack_ehp").unwrap().data_type(),
+            &DataType::Float64
+        );
+        assert_eq!(batch.num_rows(), 2);
+
+        assert_eq!(SnapShot::from_batch(&batch).unwrap(), snapshots);
+        parquet_round_trip("snapshots", snapshots);
+    }
+
+    #[test]
+    fn older_batch_test() {
+        let snapshots = snapshots();
+        let batch = SnapShot::to_batch(&snapshots).unwrap();
+        let schema = batch.schema();
+
+        // As if written before zulrah was added
+        let keep = |batch: &RecordBatch, drop: &dyn Fn(&str) -> bool| {
+            let indices: Vec<usize> = (0..schema.fields().len())
+                .filter(|index| !drop(schema.field(*index).name()))
+                .collect();
+            batch.project(&indices).unwrap()
+        };
+        let older = keep(&batch, &|name| name.starts_with("zulrah_"));
+
+        let read = SnapShot::from_batch(&older).unwrap();
+        assert_eq!(read.len(), snapshots.len());
+        for (read, written) in read.iter().zip(&snapshots) {
+            assert_eq!(read.data.skills, written.data.skills);
+            assert_eq!(read.data.bosses.zulrah.kills, -1);
+            assert_eq!(read.data.bosses.zulrah.rank, -1);
+            assert_eq!(read.data.bosses.zulrah.ehb, 0.0);
+            assert_eq!(read.data.bosses.vorkath, written.data.bosses.vorkath);
+        }
+
+        let without_id = keep(&batch, &|name| name == "id");
+        assert!(SnapShot::from_batch(&without_id).is_err());
+    }
+
+    #[test]
+    fn gain_columns_test() {
+        let gain: PlayerGain = fixture("player/player_gains.json");
+        let mut without_league = gain.clone();
+        without_league.data.activities.league_points = None;
+        without_league.starts_at = None;
+        let gains = vec![
+            (Username::new("Zezima").unwrap(), gain),
+            (Username::new("Lynx Titan").unwrap(), without_league),
+        ];
+
+        let batch = <(Username, PlayerGain)>::to_batch(&gains).unwrap();
+        let schema = batch.schema();
+        assert!(schema.field_with_name("attack_experience_gained").is_ok());
+        assert!(schema
+            .field_with_name("league_points_score_end")
+            .unwrap()
+            .is_nullable());
+        assert_eq!(
+            batch
+                .column_by_name("league_points_score_gained")
+                .unwrap()
+                .null_count(),
+            1
+        );
+
+        assert_eq!(<(Username, PlayerGain)>::from_batch(&batch).unwrap(), gains);
+        parquet_round_trip("gains", gains);
+    }
+
+    #[test]
+    fn record_and_name_change_columns_test() {
+        let records: Vec<Record> = fixture("player/player_records.json");
+        let batch = Record::to_batch(&records).unwrap();
+        assert_eq!(
+            batch
+                .column_by_name("metric")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<arrow_array::StringArray>()
+                .unwrap()
+                .value(0),
+            "agility"
+        );
+        parquet_round_trip("records", records);
+
+        let name_changes: Vec<NameChange> = fixture("group/group_name_changes.json");
+        assert!(name_changes
+            .iter()
+            .any(|name_change| name_change.player.is_some()));
+        parquet_round_trip("name_changes", name_changes);
+
+        // A batch missing a column the model needs is rejected rather than read as nulls
+        let batch = batch.project(&[0, 1, 2]).unwrap();
+        assert!(Record::from_batch(&batch).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Differences between a response and the model it was read into, found in strict mode
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ModelDrift {
+    /// The endpoint that returned the response, e.g. `player.details`
+    pub endpoint: &'static str,
+    /// Fields in the response that the model does not have, e.g. `latestSnapshot.data.skills.sailing`.
+    /// `[]` marks the items of a list
+    pub unknown_fields: Vec<String>,
+    /// Optional fields of the model that the response left out
+    pub missing_fields: Vec<String>,
+}
+
+impl ModelDrift {
+    /// A reporter that logs each drift as a warning, through `tracing` when the feature is enabled
+    pub fn warn(drift: &ModelDrift) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            endpoint = drift.endpoint,
+            unknown_fields = ?drift.unknown_fields,
+            missing_fields = ?drift.missing_fields,
+            "response does not match the model"
+        );
+        #[cfg(not(feature = "tracing"))]
+        log::warn!(
+            "{} response does not match the model, unknown fields {:?}, missing fields {:?}",
+            drift.endpoint,
+            drift.unknown_fields,
+            drift.missing_fields
+        );
+    }
+}
+
+/// Called with every [ModelDrift] found in strict mode
+pub type DriftReporter = Arc<dyn Fn(&ModelDrift) + Send + Sync>;
+
+/// Compares a response body with the model it was read into. The model is serialized back
+/// to json, so any key only in the body is a field the model dropped, and any null key only
+/// in the model is an optional field the api left out
+pub(crate) fn detect<ResponseType: Serialize>(
+    endpoint: &'static str,
+    body: &[u8],
+    model: &ResponseType,
+) -> Option<ModelDrift> {
+    let body: Value = serde_json::from_slice(body).ok()?;
+    let model = serde_json::to_value(model).ok()?;
+    let mut unknown_fields = BTreeSet::new();
+    let mut missing_fields = BTreeSet::new();
+    compare("", &body, &model, &mut unknown_fields, &mut missing_fields);
+    if unknown_fields.is_empty() && missing_fields.is_empty() {
+        return None;
+    }
+    Some(ModelDrift {
+        endpoint,
+        unknown_fields: unknown_fields.into_iter().collect(),
+        missing_fields: missing_fields.into_iter().collect(),
+    })
+}
+
+fn compare(
+    path: &str,
+    body: &Value,
+    model: &Value,
+    unknown_fields: &mut BTreeSet<String>,
+    missing_fields: &mut BTreeSet<String>,
+) {
+    let field_path = |key: &str| match path {
+        "" => key.to_string(),
+        path => format!("{}.{}", path, key),
+    };
+    match (body, model) {
+        (Value::Object(body), Value::Object(model)) => {
+            for (key, body_value) in body {
+                match model.get(key) {
+                    Some(model_value) => compare(
+                        &field_path(key),
+                        body_value,
+                        model_value,
+                        unknown_fields,
+                        missing_fields,
+                    ),
+                    None => {
+                        unknown_fields.insert(field_path(key));
+                    }
+                }
+            }
+            for (key, model_value) in model {
+                if model_value.is_null() && !body.contains_key(key) {
+                    missing_fields.insert(field_path(key));
+                }
+            }
+        }
+        (Value::Array(body), Value::Array(model)) => {
+            let item_path = format!("{}[]", path);
+            for (body_value, model_value) in body.iter().zip(model) {
+                compare(
+                    &item_path,
+                    body_value,
+                    model_value,
+                    unknown_fields,
+                    missing_fields,
+                );
+            }
+        }
+        // Values the model reshapes, like metrics, are not fields
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drift::{detect, ModelDrift};
+    use crate::models::competition::{PlayerCompetitionStanding, PlayerParticipation};
+    use crate::models::group::{Group, GroupCreateResponse, GroupDetail, PlayerMembership};
+    use crate::models::name::NameChange;
+    use crate::models::player::{
+        Achievement, AchievementProgress, AssertPlayerType, Player, PlayerArchive, PlayerDetails,
+        PlayerGain, SnapShot, TimelineDatapoint,
+    };
+    use crate::models::record::Record;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+
+    fn drift<Model: DeserializeOwned + Serialize>(fixture: &str) -> Option<ModelDrift> {
+        let body = std::fs::read(format!("./tests/mocks/{}", fixture)).unwrap();
+        let model: Model = serde_json::from_slice(&body)
+            .unwrap_or_else(|err| panic!("{} does not deserialize: {}", fixture, err));
+        detect("test", &body, &model)
+    }
+
+    #[test]
+    fn every_model_matches_its_fixture_test() {
+        let drifts = [
+            drift::<Vec<Achievement>>("player/player_achievements.json"),
+            drift::<Vec<AchievementProgress>>("player/player_achievement_progress.json"),
+            drift::<Vec<PlayerArchive>>("player/player_archives.json"),
+            drift::<AssertPlayerType>("player/player_assert_type.json"),
+            drift::<Vec<PlayerParticipation>>("player/player_competition_participation.json"),
+            drift::<Vec<PlayerCompetitionStanding>>("player/player_competition_standings.json"),
+            drift::<PlayerDetails>("player/player_details.json"),
+            drift::<PlayerGain>("player/player_gains.json"),
+            drift::<Vec<PlayerMembership>>("player/player_group_membership.json"),
+            drift::<Vec<Record>>("player/player_records.json"),
+            drift::<Vec<Player>>("player/player_search.json"),
+            drift::<Vec<SnapShot>>("player/player_snapshots.json"),
+            drift::<Vec<TimelineDatapoint>>("player/player_snapshots_timeline.json"),
+            drift::<GroupCreateResponse>("group/group_create_response.json"),
+            drift::<Vec<NameChange>>("group/group_name_changes.json"),
+            drift::<Vec<Group>>("group/group_search.json"),
+        ];
+        let drifted: Vec<_> = drifts.iter().flatten().collect();
+        assert!(drifted.is_empty(), "{:#?}", drifted);
+    }
+
+    #[test]
+    fn missing_optional_fields_test() {
+        // Player name changes leave out the player, as it is the one asked for
+        let name_changes = drift::<Vec<NameChange>>("player/player_name_changes.json").unwrap();
+        assert!(name_changes.unknown_fields.is_empty());
+        assert_eq!(name_changes.missing_fields, vec!["[].player"]);
+
+        let group = drift::<GroupDetail>("group/group_detail.json").unwrap();
+        assert!(group.unknown_fields.is_empty());
+        assert_eq!(group.missing_fields, vec!["socialLinks"]);
+    }
+
+    #[test]
+    fn unknown_fields_test() {
+        let body = std::fs::read("./tests/mocks/player/player_details.json").unwrap();
+        let mut json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        json["clanRank"] = "owner".into();
+        json["latestSnapshot"]["data"]["skills"]["sailing"] = serde_json::json!({"level": 1});
+        let body = serde_json::to_vec(&json).unwrap();
+        let details: PlayerDetails = serde_json::from_slice(&body).unwrap();
+
+        let drift = detect("player.details", &body, &details).unwrap();
+
+        assert_eq!(drift.endpoint, "player.details");
+        assert_eq!(
+            drift.unknown_fields,
+            vec!["clanRank", "latestSnapshot.data.skills.sailing"]
+        );
+        assert!(drift.missing_fields.is_empty());
+    }
+}
@@ -0,0 +1,43 @@
+use crate::transport::{Request, Response, Transport};
+use crate::WomClient;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::sync::{Arc, Mutex};
+
+/// The base url of clients from [fake_client]
+pub const FAKE_URL: &str = "http://fake";
+
+/// The raw body of a mock under `tests/mocks`, such as `player/player_details.json`
+pub fn fixture_bytes(path: &str) -> Vec<u8> {
+    std::fs::read(format!("./tests/mocks/{}", path)).unwrap()
+}
+
+/// A mock under `tests/mocks` read into a model
+pub fn fixture<T: DeserializeOwned>(path: &str) -> T {
+    serde_json::from_slice(&fixture_bytes(path)).unwrap()
+}
+
+/// A client answering each request with the status and body `route` gives for its method and
+/// its path after [FAKE_URL]. Requests are pushed to `requests` as `METHOD /path`
+pub fn fake_client<F>(requests: Arc<Mutex<Vec<String>>>, route: F) -> WomClient<impl Transport>
+where
+    F: Fn(&str, &str) -> (StatusCode, Vec<u8>) + Send + Sync + 'static,
+{
+    let transport = move |request: Request| {
+        let path = request.url.trim_start_matches(FAKE_URL).to_string();
+        let method = request.method.to_string();
+        requests
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", method, path));
+        let (status, body) = route(&method, &path);
+        async move {
+            Ok(Response {
+                status,
+                headers: vec![],
+                body,
+            })
+        }
+    };
+    WomClient::new_with_transport(transport, FAKE_URL.to_string())
+}
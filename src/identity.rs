@@ -0,0 +1,309 @@
+use crate::clients::player_client::PlayerClient;
+use crate::models::error::ApiError;
+use crate::models::global_types::{IntoUsername, PlayerId, Username};
+use crate::models::name::{NameChange, NameChangeStatus};
+use crate::models::player::{PlayerDetails, PlayerStatus};
+use crate::transport::Transport;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Who a username belongs to now
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedPlayer {
+    pub player_id: PlayerId,
+    pub username: Username,
+    pub display_name: String,
+    /// Set when the player lost their name to someone else and has not been restored.
+    /// The username is then the `archive` name Wise Old Man gave them
+    pub archived: bool,
+    /// Every approved name change, oldest first
+    pub renames: Vec<NameChange>,
+}
+
+impl ResolvedPlayer {
+    /// Every name the player has had, oldest first and ending with the current one
+    pub fn names(&self) -> Vec<Username> {
+        let mut names: Vec<Username> = vec![];
+        let renamed = self
+            .renames
+            .iter()
+            .flat_map(|rename| [&rename.old_name, &rename.new_name]);
+        for name in renamed {
+            if let Ok(name) = name.into_username() {
+                if names.last() != Some(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        if names.last() != Some(&self.username) {
+            names.push(self.username.clone());
+        }
+        names
+    }
+}
+
+#[derive(Default)]
+struct AliasGraph {
+    aliases: HashMap<Username, PlayerId>,
+    players: HashMap<PlayerId, ResolvedPlayer>,
+}
+
+/// Maps any name a player has had to who they are now, following name changes and archives.
+/// What it learns is cached, so each player costs a few requests the first time only
+pub struct IdentityResolver<'a, T: Transport> {
+    client: &'a PlayerClient<T>,
+    graph: Mutex<AliasGraph>,
+}
+
+impl<'a, T: Transport> IdentityResolver<'a, T> {
+    pub fn new(client: &'a PlayerClient<T>) -> Self {
+        Self {
+            client,
+            graph: Mutex::new(AliasGraph::default()),
+        }
+    }
+
+    /// Adds name changes fetched elsewhere, such as a group's, so the old names resolve
+    /// without looking them up
+    pub fn learn_name_changes(&self, name_changes: &[NameChange]) {
+        let mut graph = self.graph.lock().unwrap();
+        for name_change in approved(name_changes) {
+            for name in [&name_change.old_name, &name_change.new_name] {
+                if let Ok(name) = name.into_username() {
+                    graph.aliases.insert(name, name_change.player_id);
+                }
+            }
+        }
+    }
+
+    /// Forgets everything learned, for when names may have been reused since
+    pub fn clear(&self) {
+        *self.graph.lock().unwrap() = AliasGraph::default();
+    }
+
+    /// Finds the player who has, or last had, a name.
+    /// Names already seen resolve from the cache. Otherwise a player holding the name is used,
+    /// and failing that the name's archives, following a restored archive to its new name
+    pub async fn resolve(&self, name: impl IntoUsername) -> Result<ResolvedPlayer, anyhow::Error> {
+        self.resolve_username(name.into_username()?).await
+    }
+
+    fn resolve_username(
+        &self,
+        username: Username,
+    ) -> BoxFuture<'_, Result<ResolvedPlayer, anyhow::Error>> {
+        async move {
+            let known = {
+                let graph = self.graph.lock().unwrap();
+                graph
+                    .aliases
+                    .get(&username)
+                    .map(|player_id| (*player_id, graph.players.get(player_id).cloned()))
+            };
+            match known {
+                Some((_, Some(player))) => return Ok(player),
+                Some((player_id, None)) => {
+                    let details = self.client.get_details_by_id(player_id).await?;
+                    return self.learn(details, &username).await;
+                }
+                None => {}
+            }
+
+            let not_found = match self.client.get_details(&username).await {
+                Ok(details) => return self.learn(details, &username).await,
+                Err(err) if is_not_found(&err) => err,
+                Err(err) => return Err(err),
+            };
+
+            let archives = self.client.get_archives(&username).await?;
+            let Some(archive) = archives
+                .into_iter()
+                .filter(|archive| username == archive.previous_username.as_str())
+                .max_by_key(|archive| archive.created_at)
+            else {
+                return Err(not_found);
+            };
+            let player = match archive.restored_username.as_ref() {
+                Some(restored_username) => {
+                    self.resolve_username(restored_username.into_username()?)
+                        .await?
+                }
+                None => {
+                    let details = self.client.get_details_by_id(archive.player_id).await?;
+                    self.learn(details, &username).await?
+                }
+            };
+            self.graph
+                .lock()
+                .unwrap()
+                .aliases
+                .insert(username, player.player_id);
+            Ok(player)
+        }
+        .boxed()
+    }
+
+    /// Fetches the player's name changes and caches every name they have had
+    async fn learn(
+        &self,
+        details: PlayerDetails,
+        asked_for: &Username,
+    ) -> Result<ResolvedPlayer, anyhow::Error> {
+        let name_changes = self.client.get_name_changes(&details.username).await?;
+        let mut renames: Vec<NameChange> = approved(&name_changes).cloned().collect();
+        renames.sort_by_key(|rename| (rename.resolved_at.unwrap_or(rename.created_at), rename.id));
+        renames.dedup_by_key(|rename| rename.id);
+
+        let player = ResolvedPlayer {
+            player_id: details.id,
+            archived: details.status == PlayerStatus::Archived,
+            username: details.username,
+            display_name: details.display_name,
+            renames,
+        };
+        let mut graph = self.graph.lock().unwrap();
+        for name in player.names().into_iter().chain([asked_for.clone()]) {
+            graph.aliases.insert(name, player.player_id);
+        }
+        graph.players.insert(player.player_id, player.clone());
+        Ok(player)
+    }
+}
+
+fn approved(name_changes: &[NameChange]) -> impl Iterator<Item = &NameChange> {
+    name_changes
+        .iter()
+        .filter(|name_change| name_change.status == NameChangeStatus::Approved)
+}
+
+fn is_not_found(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ApiError>()
+        .is_some_and(|api_error| api_error.is_not_found())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::{self, fixture};
+    use crate::identity::IdentityResolver;
+    use crate::models::global_types::{PlayerId, Username};
+    use crate::models::name::NameChange;
+    use crate::transport::Transport;
+    use crate::WomClient;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+
+    fn details(id: i64, username: &str, status: &str) -> Value {
+        let mut details: Value = fixture("player/player_details.json");
+        details["id"] = id.into();
+        details["username"] = username.into();
+        details["displayName"] = username.into();
+        details["status"] = status.into();
+        details
+    }
+
+    /// chambou was KHX and bezrobotny before that. psikoi was archived, and lynx titan was
+    /// archived then restored as lynx titan 2
+    fn fake_client(requests: Arc<Mutex<Vec<String>>>) -> WomClient<impl Transport> {
+        fixtures::fake_client(requests, |_, path| {
+            let path = path
+                .trim_start_matches("/players/")
+                .replace("%20", " ")
+                .replace('_', " ")
+                .to_lowercase();
+            let body = match path.as_str() {
+                "chambou" | "id/300184" => details(300184, "chambou", "active"),
+                "chambou/names" => fixture("player/player_name_changes.json"),
+                "id/2" => details(2, "archive42392", "archived"),
+                "psikoi/archives" => fixture("player/player_archives.json"),
+                "lynx titan 2" => details(7, "lynx titan 2", "active"),
+                "lynx titan/archives" => json!([{
+                    "playerId": 7,
+                    "previousUsername": "lynx titan",
+                    "archiveUsername": "archive7",
+                    "restoredUsername": "lynx titan 2",
+                    "createdAt": "2023-01-01T00:00:00.000Z",
+                    "restoredAt": "2023-02-01T00:00:00.000Z",
+                }]),
+                path if path.ends_with("/names") || path.ends_with("/archives") => json!([]),
+                _ => {
+                    return (
+                        StatusCode::NOT_FOUND,
+                        br#"{"message":"Player not found."}"#.to_vec(),
+                    )
+                }
+            };
+            (StatusCode::OK, serde_json::to_vec(&body).unwrap())
+        })
+    }
+
+    #[tokio::test]
+    async fn resolve_follows_name_changes_test() {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let wom_client = fake_client(requests.clone());
+        let resolver = IdentityResolver::new(&wom_client.player_client);
+
+        let player = resolver.resolve("Chambou").await.unwrap();
+
+        assert_eq!(player.player_id, PlayerId(300184));
+        assert!(!player.archived);
+        assert_eq!(player.renames.len(), 2);
+        assert_eq!(player.names(), vec!["bezrobotny", "KHX", "chambou"]);
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec!["GET /players/Chambou", "GET /players/chambou/names"]
+        );
+
+        // Older names come from the cache
+        for old_name in ["KHX", "bezrobotny"] {
+            assert_eq!(resolver.resolve(old_name).await.unwrap(), player);
+        }
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn resolve_archived_and_restored_test() {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let wom_client = fake_client(requests.clone());
+        let resolver = IdentityResolver::new(&wom_client.player_client);
+
+        let archived = resolver.resolve("psikoi").await.unwrap();
+        assert_eq!(archived.player_id, PlayerId(2));
+        assert_eq!(archived.username, "archive42392");
+        assert!(archived.archived);
+
+        let restored = resolver.resolve("Lynx_Titan").await.unwrap();
+        assert_eq!(restored.player_id, PlayerId(7));
+        assert_eq!(restored.display_name, "lynx titan 2");
+
+        requests.lock().unwrap().clear();
+        assert_eq!(resolver.resolve("lynx titan").await.unwrap(), restored);
+        assert!(requests.lock().unwrap().is_empty());
+
+        let err = resolver.resolve("nobody").await.unwrap_err();
+        assert!(err.to_string().contains("Player not found."));
+    }
+
+    #[tokio::test]
+    async fn learned_name_changes_test() {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let wom_client = fake_client(requests.clone());
+        let resolver = IdentityResolver::new(&wom_client.player_client);
+        let name_changes: Vec<NameChange> =
+            serde_json::from_value(fixture("player/player_name_changes.json")).unwrap();
+        resolver.learn_name_changes(&name_changes);
+
+        let player = resolver
+            .resolve(Username::new("bezrobotny").unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(player.player_id, PlayerId(300184));
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec!["GET /players/id/300184", "GET /players/chambou/names"]
+        );
+    }
+}
@@ -0,0 +1,499 @@
+use crate::helpers::{format_number, sorted_gains};
+use crate::models::global_enums::Skill;
+use crate::models::player::SnapShotData;
+use crate::models::snapshot::BossMetric;
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use convert_case::{Case, Casing};
+use std::fmt::Display;
+use tiny_skia::{Color, Paint, Pixmap, Rect, Transform};
+
+const REGULAR_FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf");
+const BOLD_FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSans-Bold.ttf");
+
+const WIDTH: u32 = 640;
+const PADDING: f32 = 20.0;
+const HEADER_HEIGHT: f32 = 64.0;
+const SKILL_COLUMNS: usize = 3;
+const SKILL_CELL_HEIGHT: f32 = 34.0;
+const SECTION_TITLE_HEIGHT: f32 = 32.0;
+const BOSS_ROW_HEIGHT: f32 = 30.0;
+const LEADERBOARD_ROW_HEIGHT: f32 = 36.0;
+/// Room on the left of a bar for a rank and a twelve character username
+const NAME_COLUMN: f32 = 190.0;
+/// Room on the right of a bar for its value
+const VALUE_COLUMN: f32 = 70.0;
+
+/// How many bosses a stat card lists
+pub const TOP_BOSSES: usize = 5;
+
+const BACKGROUND: [u8; 3] = [0x1e, 0x1f, 0x22];
+const PANEL: [u8; 3] = [0x2b, 0x2d, 0x31];
+const TEXT: [u8; 3] = [0xf2, 0xf3, 0xf5];
+const MUTED: [u8; 3] = [0xb5, 0xba, 0xc1];
+const ACCENT: [u8; 3] = [0x58, 0x65, 0xf2];
+
+/// Skills as the in-game skills tab lays them out, read row by row. Overall takes the
+/// place of the total level in the last cell
+const SKILLS_TAB: [Skill; 24] = [
+    Skill::Attack,
+    Skill::Hitpoints,
+    Skill::Mining,
+    Skill::Strength,
+    Skill::Agility,
+    Skill::Smithing,
+    Skill::Defence,
+    Skill::Herblore,
+    Skill::Fishing,
+    Skill::Ranged,
+    Skill::Thieving,
+    Skill::Cooking,
+    Skill::Prayer,
+    Skill::Crafting,
+    Skill::Firemaking,
+    Skill::Magic,
+    Skill::Fletching,
+    Skill::Woodcutting,
+    Skill::Runecrafting,
+    Skill::Slayer,
+    Skill::Farming,
+    Skill::Construction,
+    Skill::Hunter,
+    Skill::Overall,
+];
+
+/// Renders a player's stat card as a PNG: their levels laid out like the skills tab, their
+/// most killed bosses and their EHP and EHB. Sized for a Discord embed
+pub fn stat_card(display_name: &str, data: &SnapShotData) -> Result<Vec<u8>, anyhow::Error> {
+    let bosses = top_bosses(data);
+    let skill_rows = SKILLS_TAB.len().div_ceil(SKILL_COLUMNS);
+    let skills_top = HEADER_HEIGHT + PADDING;
+    let bosses_top = skills_top + SKILL_CELL_HEIGHT * skill_rows as f32 + PADDING;
+    let boss_rows = bosses.len().max(1);
+    let height = bosses_top + SECTION_TITLE_HEIGHT + BOSS_ROW_HEIGHT * boss_rows as f32 + PADDING;
+    let mut canvas = Canvas::new(WIDTH, height.ceil() as u32)?;
+
+    canvas.rect(0.0, 0.0, WIDTH as f32, HEADER_HEIGHT, ACCENT);
+    canvas.text(Weight::Bold, 26.0, PADDING, 42.0, TEXT, display_name);
+    let computed = format!(
+        "EHP {:.1}   EHB {:.1}",
+        data.computed.ehp.value, data.computed.ehb.value
+    );
+    let computed_width = canvas.measure(Weight::Regular, 16.0, &computed);
+    canvas.text(
+        Weight::Regular,
+        16.0,
+        WIDTH as f32 - PADDING - computed_width,
+        39.0,
+        TEXT,
+        &computed,
+    );
+
+    let cell_width = (WIDTH as f32 - PADDING * 2.0) / SKILL_COLUMNS as f32;
+    for (index, skill) in SKILLS_TAB.iter().enumerate() {
+        let x = PADDING + cell_width * (index % SKILL_COLUMNS) as f32;
+        let y = skills_top + SKILL_CELL_HEIGHT * (index / SKILL_COLUMNS) as f32;
+        canvas.rect(
+            x + 2.0,
+            y + 2.0,
+            cell_width - 4.0,
+            SKILL_CELL_HEIGHT - 4.0,
+            PANEL,
+        );
+        let level = data
+            .skills
+            .get(skill)
+            .map(|skill| skill.level.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let level_width = canvas.measure(Weight::Bold, 16.0, &level);
+        canvas.text(
+            Weight::Regular,
+            13.0,
+            x + 12.0,
+            y + 22.0,
+            MUTED,
+            skill.as_ref(),
+        );
+        canvas.text(
+            Weight::Bold,
+            16.0,
+            x + cell_width - 12.0 - level_width,
+            y + 23.0,
+            TEXT,
+            &level,
+        );
+    }
+
+    canvas.text(
+        Weight::Bold,
+        16.0,
+        PADDING,
+        bosses_top + 20.0,
+        TEXT,
+        "Top bosses",
+    );
+    let rows_top = bosses_top + SECTION_TITLE_HEIGHT;
+    if bosses.is_empty() {
+        canvas.text(
+            Weight::Regular,
+            14.0,
+            PADDING,
+            rows_top + 20.0,
+            MUTED,
+            "No boss kills yet",
+        );
+    }
+    let most_kills = bosses.first().map_or(1, |boss| boss.kills.max(1));
+    for (index, boss) in bosses.iter().enumerate() {
+        let y = rows_top + BOSS_ROW_HEIGHT * index as f32;
+        bar_row(
+            &mut canvas,
+            y,
+            BOSS_ROW_HEIGHT,
+            &boss.metric.to_string().to_case(Case::Title),
+            boss.kills as f64 / most_kills as f64,
+            &with_separators(boss.kills),
+        );
+    }
+    canvas.png()
+}
+
+/// Renders gains as a leaderboard PNG, highest first. Each entry is a player's name and what
+/// they gained, such as `gain.data.skills.overall.experience.gained`
+pub fn gains_leaderboard<N: Display>(
+    title: &str,
+    gains: &[(N, f64)],
+) -> Result<Vec<u8>, anyhow::Error> {
+    let entries = sorted_gains(gains);
+
+    let rows = entries.len().max(1);
+    let height = HEADER_HEIGHT + PADDING * 2.0 + LEADERBOARD_ROW_HEIGHT * rows as f32;
+    let mut canvas = Canvas::new(WIDTH, height.ceil() as u32)?;
+    canvas.rect(0.0, 0.0, WIDTH as f32, HEADER_HEIGHT, ACCENT);
+    canvas.text(Weight::Bold, 24.0, PADDING, 41.0, TEXT, title);
+
+    let rows_top = HEADER_HEIGHT + PADDING;
+    if entries.is_empty() {
+        canvas.text(
+            Weight::Regular,
+            14.0,
+            PADDING,
+            rows_top + 22.0,
+            MUTED,
+            "No gains yet",
+        );
+    }
+    let most = entries.first().map_or(0.0, |(_, gained)| *gained);
+    let most = if most > 0.0 { most } else { 1.0 };
+    for (index, (name, gained)) in entries.iter().enumerate() {
+        let y = rows_top + LEADERBOARD_ROW_HEIGHT * index as f32;
+        bar_row(
+            &mut canvas,
+            y,
+            LEADERBOARD_ROW_HEIGHT,
+            &format!("{}. {}", index + 1, name),
+            gained.max(0.0) / most,
+            &format_number(*gained),
+        );
+    }
+    canvas.png()
+}
+
+/// The bosses with the most kills, most first. Unranked bosses are left out
+fn top_bosses(data: &SnapShotData) -> Vec<&BossMetric> {
+    let mut bosses: Vec<&BossMetric> = data.bosses.iter().filter(|boss| boss.kills > 0).collect();
+    bosses.sort_by_key(|boss| std::cmp::Reverse(boss.kills));
+    bosses.truncate(TOP_BOSSES);
+    bosses
+}
+
+/// A label, a bar filled to `fill` of the space left and the value after it
+fn bar_row(canvas: &mut Canvas, y: f32, row_height: f32, label: &str, fill: f64, value: &str) {
+    let bar_left = PADDING + NAME_COLUMN;
+    let bar_space = WIDTH as f32 - PADDING - VALUE_COLUMN - bar_left;
+    let bar_height = row_height - 12.0;
+    let label = canvas.fit(Weight::Regular, 14.0, label, NAME_COLUMN - 10.0);
+    canvas.text(
+        Weight::Regular,
+        14.0,
+        PADDING,
+        y + row_height / 2.0 + 5.0,
+        TEXT,
+        &label,
+    );
+    canvas.rect(bar_left, y + 6.0, bar_space, bar_height, PANEL);
+    canvas.rect(
+        bar_left,
+        y + 6.0,
+        bar_space * fill.clamp(0.0, 1.0) as f32,
+        bar_height,
+        ACCENT,
+    );
+    canvas.text(
+        Weight::Bold,
+        14.0,
+        bar_left + bar_space + 10.0,
+        y + row_height / 2.0 + 5.0,
+        TEXT,
+        value,
+    );
+}
+
+fn with_separators(value: i64) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let mut out = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    if value < 0 {
+        out.insert(0, '-');
+    }
+    out
+}
+
+#[derive(Clone, Copy)]
+enum Weight {
+    Regular,
+    Bold,
+}
+
+/// A pixmap with the bundled fonts, so rendering needs nothing from the system
+struct Canvas {
+    pixmap: Pixmap,
+    regular: FontRef<'static>,
+    bold: FontRef<'static>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Result<Self, anyhow::Error> {
+        let mut pixmap = Pixmap::new(width, height)
+            .ok_or_else(|| anyhow::anyhow!("Can not render a {}x{} image", width, height))?;
+        pixmap.fill(colour(BACKGROUND));
+        Ok(Self {
+            pixmap,
+            regular: FontRef::try_from_slice(REGULAR_FONT)?,
+            bold: FontRef::try_from_slice(BOLD_FONT)?,
+        })
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, rgb: [u8; 3]) {
+        let Some(rect) = Rect::from_xywh(x, y, width, height) else {
+            return;
+        };
+        let mut paint = Paint::default();
+        paint.set_color(colour(rgb));
+        self.pixmap
+            .fill_rect(rect, &paint, Transform::identity(), None);
+    }
+
+    fn font(&self, weight: Weight) -> FontRef<'static> {
+        match weight {
+            Weight::Regular => self.regular.clone(),
+            Weight::Bold => self.bold.clone(),
+        }
+    }
+
+    fn measure(&self, weight: Weight, size: f32, text: &str) -> f32 {
+        let font = self.font(weight);
+        let font = font.as_scaled(PxScale::from(size));
+        let mut width = 0.0;
+        let mut previous = None;
+        for c in text.chars() {
+            let glyph = font.glyph_id(c);
+            if let Some(previous) = previous {
+                width += font.kern(previous, glyph);
+            }
+            width += font.h_advance(glyph);
+            previous = Some(glyph);
+        }
+        width
+    }
+
+    /// Cuts the text short with an ellipsis when it is wider than `width`
+    fn fit(&self, weight: Weight, size: f32, text: &str, width: f32) -> String {
+        if self.measure(weight, size, text) <= width {
+            return text.to_string();
+        }
+        let mut chars: Vec<char> = text.chars().collect();
+        while !chars.is_empty() {
+            chars.pop();
+            let cut = format!("{}…", chars.iter().collect::<String>().trim_end());
+            if self.measure(weight, size, &cut) <= width {
+                return cut;
+            }
+        }
+        String::new()
+    }
+
+    /// Draws text with its baseline at `y`
+    fn text(&mut self, weight: Weight, size: f32, x: f32, y: f32, rgb: [u8; 3], text: &str) {
+        let font = self.font(weight);
+        let scaled = font.as_scaled(PxScale::from(size));
+        let mut caret = x;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = scaled.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += scaled.kern(previous, id);
+            }
+            let glyph = id.with_scale_and_position(size, ab_glyph::point(caret, y));
+            caret += scaled.h_advance(id);
+            previous = Some(id);
+            let Some(outlined) = font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                self.blend(
+                    bounds.min.x as i32 + gx as i32,
+                    bounds.min.y as i32 + gy as i32,
+                    rgb,
+                    coverage,
+                );
+            });
+        }
+    }
+
+    /// Mixes a colour into a pixel. Every pixel is opaque, as the background is filled first
+    fn blend(&mut self, x: i32, y: i32, rgb: [u8; 3], coverage: f32) {
+        if x < 0 || y < 0 || x >= self.pixmap.width() as i32 || y >= self.pixmap.height() as i32 {
+            return;
+        }
+        let width = self.pixmap.width() as usize;
+        let data = self.pixmap.data_mut();
+        let offset = (y as usize * width + x as usize) * 4;
+        let coverage = coverage.clamp(0.0, 1.0);
+        for channel in 0..3 {
+            let under = data[offset + channel] as f32;
+            data[offset + channel] =
+                (under + (rgb[channel] as f32 - under) * coverage).round() as u8;
+        }
+    }
+
+    fn png(&self) -> Result<Vec<u8>, anyhow::Error> {
+        Ok(self.pixmap.encode_png()?)
+    }
+}
+
+fn colour(rgb: [u8; 3]) -> Color {
+    Color::from_rgba8(rgb[0], rgb[1], rgb[2], 255)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::fixture;
+    use crate::image::{
+        gains_leaderboard, stat_card, with_separators, ACCENT, BACKGROUND, HEADER_HEIGHT,
+        LEADERBOARD_ROW_HEIGHT, NAME_COLUMN, PADDING, PANEL, SKILL_CELL_HEIGHT, VALUE_COLUMN,
+        WIDTH,
+    };
+    use crate::models::player::PlayerDetails;
+    use std::path::Path;
+    use tiny_skia::Pixmap;
+
+    /// Compares with the image under tests/snapshots/image, allowing small differences in
+    /// antialiasing. Run with `UPDATE_SNAPSHOTS=1` to write them after an intended change
+    fn assert_snapshot(name: &str, png: &[u8]) {
+        let path = Path::new("./tests/snapshots/image").join(format!("{}.png", name));
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, png).unwrap();
+        }
+        let expected = Pixmap::load_png(&path)
+            .unwrap_or_else(|_| panic!("missing {:?}, run with UPDATE_SNAPSHOTS=1", path));
+        let actual = Pixmap::decode_png(png).unwrap();
+        assert_eq!(
+            (actual.width(), actual.height()),
+            (expected.width(), expected.height())
+        );
+        let differing = actual
+            .pixels()
+            .iter()
+            .zip(expected.pixels())
+            .filter(|(a, b)| {
+                a.red().abs_diff(b.red()) > 16
+                    || a.green().abs_diff(b.green()) > 16
+                    || a.blue().abs_diff(b.blue()) > 16
+            })
+            .count();
+        assert!(
+            differing * 200 < actual.pixels().len(),
+            "{} has {} pixels that differ from its snapshot",
+            name,
+            differing
+        );
+    }
+
+    fn rgb(pixmap: &Pixmap, x: f32, y: f32) -> [u8; 3] {
+        let pixel = pixmap.pixel(x as u32, y as u32).unwrap();
+        [pixel.red(), pixel.green(), pixel.blue()]
+    }
+
+    fn zezima() -> PlayerDetails {
+        fixture("player/player_details.json")
+    }
+
+    #[test]
+    fn stat_card_test() {
+        let player = zezima();
+        let snapshot = player.latest_snapshot.unwrap();
+        let png = stat_card(&player.display_name, &snapshot.data).unwrap();
+        let card = Pixmap::decode_png(&png).unwrap();
+
+        assert_eq!(card.width(), WIDTH);
+        assert_eq!(rgb(&card, 2.0, 2.0), ACCENT);
+        assert_eq!(rgb(&card, 2.0, HEADER_HEIGHT + 2.0), BACKGROUND);
+        // The first skill cell's panel, left of its name
+        let skills_top = HEADER_HEIGHT + PADDING;
+        assert_eq!(rgb(&card, PADDING + 5.0, skills_top + 5.0), PANEL);
+
+        // Kraken has the most kills, so its bar is full, and the next boss's is not
+        let bosses_top = skills_top + SKILL_CELL_HEIGHT * 8.0 + PADDING + 32.0;
+        let bar_end = WIDTH as f32 - PADDING - VALUE_COLUMN - 2.0;
+        assert_eq!(rgb(&card, bar_end, bosses_top + 15.0), ACCENT);
+        assert_eq!(rgb(&card, bar_end, bosses_top + 45.0), PANEL);
+        assert_eq!(card.height(), (bosses_top + 30.0 * 5.0 + PADDING) as u32);
+        assert_snapshot("stat_card", &png);
+    }
+
+    #[test]
+    fn gains_leaderboard_test() {
+        let png = gains_leaderboard(
+            "Overall gains this week",
+            &[
+                ("Zezima", 842_000.0),
+                ("Lynx Titan", 2_105_000.0),
+                ("B0aty", 0.0),
+            ],
+        )
+        .unwrap();
+        let leaderboard = Pixmap::decode_png(&png).unwrap();
+
+        let rows_top = HEADER_HEIGHT + PADDING;
+        let bar_left = PADDING + NAME_COLUMN;
+        let bar_space = WIDTH as f32 - PADDING - VALUE_COLUMN - bar_left;
+        let row = |index: f32| rows_top + LEADERBOARD_ROW_HEIGHT * index + 18.0;
+        assert_eq!(leaderboard.height(), (row(3.0) - 18.0 + PADDING) as u32);
+        // Lynx Titan leads with a full bar, Zezima's is 40% of it and B0aty has none
+        assert_eq!(
+            rgb(&leaderboard, bar_left + bar_space - 2.0, row(0.0)),
+            ACCENT
+        );
+        assert_eq!(
+            rgb(&leaderboard, bar_left + bar_space * 0.38, row(1.0)),
+            ACCENT
+        );
+        assert_eq!(
+            rgb(&leaderboard, bar_left + bar_space * 0.42, row(1.0)),
+            PANEL
+        );
+        assert_eq!(rgb(&leaderboard, bar_left + 1.0, row(2.0)), PANEL);
+        assert_snapshot("gains_leaderboard", &png);
+
+        let empty = gains_leaderboard("Nothing yet", &[] as &[(&str, f64)]).unwrap();
+        assert_snapshot("gains_leaderboard_empty", &empty);
+        assert_eq!(with_separators(1234567), "1,234,567");
+    }
+}
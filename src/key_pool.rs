@@ -0,0 +1,355 @@
+use crate::transport::{Request, ReqwestTransport, Response, Transport, API_KEY_HEADER};
+use reqwest::StatusCode;
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How long a rate limited key is rested when the api does not say
+pub const DEFAULT_KEY_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// How an [ApiKeyPool] picks the key for each request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeySelection {
+    /// Each key in turn
+    #[default]
+    RoundRobin,
+    /// The key the api last reported the most remaining requests for.
+    /// Keys that have not been used yet are picked first
+    MostRemaining,
+}
+
+/// Requests sent with one key of an [ApiKeyPool]
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyUsage {
+    /// The last four characters of the key, enough to tell keys apart without leaking them
+    pub key_hint: String,
+    pub requests: u64,
+    /// How many times the api answered 429 Too Many Requests
+    pub rate_limited: u64,
+    /// The remaining requests the api last reported for the key
+    pub remaining: Option<u64>,
+    /// Set while the key is resting after a 429
+    pub cooling_down_for: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct KeyState {
+    key: String,
+    requests: u64,
+    rate_limited: u64,
+    remaining: Option<u64>,
+    cooling_down_until: Option<Instant>,
+}
+
+impl KeyState {
+    fn is_cooling_down(&self, now: Instant) -> bool {
+        self.cooling_down_until.is_some_and(|until| until > now)
+    }
+}
+
+/// Several api keys shared by one client. Rate limited keys are rested and the next
+/// request goes out with another key
+#[derive(Debug)]
+pub struct ApiKeyPool {
+    keys: Mutex<Vec<KeyState>>,
+    selection: KeySelection,
+    cooldown: Duration,
+    next: AtomicUsize,
+}
+
+impl ApiKeyPool {
+    pub fn new(keys: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            keys: Mutex::new(
+                keys.into_iter()
+                    .map(|key| KeyState {
+                        key: key.into(),
+                        requests: 0,
+                        rate_limited: 0,
+                        remaining: None,
+                        cooling_down_until: None,
+                    })
+                    .collect(),
+            ),
+            selection: KeySelection::default(),
+            cooldown: DEFAULT_KEY_COOLDOWN,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn selection(mut self, selection: KeySelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// How long a key rests after a 429 that has no `Retry-After` header
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Usage of each key, in the order they were given
+    pub fn usage(&self) -> Vec<KeyUsage> {
+        let now = Instant::now();
+        self.keys
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|state| KeyUsage {
+                key_hint: key_hint(&state.key),
+                requests: state.requests,
+                rate_limited: state.rate_limited,
+                remaining: state.remaining,
+                cooling_down_for: state
+                    .cooling_down_until
+                    .filter(|until| *until > now)
+                    .map(|until| until - now),
+            })
+            .collect()
+    }
+
+    /// Picks a key that is not cooling down and counts the request against it.
+    /// When every key is cooling down, the one that recovers first is used
+    fn acquire(&self, exclude: &[usize]) -> Option<(usize, String)> {
+        let now = Instant::now();
+        let mut keys = self.keys.lock().unwrap();
+        let candidates: Vec<usize> = (0..keys.len())
+            .filter(|index| !exclude.contains(index))
+            .collect();
+        let available: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|index| !keys[*index].is_cooling_down(now))
+            .collect();
+        let index = if available.is_empty() {
+            candidates
+                .into_iter()
+                .min_by_key(|index| keys[*index].cooling_down_until)?
+        } else {
+            match self.selection {
+                KeySelection::RoundRobin => {
+                    let turn = self.next.fetch_add(1, Ordering::Relaxed);
+                    available[turn % available.len()]
+                }
+                KeySelection::MostRemaining => available
+                    .into_iter()
+                    .min_by_key(|index| {
+                        let state = &keys[*index];
+                        (Reverse(state.remaining.unwrap_or(u64::MAX)), state.requests)
+                    })
+                    .unwrap(),
+            }
+        };
+        keys[index].requests += 1;
+        Some((index, keys[index].key.clone()))
+    }
+
+    /// Whether a key that has not been tried yet is ready, so a rate limited request is worth retrying
+    fn has_fresh_key(&self, tried: &[usize]) -> bool {
+        let now = Instant::now();
+        self.keys
+            .lock()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .any(|(index, state)| !tried.contains(&index) && !state.is_cooling_down(now))
+    }
+
+    fn record(&self, index: usize, response: &Response) {
+        let mut keys = self.keys.lock().unwrap();
+        let state = &mut keys[index];
+        if let Some(remaining) = header_number(response, "ratelimit-remaining")
+            .or_else(|| header_number(response, "x-ratelimit-remaining"))
+        {
+            state.remaining = Some(remaining);
+        }
+        if response.status == StatusCode::TOO_MANY_REQUESTS {
+            let cooldown = header_number(response, "retry-after")
+                .map(Duration::from_secs)
+                .unwrap_or(self.cooldown);
+            state.rate_limited += 1;
+            state.remaining = Some(0);
+            state.cooling_down_until = Some(Instant::now() + cooldown);
+        }
+    }
+}
+
+fn header_number(response: &Response, name: &str) -> Option<u64> {
+    response.header(name)?.trim().parse().ok()
+}
+
+fn key_hint(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    let hint: String = chars[chars.len().saturating_sub(4)..].iter().collect();
+    format!("…{}", hint)
+}
+
+/// Sends each request with a key from an [ApiKeyPool]. A request that is rate limited is
+/// sent again with each other key before the 429 is returned
+pub struct KeyPoolTransport<T: Transport = ReqwestTransport> {
+    inner: T,
+    pool: Arc<ApiKeyPool>,
+}
+
+impl<T: Transport> KeyPoolTransport<T> {
+    pub fn new(inner: T, pool: Arc<ApiKeyPool>) -> Self {
+        Self { inner, pool }
+    }
+}
+
+impl<T: Transport> Transport for KeyPoolTransport<T> {
+    async fn send(&self, request: Request) -> Result<Response, anyhow::Error> {
+        let mut tried = vec![];
+        loop {
+            let Some((index, key)) = self.pool.acquire(&tried) else {
+                // An empty pool sends the request as it is
+                return self.inner.send(request).await;
+            };
+            let mut keyed = request.clone();
+            keyed
+                .headers
+                .retain(|(name, _)| !name.eq_ignore_ascii_case(API_KEY_HEADER));
+            keyed.headers.push((API_KEY_HEADER.to_string(), key));
+
+            let response = self.inner.send(keyed).await?;
+            self.pool.record(index, &response);
+            tried.push(index);
+            if response.status != StatusCode::TOO_MANY_REQUESTS || !self.pool.has_fresh_key(&tried)
+            {
+                return Ok(response);
+            }
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("retries", tried.len() as u32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::key_pool::{ApiKeyPool, KeyPoolTransport, KeySelection};
+    use crate::transport::{Request, Response, Transport};
+    use crate::WomClient;
+    use reqwest::StatusCode;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Answers with the remaining quota for each key, and 429 for keys listed as exhausted
+    fn fake_api(
+        exhausted: &'static [&'static str],
+        seen: Arc<Mutex<Vec<String>>>,
+    ) -> impl Fn(Request) -> std::future::Ready<Result<Response, anyhow::Error>> + Send + Sync {
+        move |request: Request| {
+            let key = request
+                .headers
+                .iter()
+                .find(|(name, _)| name == "x-api-key")
+                .map(|(_, value)| value.clone())
+                .unwrap();
+            seen.lock().unwrap().push(key.clone());
+            let response = if exhausted.contains(&key.as_str()) {
+                Response {
+                    status: StatusCode::TOO_MANY_REQUESTS,
+                    headers: vec![("Retry-After".to_string(), "30".to_string())],
+                    body: vec![],
+                }
+            } else {
+                let remaining = match key.as_str() {
+                    "key-aaaa" => "10",
+                    _ => "90",
+                };
+                Response {
+                    status: StatusCode::OK,
+                    headers: vec![("RateLimit-Remaining".to_string(), remaining.to_string())],
+                    body: std::fs::read("./tests/mocks/player/player_details.json").unwrap(),
+                }
+            };
+            std::future::ready(Ok(response))
+        }
+    }
+
+    #[tokio::test]
+    async fn round_robin_test() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let pool = Arc::new(ApiKeyPool::new(["key-aaaa", "key-bbbb", "key-cccc"]));
+        let transport = KeyPoolTransport::new(fake_api(&[], seen.clone()), pool.clone());
+
+        for _ in 0..6 {
+            let response = transport.send(Request::get("http://fake")).await.unwrap();
+            assert_eq!(response.status, StatusCode::OK);
+        }
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            ["key-aaaa", "key-bbbb", "key-cccc", "key-aaaa", "key-bbbb", "key-cccc"]
+        );
+        let usage = pool.usage();
+        assert!(usage.iter().all(|key| key.requests == 2));
+        assert_eq!(usage[0].key_hint, "…aaaa");
+        assert_eq!(usage[0].remaining, Some(10));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limited_key_cools_down_test() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let pool = Arc::new(ApiKeyPool::new(["key-aaaa", "key-bbbb"]));
+        let wom_client = WomClient::new_with_transport(
+            KeyPoolTransport::new(fake_api(&["key-aaaa"], seen.clone()), pool.clone()),
+            "http://fake".to_string(),
+        );
+
+        for _ in 0..3 {
+            let result = wom_client
+                .player_client
+                .get_details("zezima".to_string())
+                .await;
+            assert!(result.is_ok());
+        }
+        // The rate limited key is retried with the other one, then left alone
+        assert_eq!(
+            *seen.lock().unwrap(),
+            ["key-aaaa", "key-bbbb", "key-bbbb", "key-bbbb"]
+        );
+        let usage = pool.usage();
+        assert_eq!(usage[0].rate_limited, 1);
+        assert_eq!(usage[0].remaining, Some(0));
+        assert_eq!(usage[0].cooling_down_for, Some(Duration::from_secs(30)));
+        assert_eq!(usage[1].requests, 3);
+
+        tokio::time::advance(Duration::from_secs(31)).await;
+        assert_eq!(pool.usage()[0].cooling_down_for, None);
+    }
+
+    #[tokio::test]
+    async fn every_key_rate_limited_test() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let pool = Arc::new(ApiKeyPool::new(["key-aaaa", "key-bbbb"]));
+        let transport =
+            KeyPoolTransport::new(fake_api(&["key-aaaa", "key-bbbb"], seen.clone()), pool);
+
+        let response = transport.send(Request::get("http://fake")).await.unwrap();
+
+        assert_eq!(response.status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn most_remaining_test() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let pool = Arc::new(
+            ApiKeyPool::new(["key-aaaa", "key-bbbb"]).selection(KeySelection::MostRemaining),
+        );
+        let transport = KeyPoolTransport::new(fake_api(&[], seen.clone()), pool);
+
+        for _ in 0..4 {
+            transport.send(Request::get("http://fake")).await.unwrap();
+        }
+
+        // Both are tried once, then the key with more quota left is preferred
+        assert_eq!(
+            *seen.lock().unwrap(),
+            ["key-aaaa", "key-bbbb", "key-bbbb", "key-bbbb"]
+        );
+    }
+}
@@ -1,13 +1,84 @@
+use crate::clients::competition_client::CompetitionClient;
 use crate::clients::group_client::GroupClient;
+use crate::clients::leaderboard_client::LeaderboardClient;
 use crate::clients::player_client::PlayerClient;
+use crate::drift::{DriftReporter, ModelDrift};
+use crate::key_pool::{ApiKeyPool, KeyPoolTransport};
+use crate::rate_limit::RateLimiter;
+use crate::transport::{
+    Cassette, RecordingTransport, ReplayTransport, ReqwestTransport, Transport,
+};
 use reqwest::header::{HeaderMap, HeaderValue};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Achievement definitions and progress worked out from snapshots
+pub mod achievements;
+
+/// A compact binary format for archiving snapshots
+pub mod archive;
+
+/// Updating and fetching many players at once under the api's rate limit and cooldowns
+pub mod bulk;
+
+/// SVG charts of timelines, gains and player comparisons
+#[cfg(feature = "charts")]
+pub mod charts;
 
 /// Individual clients for each endpoint
 pub mod clients;
 
+/// Arrow record batches and Parquet files of snapshots, gains, records and name changes
+#[cfg(feature = "parquet")]
+pub mod columnar;
+
+/// Strict mode, reporting responses that no longer match the models
+pub mod drift;
+
+/// Shares in-flight GET requests between concurrent callers
+mod coalesce;
+
+/// Mocks and a fake api shared by the unit tests
+#[cfg(test)]
+mod fixtures;
+
+/// PNG stat cards and leaderboards, for Discord embeds
+#[cfg(feature = "image")]
+pub mod image;
+
+/// Following name changes and archives to who a username belongs to now
+pub mod identity;
+
+/// Spreading requests across several api keys
+pub mod key_pool;
+
 /// Responses for each endpoint
 pub mod models;
 
+/// Typed, validated filters shared by the clients
+pub mod query;
+
+/// Keeps requests under the api's rate limit
+mod rate_limit;
+
+/// Recurring competitions, such as Skill of the Week, created on a rotation
+#[cfg(feature = "scheduler")]
+pub mod schedule;
+
+/// Competition standings computed locally from snapshots
+pub mod standings;
+
+/// Keeps a history of players, snapshots, memberships, name changes and records in SQLite
+#[cfg(feature = "sqlite")]
+pub mod store;
+
+/// How requests reach the api, including recording and replaying cassettes
+pub mod transport;
+
+/// A local mock Wise Old Man server for testing code built on the crate
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
 const BASE_URL: &str = "https://api.wiseoldman.net/v2";
 
 static APP_USER_AGENT: &str = concat!(
@@ -22,6 +93,10 @@ static APP_USER_AGENT: &str = concat!(
 pub(crate) enum ApiEndpoint {
     Player,
     Group,
+    Competition,
+    Efficiency,
+    Record,
+    Delta,
 }
 
 impl ApiEndpoint {
@@ -29,17 +104,24 @@ impl ApiEndpoint {
         match self {
             ApiEndpoint::Player => "/players",
             ApiEndpoint::Group => "/groups",
+            ApiEndpoint::Competition => "/competitions",
+            ApiEndpoint::Efficiency => "/efficiency",
+            ApiEndpoint::Record => "/records",
+            ApiEndpoint::Delta => "/deltas",
         }
     }
 }
 
-/// Wise Old Man Client
-pub struct WomClient {
-    pub player_client: PlayerClient,
-    pub group_client: GroupClient,
+/// Wise Old Man Client, generic over the [Transport] used to reach the api
+pub struct WomClient<T: Transport = ReqwestTransport> {
+    pub player_client: PlayerClient<T>,
+    pub group_client: GroupClient<T>,
+    pub competition_client: CompetitionClient<T>,
+    pub leaderboard_client: LeaderboardClient<T>,
 }
 
 /// Used for endpoints that take pagination
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Pagination {
     pub limit: Option<i32>,
     pub offset: Option<i32>,
@@ -58,13 +140,50 @@ impl Pagination {
     }
 }
 
+impl<T: Transport> WomClient<T> {
+    ///Creates a new `WOMClient` that sends its requests through `transport`.
+    /// Used to add middleware, swap the HTTP stack or fake the api in tests
+    pub fn new_with_transport(transport: T, base_url: String) -> Self {
+        let transport = Arc::new(transport);
+        Self {
+            player_client: PlayerClient::new(transport.clone(), &base_url),
+            group_client: GroupClient::new(transport.clone(), &base_url),
+            competition_client: CompetitionClient::new(transport.clone(), &base_url),
+            leaderboard_client: LeaderboardClient::new(transport, &base_url),
+        }
+    }
+
+    /// Turns on strict mode. `on_drift` is called whenever a response has fields the models
+    /// do not know about, or leaves out optional ones, and the call still succeeds.
+    /// Pass [ModelDrift::warn] to log them as warnings
+    pub fn strict(mut self, on_drift: impl Fn(&ModelDrift) + Send + Sync + 'static) -> Self {
+        let on_drift: DriftReporter = Arc::new(on_drift);
+        self.player_client.on_drift = Some(on_drift.clone());
+        self.group_client.on_drift = Some(on_drift.clone());
+        self.competition_client.on_drift = Some(on_drift.clone());
+        self.leaderboard_client.on_drift = Some(on_drift);
+        self
+    }
+
+    /// Keeps every request from the clients under `requests_per_minute`, waiting for a slot
+    /// before each one is sent. Use 100 with an api key and 20 without
+    pub fn rate_limited(mut self, requests_per_minute: u32) -> Self {
+        let limiter = Arc::new(RateLimiter::new(requests_per_minute));
+        self.player_client.rate_limiter = Some(limiter.clone());
+        self.group_client.rate_limiter = Some(limiter.clone());
+        self.competition_client.rate_limiter = Some(limiter.clone());
+        self.leaderboard_client.rate_limiter = Some(limiter);
+        self
+    }
+}
+
 impl WomClient {
     fn new_reqwest_client(api_key: Option<String>) -> reqwest::Client {
         let client = reqwest::Client::builder().user_agent(APP_USER_AGENT);
         match api_key {
             Some(key) => {
                 let mut headers = HeaderMap::new();
-                headers.insert("x-api-key", HeaderValue::from_str(&*key).unwrap());
+                headers.insert("x-api-key", HeaderValue::from_str(&key).unwrap());
                 client.default_headers(headers)
             }
             None => client,
@@ -73,92 +192,261 @@ impl WomClient {
         .unwrap()
     }
 
-    fn new_wom_client(client: reqwest::Client, base_url: String) -> Self {
-        let _ = env_logger::try_init();
-
-        let sub_client_base_url = base_url.clone();
-        Self {
-            player_client: PlayerClient::new(client.clone(), &*sub_client_base_url),
-            group_client: GroupClient::new(client, &*sub_client_base_url),
-        }
-    }
-
     ///Creates a new `WOMClient`
     pub fn new() -> Self {
         let client = WomClient::new_reqwest_client(None);
-        Self::new_wom_client(client, BASE_URL.to_string())
+        Self::new_with_transport(ReqwestTransport::new(client), BASE_URL.to_string())
     }
 
     ///Creates a new `WOMClient` with an API key
     pub fn new_with_key(api_key: String) -> Self {
         let client = WomClient::new_reqwest_client(Some(api_key));
-        Self::new_wom_client(client, BASE_URL.to_string())
+        Self::new_with_transport(ReqwestTransport::new(client), BASE_URL.to_string())
     }
 
     ///Creates a new `WOMClient` with a custom base URL, and optionally an API key.
     /// Used for testing and self hosted/League WOM instances
     pub fn new_with_base_url(base_url: String, api_key: Option<String>) -> Self {
         let client = WomClient::new_reqwest_client(api_key);
-        Self::new_wom_client(client, base_url)
+        Self::new_with_transport(ReqwestTransport::new(client), base_url)
+    }
+}
+
+impl WomClient<KeyPoolTransport> {
+    ///Creates a new `WOMClient` that spreads its requests across a pool of API keys.
+    /// Keep a clone of the pool to read each key's usage
+    pub fn new_with_key_pool(pool: Arc<ApiKeyPool>) -> Self {
+        Self::new_with_base_url_and_key_pool(BASE_URL.to_string(), pool)
+    }
+
+    ///Creates a new `WOMClient` with a custom base URL that spreads its requests across a pool of API keys
+    pub fn new_with_base_url_and_key_pool(base_url: String, pool: Arc<ApiKeyPool>) -> Self {
+        let client = WomClient::new_reqwest_client(None);
+        Self::new_with_transport(
+            KeyPoolTransport::new(ReqwestTransport::new(client), pool),
+            base_url,
+        )
+    }
+}
+
+impl WomClient<RecordingTransport> {
+    ///Creates a new `WOMClient` that calls the api and records every request and response to a cassette file.
+    /// The API key is redacted from the cassette
+    pub fn new_recording(
+        base_url: String,
+        api_key: Option<String>,
+        cassette_path: impl AsRef<Path>,
+    ) -> Result<Self, anyhow::Error> {
+        let client = WomClient::new_reqwest_client(api_key);
+        let cassette = Cassette::record(cassette_path, &base_url)?;
+        Ok(Self::new_with_transport(
+            RecordingTransport::new(ReqwestTransport::new(client), Arc::new(cassette)),
+            base_url,
+        ))
+    }
+}
+
+impl WomClient<ReplayTransport> {
+    ///Creates a new `WOMClient` that answers every request from a recorded cassette file without calling the api.
+    /// Requests that were not recorded fail
+    pub fn new_replaying(cassette_path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let cassette = Cassette::load(cassette_path, BASE_URL)?;
+        Ok(Self::new_with_transport(
+            ReplayTransport::new(Arc::new(cassette)),
+            BASE_URL.to_string(),
+        ))
+    }
+}
+
+impl Default for WomClient {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 pub(crate) mod helpers {
-    use crate::models::error::ErrorResponse;
+    use crate::coalesce::Coalescer;
+    use crate::drift::{detect, DriftReporter};
+    use crate::models::error::{ApiError, ErrorResponse};
+    use crate::rate_limit::{acquire, RateLimiter};
+    use crate::transport::{Request, Response, Transport};
     use anyhow::anyhow;
     use log::debug;
-    use reqwest::{Error, Response, StatusCode};
+    use reqwest::{Method, StatusCode};
     use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use std::sync::Arc;
 
-    pub async fn handle_response<ResponseType: DeserializeOwned>(
-        response: Result<Response, Error>,
+    /// Reads a response into its model. In strict mode, when `on_drift` is set, differences
+    /// between the response and the model are reported without failing the call
+    pub async fn handle_response<ResponseType: DeserializeOwned + Serialize>(
+        response: Result<Response, anyhow::Error>,
+        endpoint: &'static str,
+        on_drift: Option<&DriftReporter>,
     ) -> Result<ResponseType, anyhow::Error> {
         match response {
-            Ok(result) => match result.status() {
-                StatusCode::OK => {
-                    let body = result.json::<ResponseType>().await;
+            Ok(result) => match result.status {
+                // Creating returns 201
+                status if status.is_success() => {
+                    let body = serde_json::from_slice::<ResponseType>(&result.body);
                     match body {
-                        Ok(body) => Ok(body),
+                        Ok(body) => {
+                            if let Some(on_drift) = on_drift {
+                                if let Some(drift) = detect(endpoint, &result.body, &body) {
+                                    on_drift(&drift);
+                                }
+                            }
+                            Ok(body)
+                        }
                         Err(err) => Err(anyhow!(err)),
                     }
                 }
                 StatusCode::NOT_FOUND => {
-                    let error_body = result.json::<ErrorResponse>().await;
-                    match error_body {
-                        Ok(body) => Err(anyhow!(body.message)),
-                        Err(err) => Err(anyhow!(err)),
-                    }
+                    let error_body = serde_json::from_slice::<ErrorResponse>(&result.body);
+                    api_error(result.status, error_body)
                 }
                 StatusCode::BAD_REQUEST => {
                     debug!("{:?}", result);
 
-                    let error_body = result.json::<ErrorResponse>().await;
-                    match error_body {
-                        Ok(body) => Err(anyhow!(body.message)),
-                        Err(err) => Err(anyhow!(err)),
-                    }
+                    let error_body = serde_json::from_slice::<ErrorResponse>(&result.body);
+                    api_error(result.status, error_body)
                 }
                 _ => {
                     debug!("{:?}", result);
-                    let error_body = result.json::<ErrorResponse>().await;
-                    match error_body {
-                        Ok(body) => Err(anyhow!(body.message)),
-                        Err(err) => Err(anyhow!(err)),
-                    }
+                    let error_body = serde_json::from_slice::<ErrorResponse>(&result.body);
+                    api_error(result.status, error_body)
                 }
             },
             Err(err) => Err(anyhow!(err)),
         }
     }
 
-    pub fn _handle_empty_response(response: Result<Response, Error>) -> Result<(), Error> {
+    /// Turns an error body into an [ApiError] so callers can tell what status the api returned
+    fn api_error<ResponseType>(
+        status: StatusCode,
+        error_body: Result<ErrorResponse, serde_json::Error>,
+    ) -> Result<ResponseType, anyhow::Error> {
+        let message = match error_body {
+            Ok(body) => body.message,
+            Err(_) => status.to_string(),
+        };
+        Err(anyhow!(ApiError {
+            status: status.as_u16(),
+            message,
+        }))
+    }
+
+    /// Sends a request once the rate limiter allows it.
+    /// Concurrent GETs for the same url share one call to the api
+    pub async fn send_limited<T: Transport>(
+        transport: &Arc<T>,
+        rate_limiter: &Option<Arc<RateLimiter>>,
+        in_flight: &Coalescer,
+        endpoint: &'static str,
+        request: Request,
+    ) -> Result<Response, anyhow::Error> {
+        if request.method != Method::GET {
+            acquire(rate_limiter.as_deref()).await;
+            return send(transport.as_ref(), endpoint, request).await;
+        }
+        let transport = transport.clone();
+        let rate_limiter = rate_limiter.clone();
+        let key = request.url.clone();
+        in_flight
+            .run(key, async move {
+                acquire(rate_limiter.as_deref()).await;
+                send(transport.as_ref(), endpoint, request).await
+            })
+            .await
+    }
+
+    /// Sends a request for an endpoint, inside a span when the `tracing` feature is enabled
+    #[cfg(not(feature = "tracing"))]
+    pub async fn send<T: Transport>(
+        transport: &T,
+        _endpoint: &'static str,
+        request: Request,
+    ) -> Result<Response, anyhow::Error> {
+        transport.send(request).await
+    }
+
+    /// Sends a request for an endpoint inside a `wom_request` span recording the endpoint,
+    /// method, status, latency, retries and response size. The api key is redacted
+    #[cfg(feature = "tracing")]
+    pub async fn send<T: Transport>(
+        transport: &T,
+        endpoint: &'static str,
+        request: Request,
+    ) -> Result<Response, anyhow::Error> {
+        use tracing::field::{display, Empty};
+        use tracing::Instrument;
+
+        let span = tracing::info_span!(
+            "wom_request",
+            endpoint,
+            method = %request.method,
+            url = %request.url,
+            headers = ?request.redacted_headers(),
+            status = Empty,
+            latency_ms = Empty,
+            retries = 0u32,
+            response_size = Empty,
+            error = Empty,
+        );
+        let started = std::time::Instant::now();
+        let result = transport.send(request).instrument(span.clone()).await;
+        span.record("latency_ms", started.elapsed().as_millis() as u64);
+        match &result {
+            Ok(response) => {
+                span.record("status", response.status.as_u16());
+                span.record("response_size", response.body.len());
+            }
+            Err(err) => {
+                span.record("error", display(err));
+            }
+        }
+        result
+    }
+
+    pub fn _handle_empty_response(
+        response: Result<Response, anyhow::Error>,
+    ) -> Result<(), anyhow::Error> {
         match response {
             Ok(_) => Ok(()),
             Err(err) => Err(err),
         }
     }
 
-    pub fn query_params_to_string(query_params: &Vec<(String, String)>) -> String {
+    /// Shortens large numbers the way the website does, such as 19.3M
+    #[cfg_attr(not(any(feature = "charts", feature = "image")), allow(dead_code))]
+    pub fn format_number(value: f64) -> String {
+        let size = value.abs();
+        let (scaled, suffix) = if size >= 1e9 {
+            (value / 1e9, "B")
+        } else if size >= 1e6 {
+            (value / 1e6, "M")
+        } else if size >= 1e3 {
+            (value / 1e3, "K")
+        } else {
+            (value, "")
+        };
+        let text = format!("{:.1}", scaled);
+        let text = text.strip_suffix(".0").unwrap_or(&text);
+        format!("{}{}", text, suffix)
+    }
+
+    /// Names and gains for a leaderboard, highest first and then by name
+    #[cfg_attr(not(any(feature = "charts", feature = "image")), allow(dead_code))]
+    pub fn sorted_gains<N: std::fmt::Display>(gains: &[(N, f64)]) -> Vec<(String, f64)> {
+        let mut entries: Vec<(String, f64)> = gains
+            .iter()
+            .map(|(name, gained)| (name.to_string(), *gained))
+            .collect();
+        entries.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        entries
+    }
+
+    pub fn query_params_to_string(query_params: &[(String, String)]) -> String {
         let mut query_string = String::new();
         for (index, (key, value)) in query_params.iter().enumerate() {
             if index == 0 {
@@ -172,4 +460,100 @@ pub(crate) mod helpers {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    #[tokio::test]
+    async fn strict_mode_reports_drift_test() {
+        use crate::transport::{Request, Response};
+        use crate::WomClient;
+        use std::sync::{Arc, Mutex};
+
+        let transport = |_: Request| async move {
+            let body = std::fs::read("./tests/mocks/player/player_details.json").unwrap();
+            let mut json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            json["clanRank"] = "owner".into();
+            Ok(Response {
+                status: reqwest::StatusCode::OK,
+                headers: vec![],
+                body: serde_json::to_vec(&json).unwrap(),
+            })
+        };
+        let drifts = Arc::new(Mutex::new(vec![]));
+        let reported = drifts.clone();
+        let wom_client = WomClient::new_with_transport(transport, "http://fake".to_string())
+            .strict(move |drift| reported.lock().unwrap().push(drift.clone()));
+
+        let details = wom_client
+            .player_client
+            .get_details("zezima".to_string())
+            .await;
+
+        assert!(details.is_ok());
+        let drifts = drifts.lock().unwrap();
+        assert_eq!(drifts.len(), 1);
+        assert_eq!(drifts[0].endpoint, "player.details");
+        assert_eq!(drifts[0].unknown_fields, vec!["clanRank"]);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn endpoint_span_test() {
+        use crate::helpers::send;
+        use crate::transport::{Request, Response};
+        use std::collections::HashMap;
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+        use tracing_subscriber::Layer;
+
+        type Fields = Arc<Mutex<HashMap<String, String>>>;
+
+        struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+        impl Visit for FieldVisitor<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0
+                    .insert(field.name().to_string(), format!("{:?}", value));
+            }
+        }
+
+        struct CaptureLayer(Fields);
+
+        impl<S: tracing::Subscriber> Layer<S> for CaptureLayer {
+            fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+                attrs.record(&mut FieldVisitor(&mut self.0.lock().unwrap()));
+            }
+
+            fn on_record(&self, _: &Id, values: &Record<'_>, _: Context<'_, S>) {
+                values.record(&mut FieldVisitor(&mut self.0.lock().unwrap()));
+            }
+        }
+
+        let fields = Fields::default();
+        let subscriber = tracing_subscriber::registry().with(CaptureLayer(fields.clone()));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let transport = |_: Request| async move {
+            Ok(Response {
+                status: reqwest::StatusCode::OK,
+                headers: vec![],
+                body: b"[]".to_vec(),
+            })
+        };
+        let mut request = Request::get("http://fake/players/zezima");
+        request
+            .headers
+            .push(("x-api-key".to_string(), "secret".to_string()));
+        send(&transport, "player.details", request).await.unwrap();
+
+        let fields = fields.lock().unwrap();
+        assert_eq!(fields["endpoint"], "\"player.details\"");
+        assert_eq!(fields["method"], "GET");
+        assert_eq!(fields["status"], "200");
+        assert_eq!(fields["retries"], "0");
+        assert_eq!(fields["response_size"], "2");
+        assert!(fields.contains_key("latency_ms"));
+        assert!(fields["headers"].contains("[REDACTED]"));
+        assert!(!fields["headers"].contains("secret"));
+    }
+}
@@ -1,8 +1,9 @@
 use crate::models::global_enums::Metric;
-use crate::models::global_types::{CompetitionId, GroupId, PlayerId};
+use crate::models::global_types::{CompetitionId, GroupId, PlayerId, Username};
 use crate::models::group::Group;
+use crate::models::player::Player;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
 /// [Competition Type](https://docs.wiseoldman.net/competitions-api/competition-type-definitions#enum-competition-type)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -62,7 +63,7 @@ pub struct CompetitionLevelsProgress {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Competition {
-    pub id: i64,
+    pub id: CompetitionId,
     pub title: String,
     pub metric: Metric,
     #[serde(rename = "type")]
@@ -114,3 +115,67 @@ pub struct PlayerCompetitionStanding {
     pub levels: Option<CompetitionLevelsProgress>,
     pub rank: i64,
 }
+
+/// [Competition With Participations](https://docs.wiseoldman.net/competitions-api/competition-type-definitions#object-competition-with-participations)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompetitionWithParticipations {
+    pub id: CompetitionId,
+    pub title: String,
+    pub metric: Metric,
+    #[serde(rename = "type")]
+    pub competition_type: CompetitionType,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub group_id: Option<GroupId>,
+    pub score: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub participant_count: i64,
+    pub group: Option<Group>,
+    pub participations: Vec<ParticipationWithPlayer>,
+}
+
+/// [Participation With Player](https://docs.wiseoldman.net/competitions-api/competition-type-definitions#object-participation-with-player)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipationWithPlayer {
+    pub player_id: PlayerId,
+    pub competition_id: CompetitionId,
+    pub team_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub player: Player,
+}
+
+/// Body of [Create Competition](https://docs.wiseoldman.net/competitions-api/competition-endpoints#create-competition).
+/// A group competition takes the group's verification code and its members as participants
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCompetition {
+    pub title: String,
+    #[serde(serialize_with = "metric_as_str")]
+    pub metric: Metric,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<GroupId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_verification_code: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub participants: Vec<Username>,
+}
+
+/// The api takes a metric as its bare name
+fn metric_as_str<S: Serializer>(metric: &Metric, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(metric)
+}
+
+/// [Create Competition](https://docs.wiseoldman.net/competitions-api/competition-endpoints#create-competition)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompetitionCreateResponse {
+    pub competition: CompetitionWithParticipations,
+    /// Only useful for competitions without a group, group competitions are managed with the group's code
+    pub verification_code: String,
+}
@@ -0,0 +1,390 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A country name or code that does not match any [Country]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseCountryError(pub String);
+
+impl fmt::Display for ParseCountryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown country: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseCountryError {}
+
+macro_rules! countries {
+    ($($variant:ident => $code:literal, $name:literal;)*) => {
+        /// [Country](https://docs.wiseoldman.net/players-api/player-type-definitions#enum-country)
+        /// Every ISO 3166-1 country, plus the parts of the United Kingdom Wise Old Man lists separately
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        pub enum Country {
+            $(
+                #[serde(rename = $code)]
+                $variant,
+            )*
+        }
+
+        impl Country {
+            /// Every country, in code order
+            pub const ALL: &'static [Country] = &[$(Country::$variant),*];
+
+            /// The code the api uses, e.g. `GB` or `GB-SCT`
+            pub fn code(&self) -> &'static str {
+                match self {
+                    $(Country::$variant => $code,)*
+                }
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Country::$variant => $name,)*
+                }
+            }
+        }
+    };
+}
+
+countries! {
+    AD => "AD", "Andorra";
+    AE => "AE", "United Arab Emirates";
+    AF => "AF", "Afghanistan";
+    AG => "AG", "Antigua and Barbuda";
+    AI => "AI", "Anguilla";
+    AL => "AL", "Albania";
+    AM => "AM", "Armenia";
+    AO => "AO", "Angola";
+    AQ => "AQ", "Antarctica";
+    AR => "AR", "Argentina";
+    AS => "AS", "American Samoa";
+    AT => "AT", "Austria";
+    AU => "AU", "Australia";
+    AW => "AW", "Aruba";
+    AX => "AX", "Åland Islands";
+    AZ => "AZ", "Azerbaijan";
+    BA => "BA", "Bosnia and Herzegovina";
+    BB => "BB", "Barbados";
+    BD => "BD", "Bangladesh";
+    BE => "BE", "Belgium";
+    BF => "BF", "Burkina Faso";
+    BG => "BG", "Bulgaria";
+    BH => "BH", "Bahrain";
+    BI => "BI", "Burundi";
+    BJ => "BJ", "Benin";
+    BL => "BL", "Saint Barthélemy";
+    BM => "BM", "Bermuda";
+    BN => "BN", "Brunei";
+    BO => "BO", "Bolivia";
+    BQ => "BQ", "Caribbean Netherlands";
+    BR => "BR", "Brazil";
+    BS => "BS", "Bahamas";
+    BT => "BT", "Bhutan";
+    BV => "BV", "Bouvet Island";
+    BW => "BW", "Botswana";
+    BY => "BY", "Belarus";
+    BZ => "BZ", "Belize";
+    CA => "CA", "Canada";
+    CC => "CC", "Cocos Islands";
+    CD => "CD", "Democratic Republic of the Congo";
+    CF => "CF", "Central African Republic";
+    CG => "CG", "Congo";
+    CH => "CH", "Switzerland";
+    CI => "CI", "Côte d'Ivoire";
+    CK => "CK", "Cook Islands";
+    CL => "CL", "Chile";
+    CM => "CM", "Cameroon";
+    CN => "CN", "China";
+    CO => "CO", "Colombia";
+    CR => "CR", "Costa Rica";
+    CU => "CU", "Cuba";
+    CV => "CV", "Cabo Verde";
+    CW => "CW", "Curaçao";
+    CX => "CX", "Christmas Island";
+    CY => "CY", "Cyprus";
+    CZ => "CZ", "Czechia";
+    DE => "DE", "Germany";
+    DJ => "DJ", "Djibouti";
+    DK => "DK", "Denmark";
+    DM => "DM", "Dominica";
+    DO => "DO", "Dominican Republic";
+    DZ => "DZ", "Algeria";
+    EC => "EC", "Ecuador";
+    EE => "EE", "Estonia";
+    EG => "EG", "Egypt";
+    EH => "EH", "Western Sahara";
+    ER => "ER", "Eritrea";
+    ES => "ES", "Spain";
+    ET => "ET", "Ethiopia";
+    FI => "FI", "Finland";
+    FJ => "FJ", "Fiji";
+    FK => "FK", "Falkland Islands";
+    FM => "FM", "Micronesia";
+    FO => "FO", "Faroe Islands";
+    FR => "FR", "France";
+    GA => "GA", "Gabon";
+    GB => "GB", "United Kingdom";
+    GbNir => "GB-NIR", "Northern Ireland";
+    GbSct => "GB-SCT", "Scotland";
+    GbWls => "GB-WLS", "Wales";
+    GD => "GD", "Grenada";
+    GE => "GE", "Georgia";
+    GF => "GF", "French Guiana";
+    GG => "GG", "Guernsey";
+    GH => "GH", "Ghana";
+    GI => "GI", "Gibraltar";
+    GL => "GL", "Greenland";
+    GM => "GM", "Gambia";
+    GN => "GN", "Guinea";
+    GP => "GP", "Guadeloupe";
+    GQ => "GQ", "Equatorial Guinea";
+    GR => "GR", "Greece";
+    GS => "GS", "South Georgia and the South Sandwich Islands";
+    GT => "GT", "Guatemala";
+    GU => "GU", "Guam";
+    GW => "GW", "Guinea-Bissau";
+    GY => "GY", "Guyana";
+    HK => "HK", "Hong Kong";
+    HM => "HM", "Heard Island and McDonald Islands";
+    HN => "HN", "Honduras";
+    HR => "HR", "Croatia";
+    HT => "HT", "Haiti";
+    HU => "HU", "Hungary";
+    ID => "ID", "Indonesia";
+    IE => "IE", "Ireland";
+    IL => "IL", "Israel";
+    IM => "IM", "Isle of Man";
+    IN => "IN", "India";
+    IO => "IO", "British Indian Ocean Territory";
+    IQ => "IQ", "Iraq";
+    IR => "IR", "Iran";
+    IS => "IS", "Iceland";
+    IT => "IT", "Italy";
+    JE => "JE", "Jersey";
+    JM => "JM", "Jamaica";
+    JO => "JO", "Jordan";
+    JP => "JP", "Japan";
+    KE => "KE", "Kenya";
+    KG => "KG", "Kyrgyzstan";
+    KH => "KH", "Cambodia";
+    KI => "KI", "Kiribati";
+    KM => "KM", "Comoros";
+    KN => "KN", "Saint Kitts and Nevis";
+    KP => "KP", "North Korea";
+    KR => "KR", "South Korea";
+    KW => "KW", "Kuwait";
+    KY => "KY", "Cayman Islands";
+    KZ => "KZ", "Kazakhstan";
+    LA => "LA", "Laos";
+    LB => "LB", "Lebanon";
+    LC => "LC", "Saint Lucia";
+    LI => "LI", "Liechtenstein";
+    LK => "LK", "Sri Lanka";
+    LR => "LR", "Liberia";
+    LS => "LS", "Lesotho";
+    LT => "LT", "Lithuania";
+    LU => "LU", "Luxembourg";
+    LV => "LV", "Latvia";
+    LY => "LY", "Libya";
+    MA => "MA", "Morocco";
+    MC => "MC", "Monaco";
+    MD => "MD", "Moldova";
+    ME => "ME", "Montenegro";
+    MF => "MF", "Saint Martin";
+    MG => "MG", "Madagascar";
+    MH => "MH", "Marshall Islands";
+    MK => "MK", "North Macedonia";
+    ML => "ML", "Mali";
+    MM => "MM", "Myanmar";
+    MN => "MN", "Mongolia";
+    MO => "MO", "Macao";
+    MP => "MP", "Northern Mariana Islands";
+    MQ => "MQ", "Martinique";
+    MR => "MR", "Mauritania";
+    MS => "MS", "Montserrat";
+    MT => "MT", "Malta";
+    MU => "MU", "Mauritius";
+    MV => "MV", "Maldives";
+    MW => "MW", "Malawi";
+    MX => "MX", "Mexico";
+    MY => "MY", "Malaysia";
+    MZ => "MZ", "Mozambique";
+    NA => "NA", "Namibia";
+    NC => "NC", "New Caledonia";
+    NE => "NE", "Niger";
+    NF => "NF", "Norfolk Island";
+    NG => "NG", "Nigeria";
+    NI => "NI", "Nicaragua";
+    NL => "NL", "Netherlands";
+    NO => "NO", "Norway";
+    NP => "NP", "Nepal";
+    NR => "NR", "Nauru";
+    NU => "NU", "Niue";
+    NZ => "NZ", "New Zealand";
+    OM => "OM", "Oman";
+    PA => "PA", "Panama";
+    PE => "PE", "Peru";
+    PF => "PF", "French Polynesia";
+    PG => "PG", "Papua New Guinea";
+    PH => "PH", "Philippines";
+    PK => "PK", "Pakistan";
+    PL => "PL", "Poland";
+    PM => "PM", "Saint Pierre and Miquelon";
+    PN => "PN", "Pitcairn Islands";
+    PR => "PR", "Puerto Rico";
+    PS => "PS", "Palestine";
+    PT => "PT", "Portugal";
+    PW => "PW", "Palau";
+    PY => "PY", "Paraguay";
+    QA => "QA", "Qatar";
+    RE => "RE", "Réunion";
+    RO => "RO", "Romania";
+    RS => "RS", "Serbia";
+    RU => "RU", "Russia";
+    RW => "RW", "Rwanda";
+    SA => "SA", "Saudi Arabia";
+    SB => "SB", "Solomon Islands";
+    SC => "SC", "Seychelles";
+    SD => "SD", "Sudan";
+    SE => "SE", "Sweden";
+    SG => "SG", "Singapore";
+    SH => "SH", "Saint Helena";
+    SI => "SI", "Slovenia";
+    SJ => "SJ", "Svalbard and Jan Mayen";
+    SK => "SK", "Slovakia";
+    SL => "SL", "Sierra Leone";
+    SM => "SM", "San Marino";
+    SN => "SN", "Senegal";
+    SO => "SO", "Somalia";
+    SR => "SR", "Suriname";
+    SS => "SS", "South Sudan";
+    ST => "ST", "Sao Tome and Principe";
+    SV => "SV", "El Salvador";
+    SX => "SX", "Sint Maarten";
+    SY => "SY", "Syria";
+    SZ => "SZ", "Eswatini";
+    TC => "TC", "Turks and Caicos Islands";
+    TD => "TD", "Chad";
+    TF => "TF", "French Southern Territories";
+    TG => "TG", "Togo";
+    TH => "TH", "Thailand";
+    TJ => "TJ", "Tajikistan";
+    TK => "TK", "Tokelau";
+    TL => "TL", "Timor-Leste";
+    TM => "TM", "Turkmenistan";
+    TN => "TN", "Tunisia";
+    TO => "TO", "Tonga";
+    TR => "TR", "Türkiye";
+    TT => "TT", "Trinidad and Tobago";
+    TV => "TV", "Tuvalu";
+    TW => "TW", "Taiwan";
+    TZ => "TZ", "Tanzania";
+    UA => "UA", "Ukraine";
+    UG => "UG", "Uganda";
+    UM => "UM", "United States Minor Outlying Islands";
+    US => "US", "United States";
+    UY => "UY", "Uruguay";
+    UZ => "UZ", "Uzbekistan";
+    VA => "VA", "Vatican City";
+    VC => "VC", "Saint Vincent and the Grenadines";
+    VE => "VE", "Venezuela";
+    VG => "VG", "British Virgin Islands";
+    VI => "VI", "U.S. Virgin Islands";
+    VN => "VN", "Vietnam";
+    VU => "VU", "Vanuatu";
+    WF => "WF", "Wallis and Futuna";
+    WS => "WS", "Samoa";
+    YE => "YE", "Yemen";
+    YT => "YT", "Mayotte";
+    ZA => "ZA", "South Africa";
+    ZM => "ZM", "Zambia";
+    ZW => "ZW", "Zimbabwe";
+}
+
+impl Country {
+    /// The emoji flag. Scotland and Wales use their subdivision flags, Northern Ireland has no
+    /// flag of its own so it gets the United Kingdom's
+    pub fn flag(&self) -> String {
+        match self.code().split_once('-') {
+            Some((_, "NIR")) => Country::GB.flag(),
+            Some((country, subdivision)) => std::iter::once('\u{1F3F4}')
+                .chain(
+                    format!("{}{}", country, subdivision)
+                        .to_lowercase()
+                        .chars()
+                        .filter_map(|c| char::from_u32(0xE0000 + c as u32)),
+                )
+                .chain(std::iter::once('\u{E007F}'))
+                .collect(),
+            None => self
+                .code()
+                .chars()
+                .filter_map(|c| char::from_u32(0x1F1E6 + (c as u32 - 'A' as u32)))
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for Country {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Parses a code or a name, ignoring case. `GB_SCT` is accepted as well as `GB-SCT`
+impl FromStr for Country {
+    type Err = ParseCountryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        let code = value.replace('_', "-");
+        Country::ALL
+            .iter()
+            .find(|country| {
+                country.code().eq_ignore_ascii_case(&code)
+                    || country.name().to_lowercase() == value.to_lowercase()
+            })
+            .copied()
+            .ok_or_else(|| ParseCountryError(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::country::Country;
+    use std::collections::HashSet;
+
+    #[test]
+    fn country_codes_test() {
+        assert_eq!(Country::ALL.len(), 252);
+        let codes: HashSet<_> = Country::ALL.iter().map(|country| country.code()).collect();
+        assert_eq!(codes.len(), Country::ALL.len());
+        for country in Country::ALL {
+            let json = serde_json::to_string(country).unwrap();
+            assert_eq!(json, format!("\"{}\"", country.code()));
+            assert_eq!(serde_json::from_str::<Country>(&json).unwrap(), *country);
+        }
+    }
+
+    #[test]
+    fn country_names_and_flags_test() {
+        assert_eq!(Country::NZ.name(), "New Zealand");
+        assert_eq!(Country::GbWls.to_string(), "Wales");
+        assert_eq!(Country::NZ.flag(), "🇳🇿");
+        assert_eq!(
+            Country::GbSct.flag(),
+            "🏴\u{E0067}\u{E0062}\u{E0073}\u{E0063}\u{E0074}\u{E007F}"
+        );
+        assert_eq!(Country::GbNir.flag(), "🇬🇧");
+    }
+
+    #[test]
+    fn country_from_str_test() {
+        assert_eq!("us".parse::<Country>().unwrap(), Country::US);
+        assert_eq!("gb-sct".parse::<Country>().unwrap(), Country::GbSct);
+        assert_eq!("GB_NIR".parse::<Country>().unwrap(), Country::GbNir);
+        assert_eq!(" south korea ".parse::<Country>().unwrap(), Country::KR);
+        assert_eq!("CÔTE D'IVOIRE".parse::<Country>().unwrap(), Country::CI);
+        assert!("Gielinor".parse::<Country>().is_err());
+    }
+}
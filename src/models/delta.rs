@@ -0,0 +1,15 @@
+use crate::models::global_types::PlayerId;
+use crate::models::player::Player;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// [Delta Leaderboard Entry](https://docs.wiseoldman.net/deltas-api/delta-type-definitions#object-delta-leaderboard-entry)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeltaLeaderboardEntry {
+    pub player_id: PlayerId,
+    pub gained: f64,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub player: Player,
+}
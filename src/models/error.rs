@@ -1,6 +1,37 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub message: String,
 }
+
+/// An error returned by the api, along with the HTTP status it was returned with.
+/// Client calls surface this inside their `anyhow::Error`, so it can be recovered with `downcast_ref`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn is_not_found(&self) -> bool {
+        self.status == 404
+    }
+
+    pub fn is_bad_request(&self) -> bool {
+        self.status == 400
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == 429
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
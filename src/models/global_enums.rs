@@ -1,6 +1,7 @@
 use convert_case::{Case, Casing};
 use serde::{Deserialize, Deserializer, Serialize};
-use strum::{AsRefStr, Display, EnumString};
+use std::fmt;
+use strum::{AsRefStr, Display, EnumIter, EnumString};
 
 /// [Period](https://docs.wiseoldman.net/global-type-definitions#enum-period)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -36,15 +37,15 @@ pub enum Metric {
     ComputedMetric(ComputedMetricEnum),
 }
 
-impl Metric {
-    pub fn to_string(&self) -> String {
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let metric_string = match self {
             Metric::Skill(skill) => skill.to_string(),
             Metric::Boss(boss) => boss.to_string(),
             Metric::Activity(activity) => activity.to_string(),
             Metric::ComputedMetric(computed_metric) => computed_metric.to_string(),
         };
-        return metric_string.to_case(Case::Snake);
+        write!(f, "{}", metric_string.to_case(Case::Snake))
     }
 }
 
@@ -81,7 +82,16 @@ impl<'de> Deserialize<'de> for Metric {
 
 /// [Skill](https://docs.wiseoldman.net/global-type-definitions#enum-skill)
 #[derive(
-    Debug, Default, Clone, PartialEq, Serialize, Deserialize, EnumString, AsRefStr, Display,
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    EnumString,
+    AsRefStr,
+    Display,
+    EnumIter,
 )]
 #[serde(rename_all = "camelCase")]
 pub enum Skill {
@@ -114,7 +124,9 @@ pub enum Skill {
 }
 
 /// [Boss](https://docs.wiseoldman.net/global-type-definitions#enum-boss)
-#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, EnumString, Display)]
+#[derive(
+    Debug, Default, Clone, PartialEq, Serialize, Deserialize, EnumString, Display, EnumIter,
+)]
 #[serde(rename_all = "snake_case")]
 pub enum Boss {
     #[default]
@@ -181,7 +193,9 @@ pub enum Boss {
 }
 
 /// [Computed Metric](https://docs.wiseoldman.net/global-type-definitions#enum-computed-metric)
-#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, EnumString, Display)]
+#[derive(
+    Debug, Default, Clone, PartialEq, Serialize, Deserialize, EnumString, Display, EnumIter,
+)]
 #[serde(rename_all = "camelCase")]
 pub enum ComputedMetricEnum {
     #[default]
@@ -190,7 +204,9 @@ pub enum ComputedMetricEnum {
     Ehb,
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, EnumString, Display)]
+#[derive(
+    Debug, Default, Clone, PartialEq, Serialize, Deserialize, EnumString, Display, EnumIter,
+)]
 #[serde(rename_all = "snake_case")]
 pub enum Activity {
     #[default]
@@ -1,6 +1,395 @@
-pub type Username = String;
-pub type PlayerId = i64;
-pub type GroupId = i64;
-pub type CompetitionId = i64;
-pub type RecordId = i64;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::num::ParseIntError;
+use std::str::FromStr;
+
 pub type GroupName = String;
+
+/// The most characters an OSRS name can have
+pub const MAX_USERNAME_LENGTH: usize = 12;
+
+/// Why a name is not a valid OSRS username
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidUsername {
+    pub username: String,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for InvalidUsername {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid username {:?}: {}", self.username, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidUsername {}
+
+/// The plain `String` [Username] was before usernames were checked
+#[deprecated(note = "usernames are checked now, make one with `Username::new` or `parse`")]
+pub type RawUsername = String;
+
+/// An OSRS username, checked against the game's rules when it is made.
+/// Wise Old Man treats case, underscores, hyphens and spaces as the same, so equality, ordering
+/// and hashing use the [Username::standardize]d form while the name keeps the spelling it was given.
+/// Names read from the api are trusted and not checked again
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Username(String);
+
+impl Username {
+    /// Checks the name is 1 to 12 letters, numbers, spaces, underscores or hyphens,
+    /// and does not start or end with a separator
+    pub fn new(username: impl Into<String>) -> Result<Self, InvalidUsername> {
+        let username = username.into();
+        let invalid = |reason| {
+            Err(InvalidUsername {
+                username: username.clone(),
+                reason,
+            })
+        };
+        let is_separator = |c: char| c == ' ' || c == '_' || c == '-';
+        if username.is_empty() {
+            return invalid("it is empty");
+        }
+        if username.chars().count() > MAX_USERNAME_LENGTH {
+            return invalid("it is longer than 12 characters");
+        }
+        if !username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || is_separator(c))
+        {
+            return invalid("only letters, numbers, spaces, underscores and hyphens are allowed");
+        }
+        if username.starts_with(is_separator) || username.ends_with(is_separator) {
+            return invalid("it starts or ends with a space, underscore or hyphen");
+        }
+        Ok(Self(username))
+    }
+
+    /// The name the way Wise Old Man stores it: lowercase, with underscores and hyphens as spaces
+    pub fn standardize(&self) -> String {
+        let spaced: String = self
+            .0
+            .chars()
+            .map(|c| match c {
+                '_' | '-' => ' ',
+                c => c.to_ascii_lowercase(),
+            })
+            .collect();
+        spaced.trim().to_string()
+    }
+
+    /// The name as it was given
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Debug for Username {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for Username {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl PartialEq for Username {
+    fn eq(&self, other: &Self) -> bool {
+        self.standardize() == other.standardize()
+    }
+}
+
+impl Eq for Username {}
+
+impl PartialOrd for Username {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Username {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.standardize().cmp(&other.standardize())
+    }
+}
+
+impl Hash for Username {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.standardize().hash(state)
+    }
+}
+
+/// Compares with the standardized form, so `"IFat_Fingers"` equals `Username` `ifat fingers`
+impl PartialEq<str> for Username {
+    fn eq(&self, other: &str) -> bool {
+        Username(other.to_string()).standardize() == self.standardize()
+    }
+}
+
+impl PartialEq<&str> for Username {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl FromStr for Username {
+    type Err = InvalidUsername;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Username::new(s)
+    }
+}
+
+impl TryFrom<String> for Username {
+    type Error = InvalidUsername;
+
+    fn try_from(username: String) -> Result<Self, Self::Error> {
+        Username::new(username)
+    }
+}
+
+impl TryFrom<&str> for Username {
+    type Error = InvalidUsername;
+
+    fn try_from(username: &str) -> Result<Self, Self::Error> {
+        Username::new(username)
+    }
+}
+
+impl From<Username> for String {
+    fn from(username: Username) -> Self {
+        username.0
+    }
+}
+
+impl AsRef<str> for Username {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl rusqlite::ToSql for Username {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        self.0.to_sql()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl rusqlite::types::FromSql for Username {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        String::column_result(value).map(Username)
+    }
+}
+
+/// What the clients take a username as: a [Username], or a string that is checked before
+/// anything is sent
+pub trait IntoUsername {
+    fn into_username(self) -> Result<Username, InvalidUsername>;
+}
+
+impl IntoUsername for Username {
+    fn into_username(self) -> Result<Username, InvalidUsername> {
+        Ok(self)
+    }
+}
+
+impl IntoUsername for &Username {
+    fn into_username(self) -> Result<Username, InvalidUsername> {
+        Ok(self.clone())
+    }
+}
+
+impl IntoUsername for String {
+    fn into_username(self) -> Result<Username, InvalidUsername> {
+        Username::new(self)
+    }
+}
+
+impl IntoUsername for &String {
+    fn into_username(self) -> Result<Username, InvalidUsername> {
+        Username::new(self.as_str())
+    }
+}
+
+impl IntoUsername for &str {
+    fn into_username(self) -> Result<Username, InvalidUsername> {
+        Username::new(self)
+    }
+}
+
+macro_rules! id_type {
+    ($(#[$meta:meta])* $name:ident, $raw:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name(pub i64);
+
+        impl $name {
+            /// The id as the plain `i64` it used to be
+            #[deprecated(note = "ids are newtypes now, use `i64::from(id)` or `id.0`")]
+            pub fn as_i64(&self) -> i64 {
+                self.0
+            }
+        }
+
+        #[doc = concat!("The plain `i64` [", stringify!($name), "] was before ids became newtypes")]
+        #[deprecated(note = "ids are newtypes now, which convert from and into `i64`")]
+        pub type $raw = i64;
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.trim().parse().map($name)
+            }
+        }
+
+        impl From<i64> for $name {
+            fn from(id: i64) -> Self {
+                $name(id)
+            }
+        }
+
+        impl From<$name> for i64 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        #[cfg(feature = "sqlite")]
+        impl rusqlite::ToSql for $name {
+            fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+                self.0.to_sql()
+            }
+        }
+
+        #[cfg(feature = "sqlite")]
+        impl rusqlite::types::FromSql for $name {
+            fn column_result(
+                value: rusqlite::types::ValueRef<'_>,
+            ) -> rusqlite::types::FromSqlResult<Self> {
+                i64::column_result(value).map($name)
+            }
+        }
+
+        /// Lets code written when ids were plain `i64`s keep comparing them to numbers
+        impl PartialEq<i64> for $name {
+            fn eq(&self, other: &i64) -> bool {
+                self.0 == *other
+            }
+        }
+    };
+}
+
+id_type!(
+    /// A player's id, serialized as the bare number the api uses
+    PlayerId,
+    RawPlayerId
+);
+id_type!(
+    /// A group's id, serialized as the bare number the api uses
+    GroupId,
+    RawGroupId
+);
+id_type!(
+    /// A competition's id, serialized as the bare number the api uses
+    CompetitionId,
+    RawCompetitionId
+);
+id_type!(
+    /// A record's id, serialized as the bare number the api uses
+    RecordId,
+    RawRecordId
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::models::global_types::{GroupId, PlayerId, Username};
+    use std::collections::HashSet;
+
+    #[test]
+    fn id_newtype_test() {
+        let id: PlayerId = serde_json::from_str("1234").unwrap();
+        assert_eq!(id, PlayerId(1234));
+        assert_eq!(serde_json::to_string(&id).unwrap(), "1234");
+        assert_eq!(id.to_string(), "1234");
+        assert_eq!(" 1234 ".parse::<PlayerId>().unwrap(), id);
+        assert!("zezima".parse::<PlayerId>().is_err());
+        assert_eq!(PlayerId::from(1234), id);
+        assert_eq!(i64::from(id), 1234);
+        assert_eq!(id, 1234);
+        assert_eq!(GroupId::from(7).to_string(), "7");
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_shims_test() {
+        use crate::models::global_types::{RawPlayerId, RawUsername};
+
+        let raw: RawPlayerId = 1234;
+        let id = PlayerId::from(raw);
+        assert_eq!(id.as_i64() + 1, 1235);
+        let mut name: RawUsername = "Lynx".to_string();
+        name.push_str(" Titan");
+        assert_eq!(name.parse::<Username>().unwrap(), "lynx titan");
+    }
+
+    #[test]
+    fn username_test() {
+        let username = Username::new("IFat_Fingers").unwrap();
+        assert_eq!(username.standardize(), "ifat fingers");
+        assert_eq!(username.to_string(), "IFat_Fingers");
+        assert_eq!(username, Username::new("ifat-fingers").unwrap());
+        assert_eq!(username, "IFAT FINGERS");
+        assert_ne!(username, "ifatfingers");
+
+        let names: HashSet<_> = ["Zezima", "zezima", "ZEZIMA"]
+            .into_iter()
+            .map(|name| Username::new(name).unwrap())
+            .collect();
+        assert_eq!(names.len(), 1);
+
+        let invalid = [
+            "",
+            "thirteen char",
+            "zez!ma",
+            " zezima",
+            "zezima_",
+            "zézima",
+        ];
+        for name in invalid {
+            assert!(Username::new(name).is_err(), "{:?} should be invalid", name);
+        }
+        assert!("Lynx Titan".parse::<Username>().is_ok());
+
+        // Separators become spaces before trimming, as Wise Old Man does
+        let untrimmed: Username = serde_json::from_str(r#""Lynx_Titan_""#).unwrap();
+        assert_eq!(untrimmed.standardize(), "lynx titan");
+        assert_eq!(untrimmed, "lynx titan");
+
+        let deserialized: Username = serde_json::from_str(r#""lynx titan""#).unwrap();
+        assert_eq!(deserialized, Username::new("Lynx_Titan").unwrap());
+        assert_eq!(
+            serde_json::to_string(&deserialized).unwrap(),
+            r#""lynx titan""#
+        );
+    }
+}
@@ -1,11 +1,16 @@
-use crate::models::global_types::{GroupId, PlayerId};
+use crate::models::global_types::{GroupId, PlayerId, Username};
 use crate::models::player::Player;
 use chrono::{DateTime, Utc};
+use convert_case::{Case, Casing};
 use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator, IntoStaticStr};
 
 /// [Group Role](https://docs.wiseoldman.net/groups-api/group-type-definitions#enum-group-role)
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumIter, IntoStaticStr,
+)]
 #[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
 pub enum GroupRole {
     Achiever,
     Adamant,
@@ -278,6 +283,80 @@ pub enum GroupRole {
     Zenyte,
 }
 
+/// How much say a [GroupRole] has in the group, most privileged first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GroupRoleCategory {
+    Owner,
+    DeputyOwner,
+    Administrator,
+    /// Every other role, these are only titles
+    Rank,
+}
+
+/// Rank names clans commonly use that are not spelled like the role
+const RANK_NAME_ALIASES: [(&str, GroupRole); 3] = [
+    ("admin", GroupRole::Administrator),
+    ("deputy", GroupRole::DeputyOwner),
+    ("mod", GroupRole::Moderator),
+];
+
+impl GroupRole {
+    /// The name shown for the role, e.g. `Deputy Owner`
+    pub fn name(&self) -> String {
+        self.icon_key().to_case(Case::Title)
+    }
+
+    /// The key for the role's in-game clan rank icon, the same as its api value, e.g. `deputy_owner`
+    pub fn icon_key(&self) -> &'static str {
+        self.into()
+    }
+
+    pub fn category(&self) -> GroupRoleCategory {
+        match self {
+            GroupRole::Owner => GroupRoleCategory::Owner,
+            GroupRole::DeputyOwner => GroupRoleCategory::DeputyOwner,
+            GroupRole::Administrator => GroupRoleCategory::Administrator,
+            _ => GroupRoleCategory::Rank,
+        }
+    }
+
+    /// Lower comes first: owner, deputy owner, administrator, then every other rank
+    pub fn priority(&self) -> u8 {
+        self.category() as u8
+    }
+
+    /// Whether the role can manage the group in game
+    pub fn is_privileged(&self) -> bool {
+        self.category() != GroupRoleCategory::Rank
+    }
+
+    /// Matches a free-text rank name, e.g. from a clan roster export, to a role.
+    /// Case, spaces, hyphens and underscores are ignored, so `Deputy Owner`, `deputy-owner`
+    /// and `DEPUTYOWNER` all match
+    pub fn from_rank_name(rank_name: &str) -> Option<GroupRole> {
+        let normalized = normalize_rank_name(rank_name);
+        if normalized.is_empty() {
+            return None;
+        }
+        GroupRole::iter()
+            .find(|role| normalize_rank_name(role.icon_key()) == normalized)
+            .or_else(|| {
+                RANK_NAME_ALIASES
+                    .iter()
+                    .find(|(alias, _)| *alias == normalized)
+                    .map(|(_, role)| *role)
+            })
+    }
+}
+
+fn normalize_rank_name(rank_name: &str) -> String {
+    rank_name
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
 /// [Activity Type](https://docs.wiseoldman.net/groups-api/group-type-definitions#enum-activity-type)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -381,7 +460,7 @@ pub struct PlayerMembership {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GroupMemberFragment {
-    pub username: PlayerId,
+    pub username: Username,
     pub role: Option<GroupRole>,
 }
 
@@ -429,3 +508,91 @@ pub struct GroupCreateResponse {
     pub group: GroupDetail,
     pub verification_code: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::models::group::{GroupRole, GroupRoleCategory};
+    use std::collections::HashSet;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn group_role_icon_keys_test() {
+        let roles: Vec<GroupRole> = GroupRole::iter().collect();
+        assert_eq!(roles.len(), 269);
+        let keys: HashSet<_> = roles.iter().map(|role| role.icon_key()).collect();
+        assert_eq!(keys.len(), roles.len());
+        for role in &roles {
+            let json = serde_json::to_string(role).unwrap();
+            assert_eq!(json, format!("\"{}\"", role.icon_key()));
+            assert_eq!(serde_json::from_str::<GroupRole>(&json).unwrap(), *role);
+        }
+        assert_eq!(GroupRole::RedTopaz.icon_key(), "red_topaz");
+    }
+
+    #[test]
+    fn group_role_names_test() {
+        let names: HashSet<_> = GroupRole::iter().map(|role| role.name()).collect();
+        assert_eq!(names.len(), GroupRole::iter().count());
+        assert_eq!(GroupRole::DeputyOwner.name(), "Deputy Owner");
+        assert_eq!(GroupRole::ShortGreenGuy.name(), "Short Green Guy");
+        assert_eq!(GroupRole::Zenyte.name(), "Zenyte");
+    }
+
+    #[test]
+    fn group_role_priority_test() {
+        let mut roles: Vec<GroupRole> = GroupRole::iter().collect();
+        roles.sort_by_key(|role| role.priority());
+        assert_eq!(
+            roles[..4],
+            [
+                GroupRole::Owner,
+                GroupRole::DeputyOwner,
+                GroupRole::Administrator,
+                GroupRole::Achiever
+            ]
+        );
+        assert_eq!(GroupRole::Owner.category(), GroupRoleCategory::Owner);
+        assert_eq!(
+            GroupRole::DeputyOwner.category(),
+            GroupRoleCategory::DeputyOwner
+        );
+        assert_eq!(
+            GroupRole::Administrator.category(),
+            GroupRoleCategory::Administrator
+        );
+        assert_eq!(GroupRole::Moderator.category(), GroupRoleCategory::Rank);
+        assert_eq!(GroupRole::Achiever.category(), GroupRoleCategory::Rank);
+        assert_eq!(
+            GroupRole::from_rank_name("deputy"),
+            Some(GroupRole::DeputyOwner)
+        );
+        assert!(GroupRole::Owner.is_privileged());
+        assert!(GroupRole::Administrator.is_privileged());
+        assert!(!GroupRole::Moderator.is_privileged());
+        assert!(!GroupRole::Member.is_privileged());
+    }
+
+    #[test]
+    fn group_role_from_rank_name_test() {
+        for role in GroupRole::iter() {
+            assert_eq!(GroupRole::from_rank_name(&role.name()), Some(role));
+            assert_eq!(GroupRole::from_rank_name(role.icon_key()), Some(role));
+            let shouted = format!("  {}  ", role.name().to_uppercase().replace(' ', "-"));
+            assert_eq!(GroupRole::from_rank_name(&shouted), Some(role));
+        }
+        assert_eq!(
+            GroupRole::from_rank_name("Deputy owner"),
+            Some(GroupRole::DeputyOwner)
+        );
+        assert_eq!(
+            GroupRole::from_rank_name("Speed Runner"),
+            Some(GroupRole::SpeedRunner)
+        );
+        assert_eq!(
+            GroupRole::from_rank_name("Admin"),
+            Some(GroupRole::Administrator)
+        );
+        assert_eq!(GroupRole::from_rank_name("Guest"), None);
+        assert_eq!(GroupRole::from_rank_name("  "), None);
+    }
+}
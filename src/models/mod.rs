@@ -1,5 +1,9 @@
 /// Possible Responses found in [Competitions Types & Entities](https://docs.wiseoldman.net/competitions-api/competition-type-definitions)
 pub mod competition;
+/// Every country a player can set, with names and flags
+pub mod country;
+/// Possible Responses found in [Delta Types & Entities](https://docs.wiseoldman.net/deltas-api/delta-type-definitions)
+pub mod delta;
 ///Error models from the api
 pub mod error;
 /// Possible enums found in all models [Global Types & Entities](https://docs.wiseoldman.net/global-type-definitions)
@@ -1,5 +1,6 @@
-use crate::models::global_enums::{Activity, Boss, Skill};
-use crate::models::global_types::PlayerId;
+pub use crate::models::country::Country;
+use crate::models::global_enums::{Activity, Boss, ComputedMetricEnum, Metric, Skill};
+use crate::models::global_types::{PlayerId, Username};
 use crate::models::snapshot::{Activities, Bosses, Computed, Skills};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -15,6 +16,18 @@ pub enum PlayerType {
     Ultimate,
 }
 
+impl PlayerType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlayerType::Unknown => "unknown",
+            PlayerType::Regular => "regular",
+            PlayerType::Ironman => "ironman",
+            PlayerType::Hardcore => "hardcore",
+            PlayerType::Ultimate => "ultimate",
+        }
+    }
+}
+
 /// [Player Build](https://docs.wiseoldman.net/players-api/player-type-definitions#enum-player-build)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -28,6 +41,20 @@ pub enum PlayerBuild {
     F2pLvl3,
 }
 
+impl PlayerBuild {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlayerBuild::Main => "main",
+            PlayerBuild::F2p => "f2p",
+            PlayerBuild::Lvl3 => "lvl3",
+            PlayerBuild::Zerker => "zerker",
+            PlayerBuild::Def1 => "def1",
+            PlayerBuild::Hp10 => "hp10",
+            PlayerBuild::F2pLvl3 => "f2pLvl3",
+        }
+    }
+}
+
 /// [Player Status](https://docs.wiseoldman.net/players-api/player-type-definitions#enum-player-status)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -39,260 +66,6 @@ pub enum PlayerStatus {
     Banned,
 }
 
-/// [Country](https://docs.wiseoldman.net/players-api/player-type-definitions#enum-country)
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum Country {
-    AD,
-    AE,
-    AF,
-    AG,
-    AI,
-    AL,
-    AM,
-    AO,
-    AQ,
-    AR,
-    AS,
-    AT,
-    AU,
-    AW,
-    AX,
-    AZ,
-    BA,
-    BB,
-    BD,
-    BE,
-    BF,
-    BG,
-    BH,
-    BI,
-    BJ,
-    BL,
-    BM,
-    BN,
-    BO,
-    BQ,
-    BR,
-    BS,
-    BT,
-    BV,
-    BW,
-    BY,
-    BZ,
-    CA,
-    CC,
-    CD,
-    CF,
-    CG,
-    CH,
-    CI,
-    CK,
-    CL,
-    CM,
-    CN,
-    CO,
-    CR,
-    CU,
-    CV,
-    CW,
-    CX,
-    CY,
-    CZ,
-    DE,
-    DJ,
-    DK,
-    DM,
-    DO,
-    DZ,
-    EC,
-    EE,
-    EG,
-    EH,
-    ER,
-    ES,
-    ET,
-    FI,
-    FJ,
-    FK,
-    FM,
-    FO,
-    FR,
-    GA,
-    GB,
-    GD,
-    GE,
-    GF,
-    GG,
-    GH,
-    GI,
-    GL,
-    GM,
-    GN,
-    GP,
-    GQ,
-    GR,
-    GS,
-    GT,
-    GU,
-    GW,
-    GY,
-    HK,
-    HM,
-    HN,
-    HR,
-    HT,
-    HU,
-    ID,
-    IE,
-    IL,
-    IM,
-    IN,
-    IO,
-    IQ,
-    IR,
-    IS,
-    IT,
-    JE,
-    JM,
-    JO,
-    JP,
-    KE,
-    KG,
-    KH,
-    KI,
-    KM,
-    KN,
-    KP,
-    KR,
-    KW,
-    KY,
-    KZ,
-    LA,
-    LB,
-    LC,
-    LI,
-    LK,
-    LR,
-    LS,
-    LT,
-    LU,
-    LV,
-    LY,
-    MA,
-    MC,
-    MD,
-    ME,
-    MF,
-    MG,
-    MH,
-    MK,
-    ML,
-    MM,
-    MN,
-    MO,
-    MP,
-    MQ,
-    MR,
-    MS,
-    MT,
-    MU,
-    MV,
-    MW,
-    MX,
-    MY,
-    MZ,
-    NA,
-    NC,
-    NE,
-    NF,
-    NG,
-    NI,
-    NL,
-    NO,
-    NP,
-    NR,
-    NU,
-    NZ,
-    OM,
-    PA,
-    PE,
-    PF,
-    PG,
-    PH,
-    PK,
-    PL,
-    PM,
-    PN,
-    PR,
-    PS,
-    PT,
-    PW,
-    PY,
-    QA,
-    RE,
-    RO,
-    RS,
-    RU,
-    RW,
-    SA,
-    SB,
-    SC,
-    SD,
-    SE,
-    SG,
-    SH,
-    SI,
-    SJ,
-    SK,
-    SL,
-    SM,
-    SN,
-    SO,
-    SR,
-    SS,
-    ST,
-    SV,
-    SX,
-    SY,
-    SZ,
-    TC,
-    TD,
-    TF,
-    TG,
-    TH,
-    TJ,
-    TK,
-    TL,
-    TM,
-    TN,
-    TO,
-    TR,
-    TT,
-    TV,
-    TW,
-    TZ,
-    UA,
-    UG,
-    UM,
-    US,
-    UY,
-    UZ,
-    VA,
-    VC,
-    VE,
-    VG,
-    VI,
-    VN,
-    VU,
-    WF,
-    WS,
-    YE,
-    YT,
-    ZA,
-    ZM,
-    ZW,
-}
-
 /// [Achievement Measure](https://docs.wiseoldman.net/players-api/player-type-definitions#enum-achievement-measure)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -314,15 +87,33 @@ pub struct SnapShotData {
     pub computed: Computed,
 }
 
+impl SnapShotData {
+    /// The value tracked for a metric: experience for skills, kills for bosses, score for
+    /// activities and the value of computed metrics. Unranked values are `-1`, as the api returns them
+    pub fn value(&self, metric: &Metric) -> Option<f64> {
+        match metric {
+            Metric::Skill(skill) => self.skills.get(skill).map(|value| value.experience as f64),
+            Metric::Boss(boss) => self.bosses.get(boss).map(|value| value.kills as f64),
+            Metric::Activity(activity) => self
+                .activities
+                .get(activity)
+                .map(|value| value.score as f64),
+            Metric::ComputedMetric(computed) => {
+                self.computed.get(computed).map(|value| value.value)
+            }
+        }
+    }
+}
+
 /// [SnapShot](https://docs.wiseoldman.net/players-api/player-type-definitions#object-snapshot)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SnapShot {
-    id: i64,
-    player_id: i64,
-    created_at: DateTime<Utc>,
-    imported_at: Option<DateTime<Utc>>,
-    data: SnapShotData,
+    pub id: i64,
+    pub player_id: PlayerId,
+    pub created_at: DateTime<Utc>,
+    pub imported_at: Option<DateTime<Utc>>,
+    pub data: SnapShotData,
 }
 
 /// [Player](https://docs.wiseoldman.net/players-api/player-type-definitions#object-player)
@@ -330,7 +121,7 @@ pub struct SnapShot {
 #[serde(rename_all = "camelCase")]
 pub struct Player {
     pub id: PlayerId,
-    pub username: String,
+    pub username: Username,
     pub display_name: String,
     #[serde(rename = "type")]
     pub player_type: PlayerType,
@@ -343,7 +134,7 @@ pub struct Player {
     pub ehb: f64,
     pub ttm: f64,
     pub tt200m: f64,
-    pub registered_at: String,
+    pub registered_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     pub last_changed_at: Option<DateTime<Utc>>,
     pub last_imported_at: Option<DateTime<Utc>>,
@@ -354,7 +145,7 @@ pub struct Player {
 #[serde(rename_all = "camelCase")]
 pub struct PlayerDetails {
     pub id: PlayerId,
-    pub username: String,
+    pub username: Username,
     pub display_name: String,
     #[serde(rename = "type")]
     pub player_type: PlayerType,
@@ -367,7 +158,7 @@ pub struct PlayerDetails {
     pub ehb: f64,
     pub ttm: f64,
     pub tt200m: f64,
-    pub registered_at: String,
+    pub registered_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     pub last_changed_at: Option<DateTime<Utc>>,
     pub last_imported_at: Option<DateTime<Utc>>,
@@ -380,7 +171,7 @@ pub struct PlayerDetails {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Achievement {
-    player_id: i64,
+    player_id: PlayerId,
     name: String,
     metric: String,
     measure: AchievementMeasure,
@@ -443,6 +234,7 @@ pub struct PlayerGainData {
     pub skills: SkillGains,
     pub bosses: BossGains,
     pub activities: ActivityGains,
+    pub computed: ComputedGains,
 }
 
 /// The gains of a player's skills
@@ -563,7 +355,7 @@ pub struct BossGain {
 
 /// The gains of a player's activities
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "snake_case")]
 pub struct ActivityGains {
     pub league_points: Option<ActivityGain>,
     pub bounty_hunter_hunter: Option<ActivityGain>,
@@ -590,6 +382,23 @@ pub struct ActivityGain {
     pub rank: GainProgress,
 }
 
+/// The gains of a player's computed metrics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComputedGains {
+    pub ehp: ComputedGain,
+    pub ehb: ComputedGain,
+}
+
+/// The gains of a player's computed metric
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComputedGain {
+    pub metric: ComputedMetricEnum,
+    pub value: GainProgress,
+    pub rank: GainProgress,
+}
+
 /// Each metrics gain progress.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -154,3 +154,150 @@ pub struct ComputedMetric {
     pub value: f64,
     pub rank: i64,
 }
+
+impl Skills {
+    /// Every skill in the snapshot, in the order the api lists them
+    pub fn iter(&self) -> impl Iterator<Item = &SkillMetric> {
+        [
+            &self.overall,
+            &self.attack,
+            &self.defence,
+            &self.strength,
+            &self.hitpoints,
+            &self.ranged,
+            &self.prayer,
+            &self.magic,
+            &self.cooking,
+            &self.woodcutting,
+            &self.fletching,
+            &self.fishing,
+            &self.firemaking,
+            &self.crafting,
+            &self.smithing,
+            &self.mining,
+            &self.herblore,
+            &self.agility,
+            &self.thieving,
+            &self.slayer,
+            &self.farming,
+            &self.runecrafting,
+            &self.hunter,
+            &self.construction,
+        ]
+        .into_iter()
+    }
+
+    pub fn get(&self, metric: &Skill) -> Option<&SkillMetric> {
+        self.iter().find(|value| &value.metric == metric)
+    }
+}
+
+impl Bosses {
+    /// Every boss in the snapshot, in the order the api lists them
+    pub fn iter(&self) -> impl Iterator<Item = &BossMetric> {
+        [
+            &self.abyssal_sire,
+            &self.alchemical_hydra,
+            &self.artio,
+            &self.barrows_chests,
+            &self.bryophyta,
+            &self.callisto,
+            &self.calvarion,
+            &self.cerberus,
+            &self.chambers_of_xeric,
+            &self.chambers_of_xeric_challenge_mode,
+            &self.chaos_elemental,
+            &self.chaos_fanatic,
+            &self.commander_zilyana,
+            &self.corporeal_beast,
+            &self.crazy_archaeologist,
+            &self.dagannoth_prime,
+            &self.dagannoth_rex,
+            &self.dagannoth_supreme,
+            &self.deranged_archaeologist,
+            &self.duke_sucellus,
+            &self.general_graardor,
+            &self.giant_mole,
+            &self.grotesque_guardians,
+            &self.hespori,
+            &self.kalphite_queen,
+            &self.king_black_dragon,
+            &self.kraken,
+            &self.kreearra,
+            &self.kril_tsutsaroth,
+            &self.mimic,
+            &self.nex,
+            &self.nightmare,
+            &self.phosanis_nightmare,
+            &self.obor,
+            &self.phantom_muspah,
+            &self.sarachnis,
+            &self.scorpia,
+            &self.scurrius,
+            &self.skotizo,
+            &self.spindel,
+            &self.tempoross,
+            &self.the_gauntlet,
+            &self.the_corrupted_gauntlet,
+            &self.the_leviathan,
+            &self.the_whisperer,
+            &self.theatre_of_blood,
+            &self.theatre_of_blood_hard_mode,
+            &self.thermonuclear_smoke_devil,
+            &self.tombs_of_amascut,
+            &self.tombs_of_amascut_expert,
+            &self.tzkal_zuk,
+            &self.tztok_jad,
+            &self.vardorvis,
+            &self.venenatis,
+            &self.vetion,
+            &self.vorkath,
+            &self.wintertodt,
+            &self.zalcano,
+            &self.zulrah,
+        ]
+        .into_iter()
+    }
+
+    pub fn get(&self, metric: &Boss) -> Option<&BossMetric> {
+        self.iter().find(|value| &value.metric == metric)
+    }
+}
+
+impl Activities {
+    /// Every activity in the snapshot, in the order the api lists them
+    pub fn iter(&self) -> impl Iterator<Item = &ActivityMetric> {
+        [
+            &self.league_points,
+            &self.bounty_hunter_hunter,
+            &self.bounty_hunter_rogue,
+            &self.clue_scrolls_all,
+            &self.clue_scrolls_beginner,
+            &self.clue_scrolls_easy,
+            &self.clue_scrolls_medium,
+            &self.clue_scrolls_hard,
+            &self.clue_scrolls_elite,
+            &self.clue_scrolls_master,
+            &self.last_man_standing,
+            &self.pvp_arena,
+            &self.soul_wars_zeal,
+            &self.guardians_of_the_rift,
+        ]
+        .into_iter()
+    }
+
+    pub fn get(&self, metric: &Activity) -> Option<&ActivityMetric> {
+        self.iter().find(|value| &value.metric == metric)
+    }
+}
+
+impl Computed {
+    /// Every computed metric in the snapshot, in the order the api lists them
+    pub fn iter(&self) -> impl Iterator<Item = &ComputedMetric> {
+        [&self.ehp, &self.ehb].into_iter()
+    }
+
+    pub fn get(&self, metric: &ComputedMetricEnum) -> Option<&ComputedMetric> {
+        self.iter().find(|value| &value.metric == metric)
+    }
+}
@@ -0,0 +1,397 @@
+use crate::models::competition::CompetitionStatus;
+use crate::models::global_enums::{Metric, Period};
+use crate::models::player::{Country, PlayerBuild, PlayerType};
+use crate::{Pagination, QueryParams};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::fmt;
+
+/// The most results the api returns per page
+const MAX_LIMIT: i32 = 50;
+
+/// Why a query could not be sent
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryError {
+    /// The start of a date range was not before its end
+    StartNotBeforeEnd {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+    /// Both a period and a date range were given, the api takes one or the other
+    PeriodAndDateRange,
+    /// A query that needs a period or a date range was given neither
+    MissingPeriodOrDateRange,
+    /// The limit was outside of 1 to 50, or the offset was negative
+    InvalidPagination { limit: i32, offset: i32 },
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::StartNotBeforeEnd { start, end } => write!(
+                f,
+                "Start date {} must come before end date {}",
+                to_iso_8601(start),
+                to_iso_8601(end)
+            ),
+            QueryError::PeriodAndDateRange => {
+                write!(f, "A period and a date range cannot be used together")
+            }
+            QueryError::MissingPeriodOrDateRange => {
+                write!(f, "Either a period or a date range is required")
+            }
+            QueryError::InvalidPagination { limit, offset } => write!(
+                f,
+                "Invalid pagination, limit {} must be between 1 and {} and offset {} cannot be negative",
+                limit, MAX_LIMIT, offset
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Formats a date the way the api expects it, e.g. `2024-01-29T06:27:50.475Z`
+pub fn to_iso_8601(date: &DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Validates optional pagination before it is turned into query parameters
+pub(crate) fn pagination_to_query(
+    pagination: &Option<Pagination>,
+) -> Result<QueryParams, QueryError> {
+    match pagination {
+        Some(pagination) => {
+            let limit = pagination.limit.unwrap_or(20);
+            let offset = pagination.offset.unwrap_or(0);
+            if !(1..=MAX_LIMIT).contains(&limit) || offset < 0 {
+                return Err(QueryError::InvalidPagination { limit, offset });
+            }
+            Ok(pagination.to_query())
+        }
+        None => Ok(vec![]),
+    }
+}
+
+/// A start and end date, the start always comes before the end
+#[derive(Debug, Clone, PartialEq)]
+pub struct DateRange {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+impl DateRange {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Self, QueryError> {
+        if start >= end {
+            return Err(QueryError::StartNotBeforeEnd { start, end });
+        }
+        Ok(Self { start, end })
+    }
+
+    pub fn start(&self) -> DateTime<Utc> {
+        self.start
+    }
+
+    pub fn end(&self) -> DateTime<Utc> {
+        self.end
+    }
+
+    pub fn to_query(&self) -> QueryParams {
+        vec![
+            ("startDate".to_string(), to_iso_8601(&self.start)),
+            ("endDate".to_string(), to_iso_8601(&self.end)),
+        ]
+    }
+}
+
+/// Filters for endpoints built from snapshots: snapshots, timelines and gains.
+/// Takes either a period or a date range, not both
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SnapshotQuery {
+    period: Option<Period>,
+    date_range: Option<DateRange>,
+    pagination: Option<Pagination>,
+}
+
+impl SnapshotQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn period(mut self, period: Period) -> Self {
+        self.period = Some(period);
+        self
+    }
+
+    pub fn date_range(mut self, date_range: DateRange) -> Self {
+        self.date_range = Some(date_range);
+        self
+    }
+
+    pub fn pagination(mut self, pagination: Pagination) -> Self {
+        self.pagination = Some(pagination);
+        self
+    }
+
+    pub fn to_query(&self) -> Result<QueryParams, QueryError> {
+        let mut queries = match (&self.period, &self.date_range) {
+            (Some(_), Some(_)) => return Err(QueryError::PeriodAndDateRange),
+            (Some(period), None) => vec![("period".to_string(), period.as_str().to_string())],
+            (None, Some(date_range)) => date_range.to_query(),
+            (None, None) => return Err(QueryError::MissingPeriodOrDateRange),
+        };
+        queries.extend(pagination_to_query(&self.pagination)?);
+        Ok(queries)
+    }
+}
+
+impl From<Period> for SnapshotQuery {
+    fn from(period: Period) -> Self {
+        SnapshotQuery::new().period(period)
+    }
+}
+
+impl From<DateRange> for SnapshotQuery {
+    fn from(date_range: DateRange) -> Self {
+        SnapshotQuery::new().date_range(date_range)
+    }
+}
+
+/// Filters for record endpoints, both the period and metric are optional
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RecordQuery {
+    period: Option<Period>,
+    metric: Option<Metric>,
+}
+
+impl RecordQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn period(mut self, period: Period) -> Self {
+        self.period = Some(period);
+        self
+    }
+
+    pub fn metric(mut self, metric: Metric) -> Self {
+        self.metric = Some(metric);
+        self
+    }
+
+    pub fn to_query(&self) -> Result<QueryParams, QueryError> {
+        let mut queries = Vec::new();
+        if let Some(period) = &self.period {
+            queries.push(("period".to_string(), period.as_str().to_string()));
+        }
+        if let Some(metric) = &self.metric {
+            queries.push(("metric".to_string(), metric.to_string()));
+        }
+        Ok(queries)
+    }
+}
+
+/// Filters for competition endpoints, takes an optional status and pagination
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CompetitionQuery {
+    status: Option<CompetitionStatus>,
+    pagination: Option<Pagination>,
+}
+
+impl CompetitionQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(mut self, status: CompetitionStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn pagination(mut self, pagination: Pagination) -> Self {
+        self.pagination = Some(pagination);
+        self
+    }
+
+    pub fn to_query(&self) -> Result<QueryParams, QueryError> {
+        let mut queries = pagination_to_query(&self.pagination)?;
+        if let Some(status) = &self.status {
+            queries.push(("status".to_string(), status.as_str().to_string()));
+        }
+        Ok(queries)
+    }
+}
+
+/// Filters for the leaderboard endpoints, narrows the ranked players by type, build or country
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LeaderboardQuery {
+    player_type: Option<PlayerType>,
+    player_build: Option<PlayerBuild>,
+    country: Option<Country>,
+}
+
+impl LeaderboardQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn player_type(mut self, player_type: PlayerType) -> Self {
+        self.player_type = Some(player_type);
+        self
+    }
+
+    pub fn player_build(mut self, player_build: PlayerBuild) -> Self {
+        self.player_build = Some(player_build);
+        self
+    }
+
+    pub fn country(mut self, country: Country) -> Self {
+        self.country = Some(country);
+        self
+    }
+
+    pub fn to_query(&self) -> Result<QueryParams, QueryError> {
+        let mut queries = Vec::new();
+        if let Some(player_type) = &self.player_type {
+            queries.push(("playerType".to_string(), player_type.as_str().to_string()));
+        }
+        if let Some(player_build) = &self.player_build {
+            queries.push(("playerBuild".to_string(), player_build.as_str().to_string()));
+        }
+        if let Some(country) = &self.country {
+            queries.push(("country".to_string(), country.code().to_string()));
+        }
+        Ok(queries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::competition::CompetitionStatus;
+    use crate::models::global_enums::{Boss, Metric, Period};
+    use crate::models::player::{Country, PlayerBuild, PlayerType};
+    use crate::query::{
+        CompetitionQuery, DateRange, LeaderboardQuery, QueryError, RecordQuery, SnapshotQuery,
+    };
+    use crate::Pagination;
+    use chrono::{TimeZone, Utc};
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn date_range_serialises_to_iso_8601_test() {
+        let range = DateRange::new(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 31, 12, 30, 5).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            range.to_query(),
+            params(&[
+                ("startDate", "2024-01-01T00:00:00.000Z"),
+                ("endDate", "2024-01-31T12:30:05.000Z")
+            ])
+        );
+    }
+
+    #[test]
+    fn date_range_start_must_come_before_end_test() {
+        let start = Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        assert_eq!(
+            DateRange::new(start, end),
+            Err(QueryError::StartNotBeforeEnd { start, end })
+        );
+        assert!(DateRange::new(start, start).is_err());
+    }
+
+    #[test]
+    fn snapshot_query_period_and_date_range_are_exclusive_test() {
+        let range = DateRange::new(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+        )
+        .unwrap();
+
+        let query = SnapshotQuery::new().period(Period::Week).date_range(range);
+
+        assert_eq!(query.to_query(), Err(QueryError::PeriodAndDateRange));
+        assert_eq!(
+            SnapshotQuery::new().to_query(),
+            Err(QueryError::MissingPeriodOrDateRange)
+        );
+    }
+
+    #[test]
+    fn snapshot_query_with_pagination_test() {
+        let query = SnapshotQuery::from(Period::FiveMin).pagination(Pagination {
+            limit: Some(5),
+            offset: None,
+        });
+
+        assert_eq!(
+            query.to_query().unwrap(),
+            params(&[("period", "five_min"), ("limit", "5"), ("offset", "0")])
+        );
+    }
+
+    #[test]
+    fn invalid_pagination_test() {
+        let query = CompetitionQuery::new().pagination(Pagination {
+            limit: Some(51),
+            offset: Some(0),
+        });
+
+        assert_eq!(
+            query.to_query(),
+            Err(QueryError::InvalidPagination {
+                limit: 51,
+                offset: 0
+            })
+        );
+    }
+
+    #[test]
+    fn record_query_test() {
+        let query = RecordQuery::new()
+            .period(Period::Day)
+            .metric(Metric::Boss(Boss::Zulrah));
+
+        assert_eq!(
+            query.to_query().unwrap(),
+            params(&[("period", "day"), ("metric", "zulrah")])
+        );
+        assert!(RecordQuery::new().to_query().unwrap().is_empty());
+    }
+
+    #[test]
+    fn competition_query_test() {
+        let query = CompetitionQuery::new().status(CompetitionStatus::Ongoing);
+
+        assert_eq!(query.to_query().unwrap(), params(&[("status", "ongoing")]));
+    }
+
+    #[test]
+    fn leaderboard_query_test() {
+        let query = LeaderboardQuery::new()
+            .player_type(PlayerType::Ironman)
+            .player_build(PlayerBuild::F2pLvl3)
+            .country(Country::GbSct);
+
+        assert_eq!(
+            query.to_query().unwrap(),
+            params(&[
+                ("playerType", "ironman"),
+                ("playerBuild", "f2pLvl3"),
+                ("country", "GB-SCT")
+            ])
+        );
+        assert!(LeaderboardQuery::new().to_query().unwrap().is_empty());
+    }
+}
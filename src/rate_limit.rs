@@ -0,0 +1,59 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Spaces out calls so no more than `requests_per_minute` start in any minute
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests_per_minute.max(1),
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Waits until the next request is allowed to start
+    pub async fn acquire(&self) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let now = Instant::now();
+            let slot = match *next_slot {
+                Some(next) if next > now => next,
+                _ => now,
+            };
+            *next_slot = Some(slot + self.interval);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+/// Waits for the next slot when a rate limit is configured
+pub(crate) async fn acquire(limiter: Option<&RateLimiter>) {
+    if let Some(limiter) = limiter {
+        limiter.acquire().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rate_limit::RateLimiter;
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    #[tokio::test(start_paused = true)]
+    async fn spaces_out_requests_test() {
+        let limiter = RateLimiter::new(20);
+        let started = Instant::now();
+
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+
+        assert_eq!(started.elapsed(), Duration::from_secs(6));
+    }
+}
@@ -0,0 +1,565 @@
+use crate::clients::competition_client::CompetitionClient;
+use crate::models::competition::{CompetitionCreateResponse, CreateCompetition};
+use crate::models::global_enums::Metric;
+use crate::models::global_types::GroupId;
+use crate::transport::Transport;
+use anyhow::anyhow;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use convert_case::{Case, Casing};
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How the metric of each competition is picked
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rotation {
+    /// The metrics in order, starting over after the last one
+    Fixed(Vec<Metric>),
+    /// A random metric, likelier the higher its weight, leaving out the last `avoid_recent` picked
+    Weighted {
+        choices: Vec<(Metric, u32)>,
+        avoid_recent: usize,
+    },
+}
+
+/// When competitions start. Each one runs until the next starts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cadence {
+    /// Every week on `weekday` at `at`
+    Weekly { weekday: Weekday, at: NaiveTime },
+    /// Every `days` days at `at`
+    EveryDays { days: u32, at: NaiveTime },
+}
+
+impl Cadence {
+    fn days(&self) -> u32 {
+        match self {
+            Cadence::Weekly { .. } => 7,
+            Cadence::EveryDays { days, .. } => *days,
+        }
+    }
+
+    fn at(&self) -> NaiveTime {
+        match self {
+            Cadence::Weekly { at, .. } | Cadence::EveryDays { at, .. } => *at,
+        }
+    }
+
+    /// The first start at or after `after`
+    fn first_start(
+        &self,
+        after: DateTime<Utc>,
+        timezone: Tz,
+    ) -> Result<DateTime<Utc>, anyhow::Error> {
+        let today = after.with_timezone(&timezone).date_naive();
+        let first_day = match self {
+            Cadence::Weekly { weekday, .. } => {
+                let days_ahead = (7 + weekday.num_days_from_monday()
+                    - today.weekday().num_days_from_monday())
+                    % 7;
+                today + Duration::days(days_ahead as i64)
+            }
+            Cadence::EveryDays { .. } => today,
+        };
+        let start = local_time(timezone, first_day, self.at())?;
+        if start >= after {
+            return Ok(start);
+        }
+        let step = match self {
+            Cadence::Weekly { .. } => 7,
+            Cadence::EveryDays { .. } => 1,
+        };
+        local_time(timezone, first_day + Duration::days(step), self.at())
+    }
+
+    /// The start after `start`, at the same local time even across daylight saving changes
+    fn next_start(
+        &self,
+        start: DateTime<Utc>,
+        timezone: Tz,
+    ) -> Result<DateTime<Utc>, anyhow::Error> {
+        let day = start.with_timezone(&timezone).date_naive();
+        local_time(
+            timezone,
+            day + Duration::days(self.days() as i64),
+            self.at(),
+        )
+    }
+}
+
+/// A local time as UTC. Times skipped when clocks change move on an hour at a time,
+/// so a day a timezone skipped entirely starts on the next day
+fn local_time(timezone: Tz, day: NaiveDate, at: NaiveTime) -> Result<DateTime<Utc>, anyhow::Error> {
+    let local = day.and_time(at);
+    (0..=MAX_GAP_HOURS)
+        .find_map(|hours| {
+            timezone
+                .from_local_datetime(&(local + Duration::hours(hours)))
+                .earliest()
+        })
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| anyhow!("{} does not exist in {}", local, timezone))
+}
+
+/// The longest gap skipped when clocks change, Samoa skipped a whole day in 2011
+const MAX_GAP_HOURS: i64 = 48;
+
+/// What a [CompetitionScheduler] creates
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotationPlan {
+    pub rotation: Rotation,
+    pub cadence: Cadence,
+    /// The timezone the cadence and the dates in titles are in
+    pub timezone: Tz,
+    /// `{metric}`, `{number}`, `{start}` and `{end}` are filled in,
+    /// e.g. `{metric} of the Week #{number}`
+    pub title_template: String,
+}
+
+impl RotationPlan {
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        let has_metric = match &self.rotation {
+            Rotation::Fixed(metrics) => !metrics.is_empty(),
+            Rotation::Weighted { choices, .. } => choices.iter().any(|(_, weight)| *weight > 0),
+        };
+        if !has_metric {
+            return Err(anyhow!("The rotation has no metric to pick"));
+        }
+        if self.cadence.days() == 0 {
+            return Err(anyhow!("Competitions must be at least a day apart"));
+        }
+        Ok(())
+    }
+
+    fn title(
+        &self,
+        metric: &Metric,
+        number: u32,
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+    ) -> String {
+        let date = |time: DateTime<Utc>| {
+            time.with_timezone(&self.timezone)
+                .format("%Y-%m-%d")
+                .to_string()
+        };
+        self.title_template
+            .replace("{metric}", &metric.to_string().to_case(Case::Title))
+            .replace("{number}", &number.to_string())
+            .replace("{start}", &date(starts_at))
+            .replace("{end}", &date(ends_at))
+    }
+}
+
+/// How far a rotation has got, saved between runs so it carries on where it left off
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RotationState {
+    /// Seeds the weighted picks, so a dry run shows the same metrics that are then created
+    pub seed: u64,
+    /// How many competitions have been created
+    pub created: u32,
+    /// The metric of each competition created, oldest first
+    pub history: Vec<String>,
+    /// When the last competition created ends, the next one starts then
+    pub last_ends_at: Option<DateTime<Utc>>,
+}
+
+impl Default for RotationState {
+    fn default() -> Self {
+        Self {
+            seed: rand::random(),
+            created: 0,
+            history: vec![],
+            last_ends_at: None,
+        }
+    }
+}
+
+impl RotationState {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// One competition of the rotation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledCompetition {
+    /// Counts from 1 over the life of the rotation
+    pub number: u32,
+    pub definition: CreateCompetition,
+    /// What the api returned, `None` in a dry run
+    pub created: Option<CompetitionCreateResponse>,
+}
+
+/// Creates a group's recurring competitions, such as Skill of the Week, from a [RotationPlan]
+pub struct CompetitionScheduler {
+    plan: RotationPlan,
+    state: RotationState,
+    state_path: Option<PathBuf>,
+    dry_run: bool,
+}
+
+impl CompetitionScheduler {
+    pub fn new(plan: RotationPlan) -> Result<Self, anyhow::Error> {
+        plan.validate()?;
+        Ok(Self {
+            plan,
+            state: RotationState::default(),
+            state_path: None,
+            dry_run: false,
+        })
+    }
+
+    /// Loads the state from `path` when the file exists, and saves it there after each
+    /// competition is created
+    pub fn state_file(mut self, path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        if path.exists() {
+            self.state = RotationState::load(path)?;
+        }
+        self.state_path = Some(path.to_path_buf());
+        Ok(self)
+    }
+
+    /// Carries on from a state saved elsewhere
+    pub fn state(mut self, state: RotationState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Works out the competitions without creating them or moving the rotation on
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn rotation_state(&self) -> &RotationState {
+        &self.state
+    }
+
+    /// The next `count` competitions. The first starts when the last one created ends,
+    /// or at the first slot of the cadence after `now`
+    pub fn upcoming(
+        &self,
+        count: usize,
+        now: DateTime<Utc>,
+        group_id: GroupId,
+        verification_code: &str,
+    ) -> Result<Vec<ScheduledCompetition>, anyhow::Error> {
+        let mut state = self.state.clone();
+        (0..count)
+            .map(|_| {
+                let scheduled = self.next(&state, now, group_id, verification_code)?;
+                advance(&mut state, &scheduled);
+                Ok(scheduled)
+            })
+            .collect()
+    }
+
+    /// Creates the next `count` competitions for a group, with the group's verification code.
+    /// The rotation moves on after each one, so a failure part way keeps those already created.
+    /// In a dry run nothing is sent and the competitions are only returned
+    pub async fn schedule<T: Transport>(
+        &mut self,
+        client: &CompetitionClient<T>,
+        group_id: impl Into<GroupId>,
+        verification_code: &str,
+        count: usize,
+    ) -> Result<Vec<ScheduledCompetition>, anyhow::Error> {
+        let group_id = group_id.into();
+        let now = Utc::now();
+        if self.dry_run {
+            return self.upcoming(count, now, group_id, verification_code);
+        }
+        let mut scheduled = vec![];
+        for _ in 0..count {
+            let mut next = self.next(&self.state, now, group_id, verification_code)?;
+            next.created = Some(client.create_competition(&next.definition).await?);
+            advance(&mut self.state, &next);
+            if let Some(path) = &self.state_path {
+                self.state.save(path)?;
+            }
+            scheduled.push(next);
+        }
+        Ok(scheduled)
+    }
+
+    fn next(
+        &self,
+        state: &RotationState,
+        now: DateTime<Utc>,
+        group_id: GroupId,
+        verification_code: &str,
+    ) -> Result<ScheduledCompetition, anyhow::Error> {
+        let number = state.created + 1;
+        let timezone = self.plan.timezone;
+        let starts_at = match state.last_ends_at {
+            Some(last_ends_at) if last_ends_at >= now => last_ends_at,
+            _ => self.plan.cadence.first_start(now, timezone)?,
+        };
+        let ends_at = self.plan.cadence.next_start(starts_at, timezone)?;
+        let metric = self.pick(state, number);
+        Ok(ScheduledCompetition {
+            number,
+            definition: CreateCompetition {
+                title: self.plan.title(&metric, number, starts_at, ends_at),
+                metric,
+                starts_at,
+                ends_at,
+                group_id: Some(group_id),
+                group_verification_code: Some(verification_code.to_string()),
+                participants: vec![],
+            },
+            created: None,
+        })
+    }
+
+    fn pick(&self, state: &RotationState, number: u32) -> Metric {
+        match &self.plan.rotation {
+            Rotation::Fixed(metrics) => metrics[state.created as usize % metrics.len()].clone(),
+            Rotation::Weighted {
+                choices,
+                avoid_recent,
+            } => {
+                let recent = &state.history[state.history.len().saturating_sub(*avoid_recent)..];
+                let mut candidates: Vec<&(Metric, u32)> = choices
+                    .iter()
+                    .filter(|(metric, weight)| *weight > 0 && !recent.contains(&metric.to_string()))
+                    .collect();
+                if candidates.is_empty() {
+                    candidates = choices.iter().filter(|(_, weight)| *weight > 0).collect();
+                }
+                let weights = WeightedIndex::new(candidates.iter().map(|(_, weight)| *weight))
+                    .expect("validated to have a weighted metric");
+                let mut rng = StdRng::seed_from_u64(state.seed.wrapping_add(number as u64));
+                candidates[weights.sample(&mut rng)].0.clone()
+            }
+        }
+    }
+}
+
+fn advance(state: &mut RotationState, scheduled: &ScheduledCompetition) {
+    state.created = scheduled.number;
+    state.history.push(scheduled.definition.metric.to_string());
+    state.last_ends_at = Some(scheduled.definition.ends_at);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::global_enums::{Boss, Metric, Skill};
+    use crate::models::global_types::GroupId;
+    use crate::schedule::{Cadence, CompetitionScheduler, Rotation, RotationPlan, RotationState};
+    use crate::transport::{Request, Response};
+    use crate::WomClient;
+    use chrono::{NaiveTime, TimeZone, Utc, Weekday};
+    use chrono_tz::Europe::London;
+    use chrono_tz::Pacific::Apia;
+    use reqwest::StatusCode;
+    use std::sync::{Arc, Mutex};
+
+    fn sotw_plan() -> RotationPlan {
+        RotationPlan {
+            rotation: Rotation::Fixed(vec![
+                Metric::Skill(Skill::Agility),
+                Metric::Skill(Skill::Fishing),
+                Metric::Skill(Skill::Mining),
+            ]),
+            cadence: Cadence::Weekly {
+                weekday: Weekday::Sun,
+                at: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            },
+            timezone: London,
+            title_template: "{metric} of the Week #{number} ({start})".to_string(),
+        }
+    }
+
+    #[test]
+    fn fixed_rotation_follows_local_time_test() {
+        let scheduler = CompetitionScheduler::new(sotw_plan()).unwrap();
+        // A Wednesday, the clocks go forward on Sunday the 31st
+        let now = Utc.with_ymd_and_hms(2024, 3, 20, 12, 0, 0).unwrap();
+
+        let upcoming = scheduler
+            .upcoming(4, now, GroupId(139), "111-222-333")
+            .unwrap();
+
+        let titles: Vec<_> = upcoming
+            .iter()
+            .map(|scheduled| scheduled.definition.title.as_str())
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                "Agility of the Week #1 (2024-03-24)",
+                "Fishing of the Week #2 (2024-03-31)",
+                "Mining of the Week #3 (2024-04-07)",
+                "Agility of the Week #4 (2024-04-14)",
+            ]
+        );
+        let first = &upcoming[0].definition;
+        assert_eq!(
+            first.starts_at,
+            Utc.with_ymd_and_hms(2024, 3, 24, 18, 0, 0).unwrap()
+        );
+        // 18:00 in summer time is 17:00 UTC
+        assert_eq!(
+            first.ends_at,
+            Utc.with_ymd_and_hms(2024, 3, 31, 17, 0, 0).unwrap()
+        );
+        assert_eq!(upcoming[1].definition.starts_at, first.ends_at);
+        assert_eq!(first.group_id, Some(GroupId(139)));
+        assert_eq!(scheduler.rotation_state().created, 0);
+    }
+
+    #[test]
+    fn skipped_day_moves_to_the_next_test() {
+        let plan = RotationPlan {
+            cadence: Cadence::EveryDays {
+                days: 1,
+                at: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+            },
+            timezone: Apia,
+            ..sotw_plan()
+        };
+        let scheduler = CompetitionScheduler::new(plan).unwrap();
+        // Samoa skipped 2011-12-30 entirely
+        let now = Apia
+            .with_ymd_and_hms(2011, 12, 28, 14, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let upcoming = scheduler.upcoming(2, now, GroupId(1), "code").unwrap();
+
+        let starts = Apia.with_ymd_and_hms(2011, 12, 29, 12, 0, 0).unwrap();
+        let ends = Apia.with_ymd_and_hms(2011, 12, 31, 0, 0, 0).unwrap();
+        assert_eq!(upcoming[0].definition.starts_at, starts);
+        assert_eq!(upcoming[0].definition.ends_at, ends);
+        assert_eq!(upcoming[1].definition.starts_at, ends);
+    }
+
+    #[test]
+    fn weighted_rotation_avoids_recent_test() {
+        let plan = RotationPlan {
+            rotation: Rotation::Weighted {
+                choices: vec![
+                    (Metric::Boss(Boss::Zulrah), 5),
+                    (Metric::Boss(Boss::Vorkath), 1),
+                    (Metric::Boss(Boss::TheGauntlet), 1),
+                ],
+                avoid_recent: 2,
+            },
+            title_template: "{metric} of the Week".to_string(),
+            ..sotw_plan()
+        };
+        let scheduler = CompetitionScheduler::new(plan).unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 3, 20, 12, 0, 0).unwrap();
+
+        let upcoming = scheduler.upcoming(12, now, GroupId(1), "code").unwrap();
+
+        for window in upcoming.windows(3) {
+            let metrics: Vec<_> = window.iter().map(|s| &s.definition.metric).collect();
+            assert!(metrics[2] != metrics[0] && metrics[2] != metrics[1]);
+        }
+        // The same seed picks the same metrics
+        assert_eq!(
+            scheduler.upcoming(12, now, GroupId(1), "code").unwrap(),
+            upcoming
+        );
+
+        let empty = RotationPlan {
+            rotation: Rotation::Weighted {
+                choices: vec![(Metric::Boss(Boss::Zulrah), 0)],
+                avoid_recent: 0,
+            },
+            ..sotw_plan()
+        };
+        assert!(CompetitionScheduler::new(empty).is_err());
+    }
+
+    #[tokio::test]
+    async fn schedule_creates_and_saves_state_test() {
+        let path = std::env::temp_dir().join(format!(
+            "wom_rs_rotation_state_test_{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let bodies = Arc::new(Mutex::new(vec![]));
+        let sent = bodies.clone();
+        let transport = move |request: Request| {
+            sent.lock().unwrap().push(request.body.clone().unwrap());
+            async move {
+                Ok(Response {
+                    status: StatusCode::CREATED,
+                    headers: vec![],
+                    body: std::fs::read(
+                        "./tests/mocks/competition/competition_create_response.json",
+                    )?,
+                })
+            }
+        };
+        let wom_client = WomClient::new_with_transport(transport, "http://fake".to_string());
+        let next_week = Utc::now() + chrono::Duration::days(7);
+        let state = RotationState {
+            seed: 1,
+            created: 10,
+            history: vec![],
+            last_ends_at: Some(next_week),
+        };
+
+        let mut dry_run = CompetitionScheduler::new(sotw_plan())
+            .unwrap()
+            .state(state.clone())
+            .dry_run(true);
+        let planned = dry_run
+            .schedule(&wom_client.competition_client, 139, "111-222-333", 2)
+            .await
+            .unwrap();
+        assert!(bodies.lock().unwrap().is_empty());
+        assert!(planned.iter().all(|scheduled| scheduled.created.is_none()));
+
+        let mut scheduler = CompetitionScheduler::new(sotw_plan())
+            .unwrap()
+            .state(state)
+            .state_file(&path)
+            .unwrap();
+        let created = scheduler
+            .schedule(&wom_client.competition_client, 139, "111-222-333", 2)
+            .await
+            .unwrap();
+
+        assert_eq!(bodies.lock().unwrap().len(), 2);
+        assert!(bodies.lock().unwrap()[0].contains(r#""groupVerificationCode":"111-222-333""#));
+        assert_eq!(created[0].definition, planned[0].definition);
+        assert_eq!(created[0].number, 11);
+        assert_eq!(created[0].definition.starts_at, next_week);
+        assert!(created.iter().all(|scheduled| scheduled.created.is_some()));
+
+        let saved = RotationState::load(&path).unwrap();
+        assert_eq!(saved.created, 12);
+        assert_eq!(saved.history, ["fishing", "mining"]);
+        assert_eq!(saved.last_ends_at, Some(created[1].definition.ends_at));
+
+        // A new run carries on from the saved state
+        let resumed = CompetitionScheduler::new(sotw_plan())
+            .unwrap()
+            .state_file(&path)
+            .unwrap();
+        let next = resumed
+            .upcoming(1, Utc::now(), GroupId(139), "111-222-333")
+            .unwrap();
+        assert_eq!(next[0].number, 13);
+        assert_eq!(next[0].definition.metric, Metric::Skill(Skill::Agility));
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,374 @@
+use crate::models::competition::{
+    CompetitionLevelsProgress, CompetitionProgress, PlayerCompetitionStanding,
+};
+use crate::models::global_enums::{Activity, Boss, Metric};
+use crate::models::global_types::PlayerId;
+use crate::models::player::SnapShot;
+use crate::query::DateRange;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// The value the api returns for a metric the player is not ranked in
+pub const UNRANKED: i64 = -1;
+
+/// The lowest value the hiscores rank a metric at, anything below it comes back unranked
+pub fn minimum_value(metric: &Metric) -> i64 {
+    match metric {
+        Metric::Skill(_) | Metric::ComputedMetric(_) => 0,
+        Metric::Boss(Boss::TzkalZuk | Boss::Mimic) => 1,
+        Metric::Boss(_) => 5,
+        Metric::Activity(activity) => match activity {
+            Activity::LeaguePoints => 100,
+            Activity::BountyHunterHunter | Activity::BountyHunterRogue => 2,
+            Activity::LastManStanding => 500,
+            Activity::PvpArena => 2525,
+            Activity::SoulWarsZeal => 200,
+            Activity::GuardiansOfTheRift => 2,
+            _ => 1,
+        },
+    }
+}
+
+/// Works out what was gained between two values, either of which may be unranked.
+/// An unranked start that ends ranked counts from just below the hiscores minimum,
+/// as the player can have had at most that much before
+pub fn progress(metric: &Metric, start: i64, end: i64) -> CompetitionProgress {
+    let gained = if end == UNRANKED {
+        0
+    } else if start == UNRANKED {
+        end - (minimum_value(metric) - 1).max(0)
+    } else {
+        end - start
+    };
+    CompetitionProgress {
+        start,
+        end,
+        gained: gained.max(0),
+    }
+}
+
+/// One player taking part in a competition
+#[derive(Debug, Clone, PartialEq)]
+pub struct Participant {
+    pub player_id: PlayerId,
+    /// Only set for team competitions
+    pub team_name: Option<String>,
+    /// Any of the player's snapshots, those outside the competition are ignored
+    pub snapshots: Vec<SnapShot>,
+}
+
+/// A player's result, the same numbers as a [PlayerCompetitionStanding]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub player_id: PlayerId,
+    pub team_name: Option<String>,
+    pub progress: CompetitionProgress,
+    /// Only set for skill metrics
+    pub levels: Option<CompetitionLevelsProgress>,
+    pub rank: i64,
+}
+
+impl Standing {
+    /// Whether the api's numbers for this player agree with ours
+    pub fn agrees_with(&self, standing: &PlayerCompetitionStanding) -> bool {
+        self.player_id == standing.player_id
+            && self.progress == standing.progress
+            && self.rank == standing.rank
+    }
+}
+
+/// A team's result in a team competition
+#[derive(Debug, Clone, PartialEq)]
+pub struct TeamStanding {
+    pub name: String,
+    pub participants: usize,
+    pub total_gained: i64,
+    pub average_gained: f64,
+    /// The player who gained the most for the team
+    pub mvp: PlayerId,
+    pub rank: i64,
+}
+
+/// Computes competition standings from snapshots, without the api knowing about the competition
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompetitionEngine {
+    metric: Metric,
+    range: DateRange,
+}
+
+impl CompetitionEngine {
+    pub fn new(metric: Metric, range: DateRange) -> Self {
+        Self { metric, range }
+    }
+
+    /// Each participant's progress from their first snapshot in the competition to their last,
+    /// ranked by what they gained. Tied players share a rank, players without snapshots
+    /// in the competition gain nothing
+    pub fn standings(&self, participants: &[Participant]) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = participants
+            .iter()
+            .map(|participant| self.standing(participant))
+            .collect();
+        standings.sort_by(|a, b| {
+            b.progress
+                .gained
+                .cmp(&a.progress.gained)
+                .then(a.player_id.cmp(&b.player_id))
+        });
+        let gains: Vec<i64> = standings.iter().map(|s| s.progress.gained).collect();
+        for (standing, rank) in standings.iter_mut().zip(ranks(&gains)) {
+            standing.rank = rank;
+        }
+        standings
+    }
+
+    /// Totals, averages and MVPs for each team, ranked by total gained
+    pub fn team_standings(&self, standings: &[Standing]) -> Vec<TeamStanding> {
+        let mut teams: BTreeMap<&str, Vec<&Standing>> = BTreeMap::new();
+        for standing in standings {
+            if let Some(team_name) = &standing.team_name {
+                teams.entry(team_name).or_default().push(standing);
+            }
+        }
+
+        let mut team_standings: Vec<TeamStanding> = teams
+            .into_iter()
+            .map(|(name, members)| {
+                let total_gained: i64 = members.iter().map(|m| m.progress.gained).sum();
+                let mvp = members
+                    .iter()
+                    .max_by(|a, b| {
+                        a.progress
+                            .gained
+                            .cmp(&b.progress.gained)
+                            .then(b.player_id.cmp(&a.player_id))
+                    })
+                    .map(|m| m.player_id)
+                    .unwrap_or_default();
+                TeamStanding {
+                    name: name.to_string(),
+                    participants: members.len(),
+                    total_gained,
+                    average_gained: total_gained as f64 / members.len() as f64,
+                    mvp,
+                    rank: 0,
+                }
+            })
+            .collect();
+        team_standings.sort_by(|a, b| {
+            b.total_gained
+                .cmp(&a.total_gained)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        let totals: Vec<i64> = team_standings.iter().map(|t| t.total_gained).collect();
+        for (team, rank) in team_standings.iter_mut().zip(ranks(&totals)) {
+            team.rank = rank;
+        }
+        team_standings
+    }
+
+    fn standing(&self, participant: &Participant) -> Standing {
+        let mut in_range: Vec<&SnapShot> = participant
+            .snapshots
+            .iter()
+            .filter(|snapshot| {
+                snapshot.created_at >= self.range.start() && snapshot.created_at <= self.range.end()
+            })
+            .collect();
+        in_range.sort_by_key(|snapshot| snapshot.created_at);
+
+        let (progress, levels) = match (in_range.first(), in_range.last()) {
+            (Some(first), Some(last)) => (
+                progress(&self.metric, self.value(first), self.value(last)),
+                self.levels(first, last),
+            ),
+            _ => (progress(&self.metric, UNRANKED, UNRANKED), None),
+        };
+        Standing {
+            player_id: participant.player_id,
+            team_name: participant.team_name.clone(),
+            progress,
+            levels,
+            rank: 0,
+        }
+    }
+
+    /// Computed metrics are fractional, they are rounded down like the api's competition progress
+    fn value(&self, snapshot: &SnapShot) -> i64 {
+        snapshot
+            .data
+            .value(&self.metric)
+            .map(|value| value.floor() as i64)
+            .unwrap_or(UNRANKED)
+    }
+
+    fn levels(&self, first: &SnapShot, last: &SnapShot) -> Option<CompetitionLevelsProgress> {
+        let Metric::Skill(skill) = &self.metric else {
+            return None;
+        };
+        let start = first.data.skills.get(skill)?.level;
+        let end = last.data.skills.get(skill)?.level;
+        Some(CompetitionLevelsProgress {
+            start,
+            end,
+            gained: (end - start).max(0),
+        })
+    }
+}
+
+/// Standard competition ranking of values sorted from best to worst, ties share a rank
+fn ranks(sorted: &[i64]) -> Vec<i64> {
+    let mut ranks = Vec::with_capacity(sorted.len());
+    for (index, value) in sorted.iter().enumerate() {
+        match index
+            .checked_sub(1)
+            .map(|previous| sorted[previous].cmp(value))
+        {
+            Some(Ordering::Equal) => ranks.push(ranks[index - 1]),
+            _ => ranks.push(index as i64 + 1),
+        }
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::fixture;
+    use crate::models::global_enums::{Activity, Boss, Metric, Skill};
+    use crate::models::global_types::PlayerId;
+    use crate::models::player::SnapShot;
+    use crate::query::DateRange;
+    use crate::standings::{progress, CompetitionEngine, Participant};
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn day(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap()
+    }
+
+    fn base_snapshot() -> SnapShot {
+        fixture::<Vec<SnapShot>>("player/player_snapshots.json").remove(0)
+    }
+
+    fn snapshot(created_at: DateTime<Utc>, attack_experience: i64, zulrah_kills: i64) -> SnapShot {
+        let mut snapshot = base_snapshot();
+        snapshot.created_at = created_at;
+        snapshot.data.skills.attack.experience = attack_experience;
+        snapshot.data.bosses.zulrah.kills = zulrah_kills;
+        snapshot
+    }
+
+    fn participant(player_id: i64, team: &str, snapshots: Vec<SnapShot>) -> Participant {
+        Participant {
+            player_id: PlayerId(player_id),
+            team_name: Some(team.to_string()),
+            snapshots,
+        }
+    }
+
+    fn engine(metric: Metric) -> CompetitionEngine {
+        CompetitionEngine::new(metric, DateRange::new(day(2), day(9)).unwrap())
+    }
+
+    #[test]
+    fn unranked_progress_test() {
+        let zulrah = Metric::Boss(Boss::Zulrah);
+        assert_eq!(progress(&zulrah, 10, 15).gained, 5);
+        // Unranked at the start means fewer than 5 kills, so at most 4
+        assert_eq!(progress(&zulrah, -1, 12).gained, 8);
+        assert_eq!(progress(&zulrah, -1, -1).gained, 0);
+        assert_eq!(progress(&zulrah, 10, -1).gained, 0);
+        // The Mimic is ranked from a single kill, so any kills count
+        let mimic = Metric::Boss(Boss::Mimic);
+        assert_eq!(progress(&mimic, -1, 3).gained, 3);
+
+        let lms = Metric::Activity(Activity::LastManStanding);
+        assert_eq!(progress(&lms, -1, 520).gained, 21);
+        let attack = Metric::Skill(Skill::Attack);
+        assert_eq!(progress(&attack, -1, 1000).gained, 1000);
+    }
+
+    #[test]
+    fn standings_only_use_snapshots_within_the_competition_test() {
+        let participants = vec![
+            participant(
+                1,
+                "red",
+                vec![
+                    snapshot(day(1), 0, 0),
+                    snapshot(day(3), 1000, 0),
+                    snapshot(day(8), 5000, 0),
+                    snapshot(day(10), 90000, 0),
+                ],
+            ),
+            participant(
+                2,
+                "red",
+                vec![snapshot(day(2), 0, 0), snapshot(day(9), 8000, 0)],
+            ),
+            participant(
+                3,
+                "blue",
+                vec![snapshot(day(4), 10, 0), snapshot(day(5), 4010, 0)],
+            ),
+            participant(4, "blue", vec![snapshot(day(1), 0, 0)]),
+        ];
+
+        let standings = engine(Metric::Skill(Skill::Attack)).standings(&participants);
+
+        let summary: Vec<_> = standings
+            .iter()
+            .map(|standing| {
+                (
+                    standing.player_id.0,
+                    standing.progress.gained,
+                    standing.rank,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(2, 8000, 1), (1, 4000, 2), (3, 4000, 2), (4, 0, 4)]
+        );
+        assert_eq!(standings[1].progress.start, 1000);
+        assert_eq!(standings[1].progress.end, 5000);
+        assert!(standings[0].levels.is_some());
+        assert_eq!(standings[3].progress.start, -1);
+    }
+
+    #[test]
+    fn team_standings_test() {
+        let participants = vec![
+            participant(
+                1,
+                "red",
+                vec![snapshot(day(2), 0, -1), snapshot(day(3), 0, 20)],
+            ),
+            participant(
+                2,
+                "red",
+                vec![snapshot(day(2), 0, 10), snapshot(day(3), 0, 12)],
+            ),
+            participant(
+                3,
+                "blue",
+                vec![snapshot(day(2), 0, 50), snapshot(day(3), 0, 60)],
+            ),
+        ];
+        let engine = engine(Metric::Boss(Boss::Zulrah));
+
+        let standings = engine.standings(&participants);
+        let teams = engine.team_standings(&standings);
+
+        assert_eq!(teams.len(), 2);
+        assert_eq!(teams[0].name, "red");
+        assert_eq!(teams[0].total_gained, 18);
+        assert_eq!(teams[0].average_gained, 9.0);
+        assert_eq!(teams[0].mvp, 1);
+        assert_eq!(teams[0].rank, 1);
+        assert_eq!(teams[1].name, "blue");
+        assert_eq!(teams[1].total_gained, 10);
+        assert_eq!(teams[1].mvp, 3);
+        assert_eq!(teams[1].rank, 2);
+        assert!(standings.iter().all(|standing| standing.levels.is_none()));
+    }
+}
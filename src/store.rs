@@ -0,0 +1,911 @@
+use crate::clients::player_client::PlayerClient;
+use crate::models::global_enums::Metric;
+use crate::models::global_types::{GroupId, IntoUsername, PlayerId, Username};
+use crate::models::group::GroupMemberShip;
+use crate::models::name::NameChange;
+use crate::models::player::{Player, SnapShot};
+use crate::models::record::Record;
+use crate::query::{to_iso_8601, DateRange};
+use crate::transport::Transport;
+use anyhow::anyhow;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+/// Each entry moves the schema up one version, never edit one that has shipped
+const MIGRATIONS: &[&str] = &[
+    r#"
+CREATE TABLE players (
+    id INTEGER PRIMARY KEY,
+    username TEXT NOT NULL,
+    display_name TEXT NOT NULL,
+    type TEXT NOT NULL,
+    build TEXT NOT NULL,
+    country TEXT,
+    status TEXT NOT NULL,
+    patron INTEGER NOT NULL,
+    exp INTEGER NOT NULL,
+    ehp REAL NOT NULL,
+    ehb REAL NOT NULL,
+    ttm REAL NOT NULL,
+    tt200m REAL NOT NULL,
+    registered_at TEXT NOT NULL,
+    updated_at TEXT,
+    last_changed_at TEXT,
+    last_imported_at TEXT
+);
+CREATE INDEX players_username ON players (username);
+
+CREATE TABLE snapshots (
+    id INTEGER PRIMARY KEY,
+    player_id INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    imported_at TEXT,
+    data TEXT NOT NULL
+);
+CREATE INDEX snapshots_player_created_at ON snapshots (player_id, created_at);
+
+CREATE TABLE group_memberships (
+    group_id INTEGER NOT NULL,
+    player_id INTEGER NOT NULL,
+    role TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (group_id, player_id)
+);
+
+CREATE TABLE name_changes (
+    id INTEGER PRIMARY KEY,
+    player_id INTEGER NOT NULL,
+    old_name TEXT NOT NULL,
+    new_name TEXT NOT NULL,
+    status TEXT NOT NULL,
+    review_context TEXT,
+    resolved_at TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+CREATE INDEX name_changes_player ON name_changes (player_id);
+
+CREATE TABLE records (
+    id INTEGER PRIMARY KEY,
+    player_id INTEGER NOT NULL,
+    period TEXT NOT NULL,
+    metric TEXT NOT NULL,
+    value REAL NOT NULL,
+    updated_at TEXT NOT NULL
+);
+CREATE INDEX records_player ON records (player_id);
+"#,
+    r#"
+ALTER TABLE players ADD COLUMN username_key TEXT NOT NULL DEFAULT '';
+UPDATE players SET username_key = trim(lower(replace(replace(username, '_', ' '), '-', ' ')));
+CREATE INDEX players_username_key ON players (username_key);
+"#,
+];
+
+const PLAYER_COLUMNS: &str = "players.id, players.username, players.display_name, players.type, \
+    players.build, players.country, players.status, players.patron, players.exp, players.ehp, \
+    players.ehb, players.ttm, players.tt200m, players.registered_at, players.updated_at, \
+    players.last_changed_at, players.last_imported_at";
+
+/// What a [Store::sync_snapshots] run did
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SyncReport {
+    /// Players whose snapshots were brought up to date
+    pub players: usize,
+    /// Snapshots fetched from the api, including ones that were already stored
+    pub snapshots: usize,
+    /// Players that could not be synced and why
+    pub failed: Vec<(Username, String)>,
+}
+
+/// Keeps players, snapshots, memberships, name changes and records in a SQLite database
+pub struct Store {
+    connection: Mutex<Connection>,
+}
+
+impl Store {
+    /// Opens or creates the database at `path` and brings its schema up to date
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// A database that only lives as long as the store, handy for tests
+    pub fn open_in_memory() -> Result<Self, anyhow::Error> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(mut connection: Connection) -> Result<Self, anyhow::Error> {
+        migrate(&mut connection)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection.lock().unwrap()
+    }
+
+    /// The version of the schema the database is on
+    pub fn schema_version(&self) -> Result<usize, anyhow::Error> {
+        schema_version(&self.connection())
+    }
+
+    pub fn upsert_player(&self, player: &Player) -> Result<(), anyhow::Error> {
+        upsert_player(&self.connection(), player)
+    }
+
+    pub fn get_player(
+        &self,
+        player_id: impl Into<PlayerId>,
+    ) -> Result<Option<Player>, anyhow::Error> {
+        let player_id = player_id.into();
+        let connection = self.connection();
+        let mut statement = connection.prepare(&format!(
+            "SELECT {} FROM players WHERE id = ?1",
+            PLAYER_COLUMNS
+        ))?;
+        Ok(statement
+            .query_row(params![player_id], |row| player_from_row(row, 0))
+            .optional()?)
+    }
+
+    /// Finds a player by any spelling of their name Wise Old Man treats as the same
+    pub fn find_player(
+        &self,
+        username: impl IntoUsername,
+    ) -> Result<Option<Player>, anyhow::Error> {
+        let username = username.into_username()?;
+        let connection = self.connection();
+        let mut statement = connection.prepare(&format!(
+            "SELECT {} FROM players WHERE username_key = ?1",
+            PLAYER_COLUMNS
+        ))?;
+        Ok(statement
+            .query_row(params![username.standardize()], |row| {
+                player_from_row(row, 0)
+            })
+            .optional()?)
+    }
+
+    pub fn upsert_snapshots(&self, snapshots: &[SnapShot]) -> Result<(), anyhow::Error> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction()?;
+        for snapshot in snapshots {
+            transaction.execute(
+                "INSERT INTO snapshots (id, player_id, created_at, imported_at, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (id) DO UPDATE SET
+                    player_id = excluded.player_id,
+                    created_at = excluded.created_at,
+                    imported_at = excluded.imported_at,
+                    data = excluded.data",
+                params![
+                    snapshot.id,
+                    snapshot.player_id,
+                    to_iso_8601(&snapshot.created_at),
+                    snapshot.imported_at.as_ref().map(to_iso_8601),
+                    serde_json::to_string(&snapshot.data)?,
+                ],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// A player's snapshots taken within the range, oldest first
+    pub fn snapshots(
+        &self,
+        player_id: impl Into<PlayerId>,
+        range: &DateRange,
+    ) -> Result<Vec<SnapShot>, anyhow::Error> {
+        let player_id = player_id.into();
+        let connection = self.connection();
+        let mut statement = connection.prepare(
+            "SELECT id, player_id, created_at, imported_at, data FROM snapshots
+             WHERE player_id = ?1 AND created_at >= ?2 AND created_at <= ?3
+             ORDER BY created_at",
+        )?;
+        let snapshots = statement
+            .query_map(
+                params![
+                    player_id,
+                    to_iso_8601(&range.start()),
+                    to_iso_8601(&range.end())
+                ],
+                |row| {
+                    Ok(SnapShot {
+                        id: row.get(0)?,
+                        player_id: row.get(1)?,
+                        created_at: date_column(row, 2)?,
+                        imported_at: optional_date_column(row, 3)?,
+                        data: json_column(row, 4)?,
+                    })
+                },
+            )?
+            .collect::<Result<_, _>>()?;
+        Ok(snapshots)
+    }
+
+    /// When the newest stored snapshot of a player was taken
+    pub fn latest_snapshot_at(
+        &self,
+        player_id: impl Into<PlayerId>,
+    ) -> Result<Option<DateTime<Utc>>, anyhow::Error> {
+        let player_id = player_id.into();
+        let latest: Option<String> = self.connection().query_row(
+            "SELECT MAX(created_at) FROM snapshots WHERE player_id = ?1",
+            params![player_id],
+            |row| row.get(0),
+        )?;
+        latest.map(|latest| parse_date(&latest)).transpose()
+    }
+
+    /// Stores the memberships along with their players
+    pub fn upsert_memberships(&self, memberships: &[GroupMemberShip]) -> Result<(), anyhow::Error> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction()?;
+        for membership in memberships {
+            upsert_player(&transaction, &membership.player)?;
+            transaction.execute(
+                "INSERT INTO group_memberships (group_id, player_id, role, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (group_id, player_id) DO UPDATE SET
+                    role = excluded.role,
+                    created_at = excluded.created_at,
+                    updated_at = excluded.updated_at",
+                params![
+                    membership.group_id,
+                    membership.player_id,
+                    membership.role.as_ref().map(to_text).transpose()?,
+                    to_iso_8601(&membership.created_at),
+                    to_iso_8601(&membership.updated_at),
+                ],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// The stored members of a group, ordered by player id
+    pub fn memberships(
+        &self,
+        group_id: impl Into<GroupId>,
+    ) -> Result<Vec<GroupMemberShip>, anyhow::Error> {
+        let group_id = group_id.into();
+        let connection = self.connection();
+        let mut statement = connection.prepare(&format!(
+            "SELECT group_memberships.group_id, group_memberships.role,
+                    group_memberships.created_at, group_memberships.updated_at, {}
+             FROM group_memberships
+             JOIN players ON players.id = group_memberships.player_id
+             WHERE group_memberships.group_id = ?1
+             ORDER BY players.id",
+            PLAYER_COLUMNS
+        ))?;
+        let memberships = statement
+            .query_map(params![group_id], |row| {
+                let player = player_from_row(row, 4)?;
+                Ok(GroupMemberShip {
+                    player_id: player.id,
+                    group_id: row.get(0)?,
+                    role: optional_text_column(row, 1)?,
+                    created_at: date_column(row, 2)?,
+                    updated_at: date_column(row, 3)?,
+                    player,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(memberships)
+    }
+
+    /// Stores the name changes, without the player they may carry
+    pub fn upsert_name_changes(&self, name_changes: &[NameChange]) -> Result<(), anyhow::Error> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction()?;
+        for name_change in name_changes {
+            transaction.execute(
+                "INSERT INTO name_changes (id, player_id, old_name, new_name, status,
+                    review_context, resolved_at, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT (id) DO UPDATE SET
+                    player_id = excluded.player_id,
+                    old_name = excluded.old_name,
+                    new_name = excluded.new_name,
+                    status = excluded.status,
+                    review_context = excluded.review_context,
+                    resolved_at = excluded.resolved_at,
+                    created_at = excluded.created_at,
+                    updated_at = excluded.updated_at",
+                params![
+                    name_change.id,
+                    name_change.player_id,
+                    name_change.old_name,
+                    name_change.new_name,
+                    to_text(&name_change.status)?,
+                    name_change
+                        .review_context
+                        .as_ref()
+                        .map(serde_json::to_string)
+                        .transpose()?,
+                    name_change.resolved_at.as_ref().map(to_iso_8601),
+                    to_iso_8601(&name_change.created_at),
+                    to_iso_8601(&name_change.updated_at),
+                ],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// A player's name changes, oldest first
+    pub fn name_changes(
+        &self,
+        player_id: impl Into<PlayerId>,
+    ) -> Result<Vec<NameChange>, anyhow::Error> {
+        let player_id = player_id.into();
+        let connection = self.connection();
+        let mut statement = connection.prepare(
+            "SELECT id, player_id, old_name, new_name, status, review_context, resolved_at,
+                    created_at, updated_at
+             FROM name_changes WHERE player_id = ?1 ORDER BY created_at",
+        )?;
+        let name_changes = statement
+            .query_map(params![player_id], |row| {
+                Ok(NameChange {
+                    id: row.get(0)?,
+                    player_id: row.get(1)?,
+                    old_name: row.get(2)?,
+                    new_name: row.get(3)?,
+                    status: text_column(row, 4)?,
+                    review_context: row
+                        .get::<_, Option<String>>(5)?
+                        .map(|context| serde_json::from_str(&context))
+                        .transpose()
+                        .map_err(|err| conversion_error(5, err))?,
+                    resolved_at: optional_date_column(row, 6)?,
+                    created_at: date_column(row, 7)?,
+                    updated_at: date_column(row, 8)?,
+                    player: None,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(name_changes)
+    }
+
+    pub fn upsert_records(&self, records: &[Record]) -> Result<(), anyhow::Error> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction()?;
+        for record in records {
+            transaction.execute(
+                "INSERT INTO records (id, player_id, period, metric, value, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (id) DO UPDATE SET
+                    player_id = excluded.player_id,
+                    period = excluded.period,
+                    metric = excluded.metric,
+                    value = excluded.value,
+                    updated_at = excluded.updated_at",
+                params![
+                    record.id,
+                    record.player_id,
+                    record.period.as_str(),
+                    record.metric.to_string(),
+                    record.value,
+                    to_iso_8601(&record.updated_at),
+                ],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// A player's records, optionally only those for a metric
+    pub fn records(
+        &self,
+        player_id: impl Into<PlayerId>,
+        metric: Option<&Metric>,
+    ) -> Result<Vec<Record>, anyhow::Error> {
+        let player_id = player_id.into();
+        let connection = self.connection();
+        let mut statement = connection.prepare(
+            "SELECT id, player_id, period, metric, value, updated_at FROM records
+             WHERE player_id = ?1 AND (?2 IS NULL OR metric = ?2)
+             ORDER BY id",
+        )?;
+        let records = statement
+            .query_map(
+                params![player_id, metric.map(|metric| metric.to_string())],
+                |row| {
+                    Ok(Record {
+                        id: row.get(0)?,
+                        player_id: row.get(1)?,
+                        period: text_column(row, 2)?,
+                        metric: text_column(row, 3)?,
+                        value: row.get(4)?,
+                        updated_at: date_column(row, 5)?,
+                    })
+                },
+            )?
+            .collect::<Result<_, _>>()?;
+        Ok(records)
+    }
+
+    /// Pulls each player's snapshots taken since their newest stored one, or since they
+    /// registered if none are stored yet. The players are stored too.
+    ///
+    /// The api pages snapshots newest first, so the range is walked back from now until
+    /// a request brings nothing older
+    pub async fn sync_snapshots<T: Transport>(
+        &self,
+        client: &PlayerClient<T>,
+        players: &[Player],
+    ) -> SyncReport {
+        let mut report = SyncReport::default();
+        for player in players {
+            match self.sync_player_snapshots(client, player).await {
+                Ok(snapshots) => {
+                    report.players += 1;
+                    report.snapshots += snapshots;
+                }
+                Err(err) => report
+                    .failed
+                    .push((player.username.clone(), err.to_string())),
+            }
+        }
+        report
+    }
+
+    async fn sync_player_snapshots<T: Transport>(
+        &self,
+        client: &PlayerClient<T>,
+        player: &Player,
+    ) -> Result<usize, anyhow::Error> {
+        self.upsert_player(player)?;
+        let since = match self.latest_snapshot_at(player.id)? {
+            Some(latest) => latest + Duration::milliseconds(1),
+            None => player.registered_at,
+        };
+
+        // Pages come newest first. They are only stored once every page has arrived, as storing
+        // the newest alone would make the next run start after the pages that failed
+        let mut seen = HashSet::new();
+        let mut fetched = vec![];
+        let mut end = Utc::now();
+        while since < end {
+            let snapshots = client
+                .get_snapshots_by_date(player.username.clone(), DateRange::new(since, end)?)
+                .await?;
+            let oldest = snapshots.iter().map(|snapshot| snapshot.created_at).min();
+            fetched.extend(
+                snapshots
+                    .into_iter()
+                    .filter(|snapshot| seen.insert(snapshot.id)),
+            );
+
+            match oldest {
+                Some(oldest) if oldest < end => end = oldest,
+                _ => break,
+            }
+        }
+        self.upsert_snapshots(&fetched)?;
+        Ok(fetched.len())
+    }
+}
+
+fn migrate(connection: &mut Connection) -> Result<(), anyhow::Error> {
+    let current = schema_version(connection)?;
+    if current > MIGRATIONS.len() {
+        return Err(anyhow!(
+            "Database schema version {} is newer than this version of wom_rs supports ({})",
+            current,
+            MIGRATIONS.len()
+        ));
+    }
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", index + 1)?;
+        transaction.commit()?;
+    }
+    Ok(())
+}
+
+fn schema_version(connection: &Connection) -> Result<usize, anyhow::Error> {
+    let version: i64 = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    Ok(version as usize)
+}
+
+fn upsert_player(connection: &Connection, player: &Player) -> Result<(), anyhow::Error> {
+    connection.execute(
+        "INSERT INTO players (id, username, display_name, type, build, country, status, patron,
+            exp, ehp, ehb, ttm, tt200m, registered_at, updated_at, last_changed_at, last_imported_at,
+            username_key)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)
+         ON CONFLICT (id) DO UPDATE SET
+            username = excluded.username,
+            username_key = excluded.username_key,
+            display_name = excluded.display_name,
+            type = excluded.type,
+            build = excluded.build,
+            country = excluded.country,
+            status = excluded.status,
+            patron = excluded.patron,
+            exp = excluded.exp,
+            ehp = excluded.ehp,
+            ehb = excluded.ehb,
+            ttm = excluded.ttm,
+            tt200m = excluded.tt200m,
+            registered_at = excluded.registered_at,
+            updated_at = excluded.updated_at,
+            last_changed_at = excluded.last_changed_at,
+            last_imported_at = excluded.last_imported_at",
+        params![
+            player.id,
+            player.username,
+            player.display_name,
+            to_text(&player.player_type)?,
+            to_text(&player.build)?,
+            player.country.as_ref().map(to_text).transpose()?,
+            to_text(&player.status)?,
+            player.patron,
+            player.exp,
+            player.ehp,
+            player.ehb,
+            player.ttm,
+            player.tt200m,
+            to_iso_8601(&player.registered_at),
+            player.updated_at.as_ref().map(to_iso_8601),
+            player.last_changed_at.as_ref().map(to_iso_8601),
+            player.last_imported_at.as_ref().map(to_iso_8601),
+            player.username.standardize(),
+        ],
+    )?;
+    Ok(())
+}
+
+fn player_from_row(row: &Row, offset: usize) -> rusqlite::Result<Player> {
+    Ok(Player {
+        id: row.get(offset)?,
+        username: row.get(offset + 1)?,
+        display_name: row.get(offset + 2)?,
+        player_type: text_column(row, offset + 3)?,
+        build: text_column(row, offset + 4)?,
+        country: optional_text_column(row, offset + 5)?,
+        status: text_column(row, offset + 6)?,
+        patron: row.get(offset + 7)?,
+        exp: row.get(offset + 8)?,
+        ehp: row.get(offset + 9)?,
+        ehb: row.get(offset + 10)?,
+        ttm: row.get(offset + 11)?,
+        tt200m: row.get(offset + 12)?,
+        registered_at: date_column(row, offset + 13)?,
+        updated_at: optional_date_column(row, offset + 14)?,
+        last_changed_at: optional_date_column(row, offset + 15)?,
+        last_imported_at: optional_date_column(row, offset + 16)?,
+    })
+}
+
+/// Enums are stored as the same string the api uses for them
+fn to_text<T: Serialize>(value: &T) -> Result<String, anyhow::Error> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(text) => Ok(text),
+        other => Err(anyhow!("Expected a string value, got {}", other)),
+    }
+}
+
+fn parse_date(date: &str) -> Result<DateTime<Utc>, anyhow::Error> {
+    Ok(DateTime::parse_from_rfc3339(date)?.with_timezone(&Utc))
+}
+
+fn conversion_error(
+    index: usize,
+    err: impl std::error::Error + Send + Sync + 'static,
+) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(err))
+}
+
+fn text_column<T: DeserializeOwned>(row: &Row, index: usize) -> rusqlite::Result<T> {
+    let text: String = row.get(index)?;
+    serde_json::from_value(serde_json::Value::String(text))
+        .map_err(|err| conversion_error(index, err))
+}
+
+fn optional_text_column<T: DeserializeOwned>(
+    row: &Row,
+    index: usize,
+) -> rusqlite::Result<Option<T>> {
+    match row.get::<_, Option<String>>(index)? {
+        Some(_) => text_column(row, index).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn json_column<T: DeserializeOwned>(row: &Row, index: usize) -> rusqlite::Result<T> {
+    let json: String = row.get(index)?;
+    serde_json::from_str(&json).map_err(|err| conversion_error(index, err))
+}
+
+fn date_column(row: &Row, index: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let date: String = row.get(index)?;
+    DateTime::parse_from_rfc3339(&date)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|err| conversion_error(index, err))
+}
+
+fn optional_date_column(row: &Row, index: usize) -> rusqlite::Result<Option<DateTime<Utc>>> {
+    match row.get::<_, Option<String>>(index)? {
+        Some(_) => date_column(row, index).map(Some),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::{fake_client, fixture, fixture_bytes};
+    use crate::models::global_enums::{Metric, Skill};
+    use crate::models::global_types::Username;
+    use crate::models::group::GroupDetail;
+    use crate::models::name::NameChange;
+    use crate::models::player::SnapShot;
+    use crate::models::record::Record;
+    use crate::query::{to_iso_8601, DateRange};
+    use crate::store::{Store, MIGRATIONS};
+    use chrono::{Duration, TimeZone, Utc};
+    use reqwest::StatusCode;
+    use rusqlite::Connection;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    fn year(year: i32) -> chrono::DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn migrations_are_versioned_test() {
+        let path = std::env::temp_dir().join(format!("wom_rs_store_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let store = Store::open(&path).unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+        drop(store);
+
+        // Opening again must not run the migrations a second time
+        let store = Store::open(&path).unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn username_key_backfill_matches_standardize_test() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection.execute_batch(MIGRATIONS[0]).unwrap();
+        connection
+            .execute(
+                "INSERT INTO players (id, username, display_name, type, build, status, patron,
+                    exp, ehp, ehb, ttm, tt200m, registered_at)
+                 VALUES (1, 'Lynx_Titan_', 'Lynx Titan', 'regular', 'main', 'active', 0,
+                    0, 0, 0, 0, 0, '2020-01-01T00:00:00Z')",
+                [],
+            )
+            .unwrap();
+        connection.execute_batch(MIGRATIONS[1]).unwrap();
+
+        let key: String = connection
+            .query_row("SELECT username_key FROM players", [], |row| row.get(0))
+            .unwrap();
+        let username: Username = serde_json::from_str(r#""Lynx_Titan_""#).unwrap();
+        assert_eq!(key, username.standardize());
+    }
+
+    #[test]
+    fn memberships_and_players_round_trip_test() {
+        let store = Store::open_in_memory().unwrap();
+        let group: GroupDetail = fixture("group/group_detail.json");
+
+        store.upsert_memberships(&group.memberships).unwrap();
+        store.upsert_memberships(&group.memberships).unwrap();
+
+        assert_eq!(store.memberships(group.id).unwrap(), group.memberships);
+        let player = &group.memberships[0].player;
+        assert_eq!(store.get_player(player.id).unwrap().as_ref(), Some(player));
+        assert_eq!(
+            store.find_player(&player.display_name).unwrap().as_ref(),
+            Some(player)
+        );
+        assert_eq!(store.get_player(-1).unwrap(), None);
+    }
+
+    #[test]
+    fn player_upsert_replaces_by_id_test() {
+        let store = Store::open_in_memory().unwrap();
+        let group: GroupDetail = fixture("group/group_detail.json");
+        let mut player = group.memberships[0].player.clone();
+
+        store.upsert_player(&player).unwrap();
+        player.exp += 1000;
+        player.updated_at = Some(year(2024));
+        store.upsert_player(&player).unwrap();
+
+        assert_eq!(store.get_player(player.id).unwrap(), Some(player));
+    }
+
+    #[test]
+    fn find_player_by_any_spelling_test() {
+        let store = Store::open_in_memory().unwrap();
+        let group: GroupDetail = fixture("group/group_detail.json");
+        let mut player = group.memberships[0].player.clone();
+        player.username = Username::new("Lynx_Titan").unwrap();
+        store.upsert_player(&player).unwrap();
+
+        for spelling in ["lynx titan", "LYNX-TITAN", "Lynx_Titan"] {
+            assert_eq!(store.find_player(spelling).unwrap().as_ref(), Some(&player));
+        }
+        assert_eq!(store.find_player("lynx").unwrap(), None);
+    }
+
+    #[test]
+    fn snapshots_within_range_test() {
+        let store = Store::open_in_memory().unwrap();
+        let snapshots: Vec<SnapShot> = fixture("player/player_snapshots.json");
+        let snapshot = snapshots[0].clone();
+        let mut older = snapshot.clone();
+        older.id -= 1;
+        older.created_at = year(2023);
+
+        store
+            .upsert_snapshots(&[snapshot.clone(), older.clone()])
+            .unwrap();
+
+        let everything = DateRange::new(year(2020), year(2030)).unwrap();
+        assert_eq!(
+            store.snapshots(snapshot.player_id, &everything).unwrap(),
+            vec![older.clone(), snapshot.clone()]
+        );
+        let only_2023 = DateRange::new(year(2023), year(2024)).unwrap();
+        assert_eq!(
+            store.snapshots(snapshot.player_id, &only_2023).unwrap(),
+            vec![older]
+        );
+        assert_eq!(
+            store.latest_snapshot_at(snapshot.player_id).unwrap(),
+            Some(snapshot.created_at)
+        );
+    }
+
+    #[test]
+    fn name_changes_and_records_round_trip_test() {
+        let store = Store::open_in_memory().unwrap();
+        let name_changes: Vec<NameChange> = fixture("player/player_name_changes.json");
+        let records: Vec<Record> = fixture("player/player_records.json");
+
+        store.upsert_name_changes(&name_changes).unwrap();
+        store.upsert_records(&records).unwrap();
+
+        // The fixture repeats ids to show every review context, the last one wins
+        let player_id = name_changes[0].player_id;
+        let mut expected: Vec<_> = name_changes
+            .iter()
+            .rev()
+            .filter(|name_change| name_change.player_id == player_id)
+            .cloned()
+            .map(|mut name_change| {
+                name_change.player = None;
+                name_change
+            })
+            .collect();
+        expected.sort_by_key(|name_change| name_change.created_at);
+        expected.dedup_by_key(|name_change| name_change.id);
+        assert_eq!(store.name_changes(player_id).unwrap(), expected);
+
+        let player_id = records[0].player_id;
+        assert_eq!(store.records(player_id, None).unwrap().len(), records.len());
+        let overall = Metric::Skill(Skill::Overall);
+        assert!(store
+            .records(player_id, Some(&overall))
+            .unwrap()
+            .iter()
+            .all(|record| record.metric == overall));
+    }
+
+    #[tokio::test]
+    async fn sync_snapshots_since_last_stored_test() {
+        let snapshots: Vec<SnapShot> = fixture("player/player_snapshots.json");
+        let group: GroupDetail = fixture("group/group_detail.json");
+        let mut player = group.memberships[0].player.clone();
+        player.username = Username::new("zezima").unwrap();
+        player.id = snapshots[0].player_id;
+
+        let requests = Arc::new(Mutex::new(vec![]));
+        let wom_client = fake_client(requests.clone(), |_, _| {
+            (
+                StatusCode::OK,
+                fixture_bytes("player/player_snapshots.json"),
+            )
+        });
+        let store = Store::open_in_memory().unwrap();
+
+        let report = store
+            .sync_snapshots(&wom_client.player_client, &[player.clone()])
+            .await;
+
+        assert_eq!(report.players, 1);
+        assert_eq!(report.snapshots, 1);
+        assert!(report.failed.is_empty());
+        assert!(requests.lock().unwrap()[0]
+            .contains(&format!("startDate={}", to_iso_8601(&player.registered_at))));
+        assert_eq!(store.get_player(player.id).unwrap(), Some(player.clone()));
+
+        requests.lock().unwrap().clear();
+        store
+            .sync_snapshots(&wom_client.player_client, &[player])
+            .await;
+
+        let next_start = snapshots[0].created_at + Duration::milliseconds(1);
+        assert!(requests.lock().unwrap()[0].contains(&format!(
+            "startDate={}",
+            crate::query::to_iso_8601(&next_start)
+        )));
+    }
+
+    #[tokio::test]
+    async fn failed_page_keeps_the_range_for_the_next_sync_test() {
+        let snapshots: Vec<SnapShot> = fixture("player/player_snapshots.json");
+        let group: GroupDetail = fixture("group/group_detail.json");
+        let mut player = group.memberships[0].player.clone();
+        player.username = Username::new("zezima").unwrap();
+        player.id = snapshots[0].player_id;
+
+        // The first page arrives and the older one after it fails
+        let requests = Arc::new(Mutex::new(vec![]));
+        let pages = Arc::new(AtomicUsize::new(0));
+        let failing_client = fake_client(requests.clone(), move |_, _| {
+            match pages.fetch_add(1, Ordering::SeqCst) {
+                0 => (
+                    StatusCode::OK,
+                    fixture_bytes("player/player_snapshots.json"),
+                ),
+                _ => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    br#"{"message":"Failed to load snapshots."}"#.to_vec(),
+                ),
+            }
+        });
+        let store = Store::open_in_memory().unwrap();
+
+        let report = store
+            .sync_snapshots(&failing_client.player_client, &[player.clone()])
+            .await;
+
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+        assert_eq!(store.latest_snapshot_at(player.id).unwrap(), None);
+
+        // The next sync fetches the whole range again
+        requests.lock().unwrap().clear();
+        let wom_client = fake_client(requests.clone(), |_, _| {
+            (
+                StatusCode::OK,
+                fixture_bytes("player/player_snapshots.json"),
+            )
+        });
+        let report = store
+            .sync_snapshots(&wom_client.player_client, &[player.clone()])
+            .await;
+
+        assert_eq!(report.snapshots, 1);
+        assert!(requests.lock().unwrap()[0]
+            .contains(&format!("startDate={}", to_iso_8601(&player.registered_at))));
+        assert_eq!(
+            store.latest_snapshot_at(player.id).unwrap(),
+            Some(snapshots[0].created_at)
+        );
+    }
+}
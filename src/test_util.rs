@@ -0,0 +1,538 @@
+use crate::models::group::GroupDetail;
+use crate::models::player::PlayerDetails;
+use crate::WomClient;
+use reqwest::StatusCode;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Fixtures served for every endpoint the crate supports, with `:name` segments matching anything
+const FIXTURES: &[(&str, &str, &str)] = &[
+    (
+        "GET",
+        "/players/search",
+        include_str!("../tests/mocks/player/player_search.json"),
+    ),
+    (
+        "GET",
+        "/players/id/:id",
+        include_str!("../tests/mocks/player/player_details.json"),
+    ),
+    (
+        "POST",
+        "/players/:username/assert-type",
+        include_str!("../tests/mocks/player/player_assert_type.json"),
+    ),
+    (
+        "POST",
+        "/players/:username",
+        include_str!("../tests/mocks/player/player_details.json"),
+    ),
+    (
+        "GET",
+        "/players/:username",
+        include_str!("../tests/mocks/player/player_details.json"),
+    ),
+    (
+        "GET",
+        "/players/:username/achievements",
+        include_str!("../tests/mocks/player/player_achievements.json"),
+    ),
+    (
+        "GET",
+        "/players/:username/achievements/progress",
+        include_str!("../tests/mocks/player/player_achievement_progress.json"),
+    ),
+    (
+        "GET",
+        "/players/:username/competitions",
+        include_str!("../tests/mocks/player/player_competition_participation.json"),
+    ),
+    (
+        "GET",
+        "/players/:username/competitions/standings",
+        include_str!("../tests/mocks/player/player_competition_standings.json"),
+    ),
+    (
+        "GET",
+        "/players/:username/groups",
+        include_str!("../tests/mocks/player/player_group_membership.json"),
+    ),
+    (
+        "GET",
+        "/players/:username/gained",
+        include_str!("../tests/mocks/player/player_gains.json"),
+    ),
+    (
+        "GET",
+        "/players/:username/records",
+        include_str!("../tests/mocks/player/player_records.json"),
+    ),
+    (
+        "GET",
+        "/players/:username/snapshots",
+        include_str!("../tests/mocks/player/player_snapshots.json"),
+    ),
+    (
+        "GET",
+        "/players/:username/snapshots/timeline",
+        include_str!("../tests/mocks/player/player_snapshots_timeline.json"),
+    ),
+    (
+        "GET",
+        "/players/:username/names",
+        include_str!("../tests/mocks/player/player_name_changes.json"),
+    ),
+    (
+        "GET",
+        "/players/:username/archives",
+        include_str!("../tests/mocks/player/player_archives.json"),
+    ),
+    (
+        "POST",
+        "/competitions",
+        include_str!("../tests/mocks/competition/competition_create_response.json"),
+    ),
+    (
+        "GET",
+        "/groups",
+        include_str!("../tests/mocks/group/group_search.json"),
+    ),
+    (
+        "GET",
+        "/groups/:id",
+        include_str!("../tests/mocks/group/group_detail.json"),
+    ),
+    (
+        "GET",
+        "/groups/:id/name-changes",
+        include_str!("../tests/mocks/group/group_name_changes.json"),
+    ),
+    (
+        "GET",
+        "/efficiency/leaderboard",
+        include_str!("../tests/mocks/leaderboard/efficiency_leaderboard.json"),
+    ),
+    (
+        "GET",
+        "/records/leaderboard",
+        include_str!("../tests/mocks/leaderboard/records_leaderboard.json"),
+    ),
+    (
+        "GET",
+        "/deltas/leaderboard",
+        include_str!("../tests/mocks/leaderboard/deltas_leaderboard.json"),
+    ),
+];
+
+/// A request the mock server has received
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedRequest {
+    pub method: String,
+    /// The percent decoded path, without the query string
+    pub path: String,
+    pub query: Option<String>,
+}
+
+struct Route {
+    method: String,
+    path: String,
+    status: StatusCode,
+    body: String,
+}
+
+impl Route {
+    fn matches(&self, method: &str, path: &str) -> bool {
+        let pattern: Vec<&str> = self.path.trim_matches('/').split('/').collect();
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        self.method.eq_ignore_ascii_case(method)
+            && pattern.len() == segments.len()
+            && pattern.iter().zip(&segments).all(|(pattern, segment)| {
+                pattern.starts_with(':') || pattern.eq_ignore_ascii_case(segment)
+            })
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    /// Routes added through the builder methods, checked newest first and before the fixtures
+    overrides: Vec<Route>,
+    fixtures: Vec<Route>,
+    failures: VecDeque<StatusCode>,
+    requests: Vec<ReceivedRequest>,
+}
+
+impl MockState {
+    fn respond(&mut self, method: &str, path: &str) -> (StatusCode, String) {
+        if let Some(status) = self.failures.pop_front() {
+            return (status, error_body(status));
+        }
+        self.overrides
+            .iter()
+            .rev()
+            .chain(self.fixtures.iter())
+            .find(|route| route.matches(method, path))
+            .map(|route| (route.status, route.body.clone()))
+            .unwrap_or_else(|| (StatusCode::NOT_FOUND, error_body(StatusCode::NOT_FOUND)))
+    }
+}
+
+fn error_body(status: StatusCode) -> String {
+    serde_json::json!({ "message": status.canonical_reason().unwrap_or("Error") }).to_string()
+}
+
+/// A local Wise Old Man server preloaded with fixtures for every endpoint the crate supports.
+/// Point a client at it with [WomClient::new_with_base_url] or [MockWom::client].
+///
+/// ```no_run
+/// use wom_rs::test_util::MockWom;
+/// use reqwest::StatusCode;
+///
+/// let server = MockWom::start().fail_next(StatusCode::TOO_MANY_REQUESTS);
+/// let wom_client = server.client();
+/// ```
+pub struct MockWom {
+    address: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl MockWom {
+    /// Starts the server on a random local port
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind the mock server");
+        let address = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(MockState {
+            fixtures: FIXTURES
+                .iter()
+                .map(|(method, path, body)| Route {
+                    method: method.to_string(),
+                    path: path.to_string(),
+                    status: StatusCode::OK,
+                    body: body.to_string(),
+                })
+                .collect(),
+            ..Default::default()
+        }));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let server_state = state.clone();
+        let server_shutdown = shutdown.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if server_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let state = server_state.clone();
+                    thread::spawn(move || handle_connection(stream, state));
+                }
+            }
+        });
+
+        Self {
+            address,
+            state,
+            shutdown,
+        }
+    }
+
+    /// The base url to give to [WomClient::new_with_base_url]
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// A [WomClient] pointed at this server
+    pub fn client(&self) -> WomClient {
+        WomClient::new_with_base_url(self.base_url(), None)
+    }
+
+    /// Serves `player` for its username and id, on both the details and update endpoints
+    pub fn with_player(self, player: PlayerDetails) -> Self {
+        let body = serde_json::to_string(&player).unwrap();
+        for (method, path) in [
+            ("GET", format!("/players/{}", player.username)),
+            ("POST", format!("/players/{}", player.username)),
+            ("GET", format!("/players/id/{}", player.id)),
+        ] {
+            self.add_route(method, &path, StatusCode::OK, body.clone());
+        }
+        self
+    }
+
+    /// Serves `group` on the group details endpoint for its id
+    pub fn with_group(self, group: GroupDetail) -> Self {
+        let body = serde_json::to_string(&group).unwrap();
+        self.add_route(
+            "GET",
+            &format!("/groups/{}", group.id),
+            StatusCode::OK,
+            body,
+        );
+        self
+    }
+
+    /// Serves `body` with `status` for a method and path, `:name` segments in the path match anything
+    pub fn with_response(
+        self,
+        method: &str,
+        path: &str,
+        status: StatusCode,
+        body: impl Into<String>,
+    ) -> Self {
+        self.add_route(method, path, status, body.into());
+        self
+    }
+
+    /// Answers the next request with `status` and a WOM style error body, whatever the endpoint.
+    /// Can be called multiple times to fail several requests in a row
+    pub fn fail_next(self, status: StatusCode) -> Self {
+        self.state.lock().unwrap().failures.push_back(status);
+        self
+    }
+
+    /// Every request the server has received so far, oldest first
+    pub fn received_requests(&self) -> Vec<ReceivedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    fn add_route(&self, method: &str, path: &str, status: StatusCode, body: String) {
+        self.state.lock().unwrap().overrides.push(Route {
+            method: method.to_string(),
+            path: path.to_string(),
+            status,
+            body,
+        });
+    }
+}
+
+impl Drop for MockWom {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wakes the accept loop up so it can see the shutdown flag
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: Arc<Mutex<MockState>>) {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return;
+    };
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).is_err() || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    let _ = reader.read_exact(&mut body);
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (percent_decode(path), Some(query.to_string())),
+        None => (percent_decode(target), None),
+    };
+    let (status, body) = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(ReceivedRequest {
+            method: method.to_string(),
+            path: path.clone(),
+            query,
+        });
+        state.respond(method, &path)
+    };
+
+    let response = format!(
+        "HTTP/1.1 {} {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or(""),
+        body.len(),
+        body
+    );
+    let mut stream = stream;
+    let _ = stream.write_all(response.as_bytes());
+    let _ = stream.flush();
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' if index + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        index += 3;
+                    }
+                    None => {
+                        decoded.push(b'%');
+                        index += 1;
+                    }
+                }
+            }
+            byte => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{percent_decode, MockWom};
+    use crate::models::error::ApiError;
+    use crate::models::global_enums::Period;
+    use crate::models::global_types::{GroupId, PlayerId, Username};
+    use reqwest::StatusCode;
+
+    #[tokio::test]
+    async fn serves_fixtures_test() {
+        let server = MockWom::start();
+        let wom_client = server.client();
+
+        let details = wom_client
+            .player_client
+            .get_details("Zezima".to_string())
+            .await;
+        let gains = wom_client
+            .player_client
+            .get_gains_by_period("IFat Fingers".to_string(), Period::Week)
+            .await;
+        let group = wom_client.group_client.get_group_details(1).await;
+
+        assert!(details.is_ok());
+        assert!(gains.is_ok());
+        assert!(group.is_ok());
+        let requests = server.received_requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].path, "/players/IFat Fingers/gained");
+        assert_eq!(requests[1].query.as_deref(), Some("period=week"));
+    }
+
+    #[tokio::test]
+    async fn with_player_test() {
+        let fixture = MockWom::start()
+            .client()
+            .player_client
+            .get_details("zezima".to_string())
+            .await
+            .unwrap();
+        let mut player = fixture.clone();
+        player.id = PlayerId(42);
+        player.username = Username::new("lynx titan").unwrap();
+        player.display_name = "Lynx Titan".to_string();
+
+        let server = MockWom::start().with_player(player);
+        let wom_client = server.client();
+
+        let by_name = wom_client
+            .player_client
+            .get_details("Lynx Titan".to_string())
+            .await
+            .unwrap();
+        let by_id = wom_client
+            .player_client
+            .get_details_by_id(42)
+            .await
+            .unwrap();
+        let other = wom_client
+            .player_client
+            .get_details("zezima".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(by_name.display_name, "Lynx Titan");
+        assert_eq!(by_id.id, 42);
+        assert_eq!(other, fixture);
+    }
+
+    #[tokio::test]
+    async fn with_group_test() {
+        let mut group = MockWom::start()
+            .client()
+            .group_client
+            .get_group_details(1)
+            .await
+            .unwrap();
+        group.id = GroupId(7);
+        group.name = "Seven".to_string();
+
+        let server = MockWom::start().with_group(group);
+        let result = server.client().group_client.get_group_details(7).await;
+
+        assert_eq!(result.unwrap().name, "Seven");
+    }
+
+    #[tokio::test]
+    async fn fail_next_test() {
+        let server = MockWom::start()
+            .fail_next(StatusCode::TOO_MANY_REQUESTS)
+            .fail_next(StatusCode::INTERNAL_SERVER_ERROR);
+        let wom_client = server.client();
+
+        let first = wom_client
+            .player_client
+            .get_details("zezima".to_string())
+            .await;
+        let second = wom_client.group_client.get_group_details(1).await;
+        let third = wom_client
+            .player_client
+            .get_details("zezima".to_string())
+            .await;
+
+        let first_error = first.unwrap_err();
+        assert_eq!(first_error.downcast_ref::<ApiError>().unwrap().status, 429);
+        let second_error = second.unwrap_err();
+        assert_eq!(second_error.downcast_ref::<ApiError>().unwrap().status, 500);
+        assert!(third.is_ok());
+    }
+
+    #[tokio::test]
+    async fn with_response_test() {
+        let server = MockWom::start().with_response(
+            "GET",
+            "/players/nobody",
+            StatusCode::NOT_FOUND,
+            r#"{"message": "Player not found."}"#,
+        );
+
+        let result = server
+            .client()
+            .player_client
+            .get_details("nobody".to_string())
+            .await;
+
+        let error = result.unwrap_err();
+        assert!(error.downcast_ref::<ApiError>().unwrap().is_not_found());
+        assert_eq!(error.to_string(), "Player not found.");
+    }
+
+    #[test]
+    fn percent_decode_test() {
+        assert_eq!(percent_decode("lynx%20titan"), "lynx titan");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+        // A multi-byte character after the percent sign is kept rather than sliced
+        assert_eq!(percent_decode("%aé"), "%aé");
+    }
+}
@@ -0,0 +1,499 @@
+use anyhow::anyhow;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Header the api key is sent in, its value is never written to a cassette or a trace
+pub(crate) const API_KEY_HEADER: &str = "x-api-key";
+const REDACTED: &str = "[REDACTED]";
+
+/// A request to the api as built by the clients
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl Request {
+    pub fn new(method: Method, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: vec![],
+            body: None,
+        }
+    }
+
+    pub fn get(url: impl Into<String>) -> Self {
+        Self::new(Method::GET, url)
+    }
+
+    pub fn post(url: impl Into<String>) -> Self {
+        Self::new(Method::POST, url)
+    }
+
+    /// The request's headers with the api key replaced, safe to write to a cassette or a trace
+    pub(crate) fn redacted_headers(&self) -> Vec<(String, String)> {
+        self.headers
+            .iter()
+            .map(|(name, value)| {
+                if name.eq_ignore_ascii_case(API_KEY_HEADER) {
+                    (name.clone(), REDACTED.to_string())
+                } else {
+                    (name.clone(), value.clone())
+                }
+            })
+            .collect()
+    }
+}
+
+/// The status, headers and body the api answered a [Request] with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: StatusCode,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Looks up a header by name, ignoring case
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// How requests from the clients reach the api.
+/// Implement this to add middleware such as logging or caching, to run on another HTTP stack,
+/// or to fake the api in tests without opening a socket.
+/// Any `Fn(Request) -> impl Future<Output = Result<Response, anyhow::Error>>` closure is a transport.
+pub trait Transport: Send + Sync + 'static {
+    fn send(
+        &self,
+        request: Request,
+    ) -> impl Future<Output = Result<Response, anyhow::Error>> + Send;
+}
+
+impl<F, Fut> Transport for F
+where
+    F: Fn(Request) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Response, anyhow::Error>> + Send,
+{
+    fn send(
+        &self,
+        request: Request,
+    ) -> impl Future<Output = Result<Response, anyhow::Error>> + Send {
+        self(request)
+    }
+}
+
+/// The default transport, sends requests over HTTP with reqwest
+#[derive(Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl From<reqwest::Client> for ReqwestTransport {
+    fn from(client: reqwest::Client) -> Self {
+        Self::new(client)
+    }
+}
+
+impl Transport for ReqwestTransport {
+    async fn send(&self, request: Request) -> Result<Response, anyhow::Error> {
+        send_http(&self.client, request).await
+    }
+}
+
+/// Sends requests through another transport and writes every request and response to the cassette
+pub struct RecordingTransport<T: Transport = ReqwestTransport> {
+    inner: T,
+    cassette: Arc<Cassette>,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    pub fn new(inner: T, cassette: Arc<Cassette>) -> Self {
+        Self { inner, cassette }
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    async fn send(&self, request: Request) -> Result<Response, anyhow::Error> {
+        let response = self.inner.send(request.clone()).await?;
+        self.cassette.record_interaction(&request, &response)?;
+        Ok(response)
+    }
+}
+
+/// Answers requests from the cassette without touching the network
+pub struct ReplayTransport {
+    cassette: Arc<Cassette>,
+}
+
+impl ReplayTransport {
+    pub fn new(cassette: Arc<Cassette>) -> Self {
+        Self { cassette }
+    }
+}
+
+impl Transport for ReplayTransport {
+    async fn send(&self, request: Request) -> Result<Response, anyhow::Error> {
+        self.cassette.replay_interaction(&request)
+    }
+}
+
+async fn send_http(client: &reqwest::Client, request: Request) -> Result<Response, anyhow::Error> {
+    let mut builder = client.request(request.method, request.url);
+    for (name, value) in request.headers {
+        builder = builder.header(name, value);
+    }
+    if let Some(body) = request.body {
+        builder = builder
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body);
+    }
+
+    let response = builder.send().await?;
+    let status = response.status();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).to_string(),
+            )
+        })
+        .collect();
+    let body = response.bytes().await?.to_vec();
+    Ok(Response {
+        status,
+        headers,
+        body,
+    })
+}
+
+/// A recorded request, the url is kept relative to the base url so cassettes replay against any host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+/// A recorded response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// One request and the response it got
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// A file of recorded interactions with the api.
+/// When recording the file is rewritten after every request, so a failing test still leaves what it captured.
+/// When replaying each interaction answers one matching request, in the order they were recorded
+pub struct Cassette {
+    path: PathBuf,
+    base_url: String,
+    interactions: Mutex<Vec<(Interaction, bool)>>,
+}
+
+impl Cassette {
+    /// Starts a new, empty cassette at `path`, replacing any existing file
+    pub fn record(path: impl AsRef<Path>, base_url: &str) -> Result<Self, anyhow::Error> {
+        let cassette = Self {
+            path: path.as_ref().to_path_buf(),
+            base_url: base_url.to_string(),
+            interactions: Mutex::new(vec![]),
+        };
+        cassette.save()?;
+        Ok(cassette)
+    }
+
+    /// Loads a recorded cassette from `path`
+    pub fn load(path: impl AsRef<Path>, base_url: &str) -> Result<Self, anyhow::Error> {
+        let contents = fs::read_to_string(path.as_ref())?;
+        let file: CassetteFile = serde_json::from_str(&contents)?;
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            base_url: base_url.to_string(),
+            interactions: Mutex::new(
+                file.interactions
+                    .into_iter()
+                    .map(|interaction| (interaction, false))
+                    .collect(),
+            ),
+        })
+    }
+
+    /// Every interaction in the cassette
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(interaction, _)| interaction.clone())
+            .collect()
+    }
+
+    fn relative_path(&self, url: &str) -> String {
+        url.strip_prefix(&self.base_url).unwrap_or(url).to_string()
+    }
+
+    fn recorded_request(&self, request: &Request) -> RecordedRequest {
+        RecordedRequest {
+            method: request.method.to_string(),
+            path: self.relative_path(&request.url),
+            headers: request.redacted_headers(),
+            body: request.body.clone(),
+        }
+    }
+
+    fn record_interaction(
+        &self,
+        request: &Request,
+        response: &Response,
+    ) -> Result<(), anyhow::Error> {
+        let interaction = Interaction {
+            request: self.recorded_request(request),
+            response: RecordedResponse {
+                status: response.status.as_u16(),
+                headers: response.headers.clone(),
+                body: String::from_utf8_lossy(&response.body).to_string(),
+            },
+        };
+        self.interactions.lock().unwrap().push((interaction, true));
+        self.save()
+    }
+
+    fn replay_interaction(&self, request: &Request) -> Result<Response, anyhow::Error> {
+        let recorded = self.recorded_request(request);
+        let mut interactions = self.interactions.lock().unwrap();
+        let (interaction, used) = interactions
+            .iter_mut()
+            .find(|(interaction, used)| {
+                !*used
+                    && interaction.request.method == recorded.method
+                    && interaction.request.path == recorded.path
+                    && interaction.request.body == recorded.body
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "No recorded interaction for {} {} in {}",
+                    recorded.method,
+                    recorded.path,
+                    self.path.display()
+                )
+            })?;
+        *used = true;
+        Ok(Response {
+            status: StatusCode::from_u16(interaction.response.status)?,
+            headers: interaction.response.headers.clone(),
+            body: interaction.response.body.clone().into_bytes(),
+        })
+    }
+
+    fn save(&self) -> Result<(), anyhow::Error> {
+        let file = CassetteFile {
+            interactions: self.interactions(),
+        };
+        fs::write(&self.path, serde_json::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::{
+        Cassette, RecordingTransport, ReplayTransport, Request, ReqwestTransport, Response,
+        Transport,
+    };
+    use crate::WomClient;
+    use httpmock::prelude::*;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const CONTENT_TYPE: &str = "content-type";
+    const APPLICATION_JSON: &str = "application/json";
+
+    fn cassette_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("wom_rs_{}_{}.json", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn record_then_replay_test() {
+        let path = cassette_path("record_then_replay");
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/players/zezima")
+                .header("x-api-key", "super secret");
+            then.status(200)
+                .header(CONTENT_TYPE, APPLICATION_JSON)
+                .body_from_file("./tests/mocks/player/player_details.json");
+        });
+
+        let recording =
+            WomClient::new_recording(server.base_url(), Some("super secret".to_string()), &path)
+                .unwrap();
+        let recorded = recording
+            .player_client
+            .get_details("zezima".to_string())
+            .await
+            .unwrap();
+        mock.assert();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("super secret"));
+        assert!(!contents.contains(&server.base_url()));
+
+        let replaying = WomClient::new_replaying(&path).unwrap();
+        let replayed = replaying
+            .player_client
+            .get_details("zezima".to_string())
+            .await
+            .unwrap();
+        assert_eq!(recorded, replayed);
+        mock.assert_hits(1);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn replay_unmatched_request_fails_test() {
+        let path = cassette_path("replay_unmatched");
+        std::fs::write(&path, r#"{"interactions": []}"#).unwrap();
+
+        let replaying = WomClient::new_replaying(&path).unwrap();
+        let result = replaying.group_client.get_group_details(1).await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("No recorded interaction for GET /groups/1"));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn api_key_header_is_redacted_test() {
+        let path = cassette_path("redacted_header");
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/groups/1");
+            then.status(404)
+                .header(CONTENT_TYPE, APPLICATION_JSON)
+                .body(r#"{"message": "Group not found."}"#);
+        });
+
+        let cassette = Arc::new(Cassette::record(&path, &server.base_url()).unwrap());
+        let transport = RecordingTransport::new(
+            ReqwestTransport::new(reqwest::Client::new()),
+            cassette.clone(),
+        );
+        let mut request = Request::get(format!("{}/groups/1", server.base_url()));
+        request
+            .headers
+            .push(("x-api-key".to_string(), "super secret".to_string()));
+        let response = transport.send(request.clone()).await.unwrap();
+        assert_eq!(response.status.as_u16(), 404);
+
+        let interactions = cassette.interactions();
+        assert_eq!(interactions.len(), 1);
+        assert_eq!(
+            interactions[0].request.headers,
+            vec![("x-api-key".to_string(), "[REDACTED]".to_string())]
+        );
+
+        let replay =
+            ReplayTransport::new(Arc::new(Cassette::load(&path, &server.base_url()).unwrap()));
+        let replayed = replay.send(request.clone()).await.unwrap();
+        assert_eq!(replayed.body, response.body);
+        assert!(replay.send(request).await.is_err());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn closure_transport_test() {
+        let transport = |request: Request| async move {
+            assert_eq!(request.url, "http://fake/players/zezima");
+            Ok(Response {
+                status: reqwest::StatusCode::OK,
+                headers: vec![],
+                body: std::fs::read("./tests/mocks/player/player_details.json")?,
+            })
+        };
+
+        let wom_client = WomClient::new_with_transport(transport, "http://fake".to_string());
+        let result = wom_client
+            .player_client
+            .get_details("zezima".to_string())
+            .await;
+
+        assert_eq!(result.unwrap().display_name, "Zezima");
+    }
+
+    #[tokio::test]
+    async fn middleware_transport_test() {
+        struct CountingTransport<T: Transport> {
+            inner: T,
+            count: Arc<AtomicUsize>,
+        }
+
+        impl<T: Transport> Transport for CountingTransport<T> {
+            async fn send(&self, request: Request) -> Result<Response, anyhow::Error> {
+                self.count.fetch_add(1, Ordering::SeqCst);
+                self.inner.send(request).await
+            }
+        }
+
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/groups/1");
+            then.status(200)
+                .header(CONTENT_TYPE, APPLICATION_JSON)
+                .body_from_file("./tests/mocks/group/group_detail.json");
+        });
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let transport = CountingTransport {
+            inner: ReqwestTransport::new(reqwest::Client::new()),
+            count: count.clone(),
+        };
+        let wom_client = WomClient::new_with_transport(transport, server.base_url());
+        let result = wom_client.group_client.get_group_details(1).await;
+
+        mock.assert();
+        assert!(result.is_ok());
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,287 @@
+#![cfg(feature = "cli")]
+
+use httpmock::prelude::*;
+use std::process::{Command, Output};
+
+const CONTENT_TYPE: &str = "content-type";
+const APPLICATION_JSON: &str = "application/json";
+
+fn wom(server: &MockServer, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_wom"))
+        .env_remove("WOM_API_KEY")
+        .env("WOM_BASE_URL", server.base_url())
+        .args(args)
+        .output()
+        .expect("failed to run wom")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn player_details_table_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/players/zezima");
+        then.status(200)
+            .header(CONTENT_TYPE, APPLICATION_JSON)
+            .body_from_file("./tests/mocks/player/player_details.json");
+    });
+
+    let output = wom(&server, &["player", "details", "zezima"]);
+
+    mock.assert();
+    assert!(output.status.success());
+    let table = stdout(&output);
+    assert!(table.starts_with("field"));
+    assert!(table
+        .lines()
+        .any(|line| line.starts_with("displayName") && line.ends_with("Zezima")));
+    assert!(table
+        .lines()
+        .any(|line| line.starts_with("latestSnapshot.data.skills.attack.level")));
+}
+
+#[test]
+fn player_details_json_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/players/zezima");
+        then.status(200)
+            .header(CONTENT_TYPE, APPLICATION_JSON)
+            .body_from_file("./tests/mocks/player/player_details.json");
+    });
+
+    let output = wom(
+        &server,
+        &["--format", "json", "player", "details", "zezima"],
+    );
+
+    mock.assert();
+    assert!(output.status.success());
+    let details: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(details["id"], 1);
+    assert_eq!(details["displayName"], "Zezima");
+}
+
+#[test]
+fn player_update_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST).path("/players/zezima");
+        then.status(200)
+            .header(CONTENT_TYPE, APPLICATION_JSON)
+            .body_from_file("./tests/mocks/player/player_details.json");
+    });
+
+    let output = wom(&server, &["player", "update", "zezima"]);
+
+    mock.assert();
+    assert!(output.status.success());
+}
+
+#[test]
+fn player_gains_by_period_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/players/zezima/gained")
+            .query_param("period", "five_min");
+        then.status(200)
+            .header(CONTENT_TYPE, APPLICATION_JSON)
+            .body_from_file("./tests/mocks/player/player_gains.json");
+    });
+
+    let output = wom(
+        &server,
+        &["player", "gains", "zezima", "--period", "five_min"],
+    );
+
+    mock.assert();
+    assert!(output.status.success());
+}
+
+#[test]
+fn player_records_with_metric_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/players/zezima/records")
+            .query_param("period", "week")
+            .query_param("metric", "zulrah");
+        then.status(200)
+            .header(CONTENT_TYPE, APPLICATION_JSON)
+            .body_from_file("./tests/mocks/player/player_records.json");
+    });
+
+    let output = wom(
+        &server,
+        &[
+            "player", "records", "zezima", "--period", "week", "--metric", "zulrah",
+        ],
+    );
+
+    mock.assert();
+    assert!(output.status.success());
+}
+
+#[test]
+fn player_names_csv_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/players/zezima/names");
+        then.status(200)
+            .header(CONTENT_TYPE, APPLICATION_JSON)
+            .body_from_file("./tests/mocks/player/player_name_changes.json");
+    });
+
+    let output = wom(&server, &["--format", "csv", "player", "names", "zezima"]);
+
+    mock.assert();
+    assert!(output.status.success());
+    let csv = stdout(&output);
+    let header = csv.lines().next().unwrap();
+    assert!(header.split(',').any(|column| column == "oldName"));
+    assert_eq!(csv.lines().count(), 9);
+}
+
+#[test]
+fn group_search_csv_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/groups")
+            .query_param("name", "Lead")
+            .query_param("limit", "5");
+        then.status(200)
+            .header(CONTENT_TYPE, APPLICATION_JSON)
+            .body_from_file("./tests/mocks/group/group_search.json");
+    });
+
+    let output = wom(
+        &server,
+        &["-f", "csv", "group", "search", "Lead", "--limit", "5"],
+    );
+
+    mock.assert();
+    assert!(output.status.success());
+    let csv = stdout(&output);
+    let mut lines = csv.lines();
+    let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+    let mut sorted = header.clone();
+    sorted.sort();
+    assert_eq!(header, sorted);
+    let column = |line: &str, name: &str| {
+        let index = header.iter().position(|column| *column == name).unwrap();
+        line.split(',').nth(index).unwrap().to_string()
+    };
+    let first = lines.next().unwrap();
+    assert_eq!(column(first, "id"), "3578");
+    assert_eq!(column(first, "name"), "Lead The Pack");
+    assert_eq!(column(first, "clanChat"), "LTP");
+    assert_eq!(column(lines.next().unwrap(), "name"), "The Garage");
+}
+
+#[test]
+fn group_details_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/groups/1");
+        then.status(200)
+            .header(CONTENT_TYPE, APPLICATION_JSON)
+            .body_from_file("./tests/mocks/group/group_detail.json");
+    });
+
+    let output = wom(&server, &["group", "details", "1"]);
+
+    mock.assert();
+    assert!(output.status.success());
+}
+
+#[test]
+fn group_name_changes_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/groups/1/name-changes");
+        then.status(200)
+            .header(CONTENT_TYPE, APPLICATION_JSON)
+            .body_from_file("./tests/mocks/group/group_name_changes.json");
+    });
+
+    let output = wom(&server, &["-f", "json", "group", "name-changes", "1"]);
+
+    mock.assert();
+    assert!(output.status.success());
+    let name_changes: Vec<serde_json::Value> = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(name_changes.len(), 2);
+}
+
+#[test]
+fn api_key_flag_is_sent_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/players/zezima")
+            .header("x-api-key", "secret");
+        then.status(200)
+            .header(CONTENT_TYPE, APPLICATION_JSON)
+            .body_from_file("./tests/mocks/player/player_details.json");
+    });
+
+    let output = wom(
+        &server,
+        &["--api-key", "secret", "player", "details", "zezima"],
+    );
+
+    mock.assert();
+    assert!(output.status.success());
+}
+
+#[test]
+fn not_found_exit_code_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/players/nobody");
+        then.status(404)
+            .header(CONTENT_TYPE, APPLICATION_JSON)
+            .body(r#"{"message": "Player not found."}"#);
+    });
+
+    let output = wom(&server, &["player", "details", "nobody"]);
+
+    mock.assert();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Player not found."));
+}
+
+#[test]
+fn rate_limited_exit_code_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/players/zezima");
+        then.status(429)
+            .header(CONTENT_TYPE, APPLICATION_JSON)
+            .body(r#"{"message": "Too many requests."}"#);
+    });
+
+    let output = wom(&server, &["player", "details", "zezima"]);
+
+    mock.assert();
+    assert_eq!(output.status.code(), Some(5));
+}
+
+#[test]
+fn usage_error_exit_code_test() {
+    let server = MockServer::start();
+
+    let output = wom(&server, &["player", "gains", "zezima"]);
+
+    assert_eq!(output.status.code(), Some(2));
+
+    // Invalid names are rejected before anything is sent
+    let output = wom(&server, &["player", "details", "not a valid name"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid username"));
+}